@@ -5,13 +5,17 @@
 use crate::context::variable::SessionVariableProvider;
 #[cfg(feature = "plugin")]
 use crate::data_source::connector_plugin;
-#[cfg(any(feature = "postgres", feature = "mysql"))]
+#[cfg(feature = "database")]
 use crate::data_source::database;
 #[cfg(feature = "deltalake")]
 use crate::data_source::deltalake;
 #[cfg(feature = "flight")]
 use crate::data_source::flight_stream;
-use crate::data_source::{csv, json, local_fs, location, nd_json, object_store, parquet};
+#[cfg(feature = "iceberg")]
+use crate::data_source::iceberg;
+use crate::data_source::{csv, json, location, nd_json, object_store, parquet};
+#[cfg(feature = "orc")]
+use crate::data_source::{orc, transport::http};
 #[cfg(feature = "plugin")]
 use crate::request::body::PluginOption;
 use crate::request::body::{
@@ -26,7 +30,7 @@ use chrono::{DateTime, Utc};
 use datafusion::{
     arrow::{compute, datatypes::SchemaRef, record_batch::RecordBatch},
     dataframe::DataFrame,
-    execution::context,
+    execution::{context, runtime_env::RuntimeEnvBuilder},
     logical_expr::{col, JoinType},
     scalar::ScalarValue,
 };
@@ -50,10 +54,21 @@ impl SessionContext {
         config: context::SessionConfig,
         keep_alive: Option<i64>,
     ) -> Result<Self, ResponseError> {
-        let df_ctx = context::SessionContext::new_with_config(config);
+        // external databases appear in `information_schema`
+        #[cfg(feature = "database")]
+        let config = config.with_information_schema(true);
+
+        let runtime = RuntimeEnvBuilder::new()
+            .with_object_store_registry(Arc::new(object_store::registry::StoreRegistry::default()))
+            .build_arc()?;
+
+        let df_ctx = context::SessionContext::new_with_config_rt(config, runtime);
 
         object_store::registry::register(&df_ctx)?;
 
+        #[cfg(feature = "database")]
+        database::catalog::register(&df_ctx);
+
         let last_accessed_at = Utc::now();
         let data_source_map = HashMap::<String, DataSource>::new();
 
@@ -115,6 +130,10 @@ pub trait Session: Send + Sync + 'static {
     ) -> Result<(), ResponseError>;
     #[cfg(feature = "deltalake")]
     async fn append_from_deltalake(&self, data_source: &DataSource) -> Result<(), ResponseError>;
+    #[cfg(feature = "iceberg")]
+    async fn append_from_iceberg(&self, data_source: &DataSource) -> Result<(), ResponseError>;
+    #[cfg(feature = "database")]
+    async fn append_from_database(&self, data_source: &DataSource) -> Result<(), ResponseError>;
     #[cfg(feature = "plugin")]
     async fn append_from_connector_plugin(
         &self,
@@ -127,8 +146,20 @@ pub trait Session: Send + Sync + 'static {
         name: &str,
         data: bytes::Bytes,
     ) -> Result<(), ResponseError>;
+    #[cfg(feature = "orc")]
+    async fn append_from_orc_rest(&self, data_source: &DataSource) -> Result<(), ResponseError>;
+    #[cfg(feature = "orc")]
+    async fn append_from_orc_bytes(
+        &self,
+        name: &str,
+        data: bytes::Bytes,
+    ) -> Result<(), ResponseError>;
     async fn save_to_object_store(&self, data_source: &DataSource) -> Result<(), ResponseError>;
+    #[deprecated(note = "use `save_to_object_store`, which saves the local files of any format")]
+    #[allow(dead_code)]
     async fn save_to_file(&self, data_source: &DataSource) -> Result<(), ResponseError>;
+    #[cfg(feature = "database")]
+    async fn save_to_database(&self, data_source: &DataSource) -> Result<(), ResponseError>;
     async fn remove_data_source(&self, name: &str) -> Result<(), ResponseError>;
     async fn append_variables(&self, variables: &Variables) -> Result<(), ResponseError>;
     async fn execute_merge_processor(
@@ -136,6 +167,10 @@ pub trait Session: Send + Sync + 'static {
         merge_processor: &MergeProcessor,
     ) -> Result<(), ResponseError>;
     async fn execute_logical_plan(&self, sql: &str) -> Result<DataFrame, ResponseError>;
+    #[cfg(feature = "database")]
+    async fn refresh_database_schema(&self, sql: &str) -> Result<bool, ResponseError>;
+    #[cfg(feature = "database")]
+    async fn identity(&self) -> Option<Arc<database::impersonation::DatabaseIdentity>>;
 }
 
 #[async_trait]
@@ -415,7 +450,82 @@ impl Session for ConcurrentSessionContext {
             None => DataSourceOption::default(),
         };
 
-        let record_batches = deltalake::to_record_batch(&data_source.location, &options)?;
+        let table = deltalake::DeltaTable::try_new(&data_source.location, &options).await?;
+
+        self.exists_data_source(data_source).await?;
+
+        self.touch().await;
+        {
+            let session = &mut self.write().await;
+
+            session
+                .df_ctx
+                .register_table(&data_source.name, Arc::new(table))?;
+
+            session
+                .data_source_map
+                .insert(data_source.name.clone(), data_source.clone());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "iceberg")]
+    async fn append_from_iceberg(&self, data_source: &DataSource) -> Result<(), ResponseError> {
+        let options = match &data_source.options {
+            Some(o) => o.clone(),
+            None => DataSourceOption::default(),
+        };
+
+        let table = iceberg::to_table_provider(&data_source.location, &options).await?;
+
+        self.exists_data_source(data_source).await?;
+
+        self.touch().await;
+        {
+            let session = &mut self.write().await;
+
+            session.df_ctx.register_table(&data_source.name, table)?;
+
+            session
+                .data_source_map
+                .insert(data_source.name.clone(), data_source.clone());
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "database")]
+    async fn append_from_database(&self, data_source: &DataSource) -> Result<(), ResponseError> {
+        let Some(sql) = data_source
+            .options
+            .as_ref()
+            .and_then(|options| options.sql.as_ref())
+        else {
+            return Err(ResponseError::request_validation(
+                "Must be required 'sql' option in 'database' data source",
+            ));
+        };
+
+        // bind parameters are resolved by the session variables
+        let (variables, identity) = {
+            let df_ctx = &self.read().await.df_ctx;
+            (
+                df_ctx
+                    .state()
+                    .execution_props()
+                    .get_var_provider(datafusion::variable::VarType::UserDefined),
+                database::impersonation::DatabaseIdentity::from_context(df_ctx),
+            )
+        };
+
+        let record_batches = database::passthrough::execute(
+            &data_source.location,
+            sql,
+            variables,
+            identity.as_deref(),
+        )
+        .await?;
 
         Self::register_record_batch(self, data_source, &record_batches).await?;
 
@@ -486,52 +596,75 @@ impl Session for ConcurrentSessionContext {
         Ok(())
     }
 
-    async fn save_to_object_store(&self, data_source: &DataSource) -> Result<(), ResponseError> {
-        self.touch().await;
-        let session = &mut self.read().await;
-        object_store::writer::write(&session.df_ctx, data_source).await?;
-        Ok(())
-    }
-
-    async fn save_to_file(&self, data_source: &DataSource) -> Result<(), ResponseError> {
-        assert_ne!(
-            data_source.format,
-            DataSourceFormat::Json,
-            "Can use only for JSON format by `save_to_file()`"
-        );
-
-        let mut file_path = std::path::PathBuf::from(&Settings::global().server.data_dir);
-        file_path.push(location::uri::to_file_path_and_name(&data_source.location)?);
-
+    #[cfg(feature = "orc")]
+    async fn append_from_orc_rest(&self, data_source: &DataSource) -> Result<(), ResponseError> {
         let options = match &data_source.options {
             Some(options) => options.clone(),
             None => DataSourceOption::default(),
         };
 
-        if !options.overwrite.unwrap_or(false) && file_path.exists() {
-            return Err(ResponseError::already_existing(format!(
-                "Already exists '{}' in local file system",
-                &data_source.location
-            )));
-        }
+        let data = http::get_bytes(&data_source.location, &options).await?;
+
+        self.exists_data_source(data_source).await?;
+
+        self.touch().await;
+        {
+            let session = &mut self.write().await;
+
+            orc::register_bytes(&session.df_ctx, data_source, data).await?;
 
-        if let Some(path) = file_path.as_path().parent() {
-            local_fs::fs::mkdir_if_not_exists(path, true)?;
+            session
+                .data_source_map
+                .insert(data_source.name.clone(), data_source.clone());
         }
 
-        log::debug!("save record batches to {:?}", file_path.to_str().unwrap());
+        Ok(())
+    }
+
+    #[cfg(feature = "orc")]
+    async fn append_from_orc_bytes(
+        &self,
+        name: &str,
+        data: bytes::Bytes,
+    ) -> Result<(), ResponseError> {
+        let data_source = DataSource::new(DataSourceFormat::Orc, name, None);
+
+        self.exists_data_source(&data_source).await?;
 
         self.touch().await;
         {
-            let session = &mut self.read().await;
-            let data_frame = session.df_ctx.table(&data_source.name).await?;
+            let session = &mut self.write().await;
+
+            orc::register_bytes(&session.df_ctx, &data_source, data).await?;
 
-            local_fs::json_file::write(&data_frame.collect().await?, file_path.to_str().unwrap())?;
+            session
+                .data_source_map
+                .insert(data_source.name.clone(), data_source.clone());
         }
 
         Ok(())
     }
 
+    async fn save_to_object_store(&self, data_source: &DataSource) -> Result<(), ResponseError> {
+        self.touch().await;
+        let session = &mut self.read().await;
+        object_store::writer::write(&session.df_ctx, data_source).await?;
+        Ok(())
+    }
+
+    /// Local files are saved by the object store writer.
+    async fn save_to_file(&self, data_source: &DataSource) -> Result<(), ResponseError> {
+        self.save_to_object_store(data_source).await
+    }
+
+    #[cfg(feature = "database")]
+    async fn save_to_database(&self, data_source: &DataSource) -> Result<(), ResponseError> {
+        self.touch().await;
+        let session = &mut self.read().await;
+        database::table_writer::write(&session.df_ctx, data_source).await?;
+        Ok(())
+    }
+
     async fn remove_data_source(&self, name: &str) -> Result<(), ResponseError> {
         self.touch().await;
         {
@@ -697,17 +830,41 @@ impl Session for ConcurrentSessionContext {
     async fn execute_logical_plan(&self, sql: &str) -> Result<DataFrame, ResponseError> {
         self.touch().await;
 
-        #[cfg(not(any(feature = "postgres", feature = "mysql")))]
+        #[cfg(not(feature = "database"))]
         {
             let context = &self.read().await.df_ctx;
             Ok(context.sql(sql).await?)
         }
 
-        #[cfg(any(feature = "postgres", feature = "mysql"))]
+        #[cfg(feature = "database")]
         {
             let context = &self.read().await.df_ctx;
             database::table_register::from_sql(context, sql).await?;
-            Ok(context.sql(sql).await?)
+
+            match context.sql(sql).await {
+                // retries once with the refreshed schema of database tables
+                Err(e)
+                    if database::table_register::is_schema_mismatch(&e)
+                        && database::table_register::refresh(context, sql).await? =>
+                {
+                    log::debug!("Retry with the refreshed schema: {e}");
+                    database::table_register::from_sql(context, sql).await?;
+                    Ok(context.sql(sql).await?)
+                }
+                result => Ok(result?),
+            }
         }
     }
+
+    #[cfg(feature = "database")]
+    async fn refresh_database_schema(&self, sql: &str) -> Result<bool, ResponseError> {
+        let context = &self.read().await.df_ctx;
+        Ok(database::table_register::refresh(context, sql).await?)
+    }
+
+    /// Authenticated user of the request created the session.
+    #[cfg(feature = "database")]
+    async fn identity(&self) -> Option<Arc<database::impersonation::DatabaseIdentity>> {
+        database::impersonation::DatabaseIdentity::from_context(&self.read().await.df_ctx)
+    }
 }
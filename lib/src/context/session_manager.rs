@@ -8,14 +8,20 @@ use std::sync::Arc;
 
 use async_trait::async_trait; // TODO: Replace in the future when the Rust compiler's async trait supports object safety.
 use axum::http;
+#[cfg(feature = "database")]
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
 use datafusion::{
     arrow::record_batch::RecordBatch, dataframe::DataFrame, execution::context::SessionConfig,
     physical_plan::SendableRecordBatchStream,
 };
+#[cfg(feature = "database")]
+use futures::StreamExt;
 use tokio::sync::RwLock;
 
 use crate::context::session::{ConcurrentSessionContext, Session, SessionContext};
-use crate::data_source::{location, schema::DataSourceSchema};
+#[cfg(feature = "database")]
+use crate::data_source::database;
+use crate::data_source::{location, schema::DataSourceSchema, transport::cache};
 use crate::request::body::{
     DataSource, DataSourceFormat, MergeDirection, MergeOption, MergeProcessor, Variables,
 };
@@ -144,6 +150,13 @@ pub trait SessionManager: Send + Sync + 'static {
         data_source: &DataSource,
     ) -> Result<(), ResponseError>;
 
+    #[cfg(feature = "iceberg")]
+    async fn append_from_iceberg(
+        &self,
+        session_id: &str,
+        data_source: &DataSource,
+    ) -> Result<(), ResponseError>;
+
     #[cfg(feature = "plugin")]
     async fn append_connector_plugin(
         &self,
@@ -164,6 +177,21 @@ pub trait SessionManager: Send + Sync + 'static {
         data: bytes::Bytes,
     ) -> Result<(), ResponseError>;
 
+    #[cfg(feature = "orc")]
+    async fn append_orc_rest(
+        &self,
+        session_id: &str,
+        data_source: &DataSource,
+    ) -> Result<(), ResponseError>;
+
+    #[cfg(feature = "orc")]
+    async fn append_orc_bytes(
+        &self,
+        session_id: &str,
+        name: &str,
+        data: bytes::Bytes,
+    ) -> Result<(), ResponseError>;
+
     async fn append_variables(
         &self,
         session_id: &str,
@@ -199,6 +227,13 @@ pub trait SessionManager: Send + Sync + 'static {
         session_id: &str,
         sql: &str,
     ) -> Result<SendableRecordBatchStream, ResponseError>;
+
+    #[cfg(feature = "database")]
+    async fn verify_identity(
+        &self,
+        session_id: &str,
+        identity: Option<&database::impersonation::DatabaseIdentity>,
+    ) -> Result<(), ResponseError>;
 }
 
 macro_rules! context {
@@ -213,6 +248,7 @@ macro_rules! context {
 }
 
 #[async_trait]
+#[allow(clippy::too_many_lines)]
 impl SessionManager for SessionContextManager {
     async fn create_new_session(
         &self,
@@ -266,7 +302,10 @@ impl SessionManager for SessionContextManager {
             }
         }
 
-        self.contexts.write().await.remove(session_id);
+        let context = self.contexts.write().await.remove(session_id);
+        if let Some(context) = context {
+            cache::release(&context.id().await).await;
+        }
 
         Ok(())
     }
@@ -284,7 +323,10 @@ impl SessionManager for SessionContextManager {
 
         for session_id in expired_ids {
             log::info!("Session {session_id} has been expired");
-            self.contexts.write().await.remove(&session_id);
+            let context = self.contexts.write().await.remove(&session_id);
+            if let Some(context) = context {
+                cache::release(&context.id().await).await;
+            }
         }
     }
 
@@ -345,6 +387,21 @@ impl SessionManager for SessionContextManager {
         session_id: &str,
         data_source: &DataSource,
     ) -> Result<(), ResponseError> {
+        // location of the external database is the namespace
+        #[cfg(feature = "database")]
+        if data_source.format == DataSourceFormat::Database {
+            data_source.validator()?;
+
+            context!(self, session_id)?
+                .append_from_database(data_source)
+                .await?;
+
+            #[cfg(feature = "telemetry")]
+            server::metrics::track_data_sources_total("database", data_source.format.to_str());
+
+            return Ok(());
+        }
+
         let uri = location::uri::to_parts(&data_source.location)
             .map_err(|e| ResponseError::unsupported_type(e.to_string()))?;
         let scheme = location::uri::scheme(&uri)?;
@@ -397,6 +454,15 @@ impl SessionManager for SessionContextManager {
                         .await?;
                 }
             }
+            #[cfg(feature = "orc")]
+            DataSourceFormat::Orc => {
+                if scheme.handle_object_store() {
+                    self.append_from_object_store(session_id, data_source)
+                        .await?;
+                } else {
+                    self.append_orc_rest(session_id, data_source).await?;
+                }
+            }
             DataSourceFormat::Json => {
                 if scheme.remote_source() {
                     self.append_json_rest(session_id, data_source).await?;
@@ -419,6 +485,17 @@ impl SessionManager for SessionContextManager {
             DataSourceFormat::Deltalake => {
                 self.append_from_deltalake(session_id, data_source).await?;
             }
+            #[cfg(feature = "iceberg")]
+            DataSourceFormat::Iceberg => {
+                self.append_from_iceberg(session_id, data_source).await?;
+            }
+            #[cfg(feature = "database")]
+            DataSourceFormat::Database => {
+                // MEMO: will not to be reached this control path
+                return Err(ResponseError::request_validation(
+                    "Invalid location of 'database', use the namespace of external database.",
+                ));
+            }
         }
 
         #[cfg(feature = "telemetry")]
@@ -444,15 +521,20 @@ impl SessionManager for SessionContextManager {
         session_id: &str,
         data_source: &DataSource,
     ) -> Result<(), ResponseError> {
+        #[cfg(feature = "database")]
+        if data_source.format == DataSourceFormat::Database {
+            data_source.validator()?;
+
+            return context!(self, session_id)?
+                .save_to_database(data_source)
+                .await;
+        }
+
         let uri = location::uri::to_parts(&data_source.location)
             .map_err(|e| ResponseError::unsupported_type(e.to_string()))?;
         let scheme = location::uri::scheme(&uri)?;
 
-        if data_source.format == DataSourceFormat::Json {
-            context!(self, session_id)?
-                .save_to_file(data_source)
-                .await?;
-        } else if scheme.handle_object_store() {
+        if scheme.handle_object_store() {
             context!(self, session_id)?
                 .save_to_object_store(data_source)
                 .await?;
@@ -610,6 +692,18 @@ impl SessionManager for SessionContextManager {
         Ok(())
     }
 
+    #[cfg(feature = "iceberg")]
+    async fn append_from_iceberg(
+        &self,
+        session_id: &str,
+        data_source: &DataSource,
+    ) -> Result<(), ResponseError> {
+        context!(self, session_id)?
+            .append_from_iceberg(data_source)
+            .await?;
+        Ok(())
+    }
+
     #[cfg(feature = "plugin")]
     async fn append_connector_plugin(
         &self,
@@ -645,6 +739,31 @@ impl SessionManager for SessionContextManager {
         Ok(())
     }
 
+    #[cfg(feature = "orc")]
+    async fn append_orc_rest(
+        &self,
+        session_id: &str,
+        data_source: &DataSource,
+    ) -> Result<(), ResponseError> {
+        context!(self, session_id)?
+            .append_from_orc_rest(data_source)
+            .await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "orc")]
+    async fn append_orc_bytes(
+        &self,
+        session_id: &str,
+        name: &str,
+        data: bytes::Bytes,
+    ) -> Result<(), ResponseError> {
+        context!(self, session_id)?
+            .append_from_orc_bytes(name, data)
+            .await?;
+        Ok(())
+    }
+
     async fn append_variables(
         &self,
         session_id: &str,
@@ -726,10 +845,28 @@ impl SessionManager for SessionContextManager {
         session_id: &str,
         sql: &str,
     ) -> Result<Vec<RecordBatch>, ResponseError> {
-        Ok(Self::execute_logical_plan(self, session_id, sql)
+        let result = Self::execute_logical_plan(self, session_id, sql)
             .await?
             .collect()
-            .await?)
+            .await;
+
+        // remote database reported the columns not matched to the cached schema
+        #[cfg(feature = "database")]
+        if let Err(e) = &result {
+            if database::table_register::is_schema_mismatch(e)
+                && context!(self, session_id)?
+                    .refresh_database_schema(sql)
+                    .await?
+            {
+                log::debug!("Retry with the refreshed schema: {e}");
+                return Ok(Self::execute_logical_plan(self, session_id, sql)
+                    .await?
+                    .collect()
+                    .await?);
+            }
+        }
+
+        Ok(result?)
     }
 
     async fn execute_sql_stream(
@@ -737,9 +874,54 @@ impl SessionManager for SessionContextManager {
         session_id: &str,
         sql: &str,
     ) -> Result<SendableRecordBatchStream, ResponseError> {
-        Ok(Self::execute_logical_plan(self, session_id, sql)
+        let stream = Self::execute_logical_plan(self, session_id, sql)
             .await?
             .execute_stream()
-            .await?)
+            .await?;
+
+        // remote database reported the columns not matched to the cached schema before the first batch
+        #[cfg(feature = "database")]
+        let stream: SendableRecordBatchStream = {
+            let mut stream = stream;
+            let first = stream.next().await;
+
+            if let Some(Err(e)) = &first {
+                if database::table_register::is_schema_mismatch(e)
+                    && context!(self, session_id)?
+                        .refresh_database_schema(sql)
+                        .await?
+                {
+                    log::debug!("Retry with the refreshed schema: {e}");
+                    return Ok(Self::execute_logical_plan(self, session_id, sql)
+                        .await?
+                        .execute_stream()
+                        .await?);
+                }
+            }
+
+            Box::pin(RecordBatchStreamAdapter::new(
+                stream.schema(),
+                futures::stream::iter(first).chain(stream),
+            ))
+        };
+
+        Ok(stream)
+    }
+
+    /// The session accesses the external databases as the user who created it,
+    /// the requests of the other users (including anonymous) are rejected.
+    #[cfg(feature = "database")]
+    async fn verify_identity(
+        &self,
+        session_id: &str,
+        identity: Option<&database::impersonation::DatabaseIdentity>,
+    ) -> Result<(), ResponseError> {
+        let owner = context!(self, session_id)?.identity().await;
+
+        if owner.as_deref() == identity {
+            Ok(())
+        } else {
+            Err(ResponseError::session_forbidden(session_id))
+        }
     }
 }
@@ -8,6 +8,8 @@ use pyo3::{
     Py, PyAny, PyResult, Python,
 };
 
+#[cfg(feature = "orc")]
+use crate::data_source::orc;
 use crate::data_source::{
     csv, decoder::build_record_batch, location, nd_json, parquet, schema::DataSourceSchema,
     with_jsonpath,
@@ -71,12 +73,26 @@ pub fn to_record_batch(
         DataSourceFormat::Parquet => {
             parquet::from_bytes_to_record_batch(py_result_to_bytes(&py_result)?)?
         }
+        #[cfg(feature = "orc")]
+        DataSourceFormat::Orc => orc::from_bytes_to_record_batch(py_result_to_bytes(&py_result)?)?,
         #[cfg(feature = "deltalake")]
         DataSourceFormat::Deltalake => {
             return Err(ResponseError::unsupported_type(
                 "Format of plugins are not supported 'deltalake'",
             ));
         }
+        #[cfg(feature = "iceberg")]
+        DataSourceFormat::Iceberg => {
+            return Err(ResponseError::unsupported_type(
+                "Format of plugins are not supported 'iceberg'",
+            ));
+        }
+        #[cfg(feature = "database")]
+        DataSourceFormat::Database => {
+            return Err(ResponseError::unsupported_type(
+                "Format of plugins are not supported 'database'",
+            ));
+        }
         #[cfg(feature = "flight")]
         DataSourceFormat::Flight => {
             return Err(ResponseError::unsupported_type(
@@ -17,14 +17,7 @@ pub async fn from_response_to_record_batch(
     schema: Option<&DataSourceSchema>,
     options: &DataSourceOption,
 ) -> Result<Vec<RecordBatch>, ResponseError> {
-    from_bytes_to_record_batch(
-        match http::get(uri, options, http::ResponseDataType::Binary).await? {
-            http::ResponseData::Binary(data) => data,
-            http::ResponseData::Text(_) => bytes::Bytes::new(),
-        },
-        schema,
-        options,
-    )
+    from_bytes_to_record_batch(http::get_bytes(uri, options).await?, schema, options)
 }
 
 pub fn from_bytes_to_record_batch(
@@ -217,7 +217,7 @@ impl DataType {
                 fields: fields
                     .iter()
                     .map(|field| Field {
-                        name: field.name().to_string(),
+                        name: field.name().clone(),
                         data_type: Self::from_arrow_data_type(field.data_type()),
                         nullable: Some(field.is_nullable()),
                     })
@@ -2,61 +2,168 @@
 // Sasaki, Naoki <nsasaki@sal.co.jp> July 27, 2024
 //
 
-use futures::stream::{Stream, StreamExt};
+use futures::stream::{Stream, StreamExt, TryStreamExt};
+use std::any::Any;
 use std::pin::Pin;
 use std::sync::Arc;
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+use std::time::Duration;
 
 use async_trait::async_trait; // TODO: Replace in the future when the Rust compiler's async trait supports object safety.
+use datafusion::{
+    arrow::datatypes::{DataType, SchemaRef},
+    common::DFSchema,
+    error::DataFusionError,
+    logical_expr::{Expr, TableProviderFilterPushDown},
+    physical_plan::SendableRecordBatchStream,
+    scalar::ScalarValue,
+    sql::{sqlparser::dialect::Dialect as ParserDialect, unparser::dialect::Dialect},
+};
 #[cfg(feature = "mysql")]
 use sqlx::mysql::MySqlRow;
 #[cfg(feature = "postgres")]
 use sqlx::postgres::PgRow;
+#[cfg(feature = "sqlite")]
+use sqlx::sqlite::SqliteRow;
+#[cfg(feature = "sqlite")]
+use sqlx::ValueRef;
 use sqlx::{
     pool::PoolOptions,
-    {Pool, Row},
+    Transaction, {Column, Executor, Pool, Row, Statement, TypeInfo},
+};
+
+use crate::data_source::database::column_type::ColumnType;
+#[cfg(feature = "mysql")]
+use crate::data_source::database::dtype_mysql;
+#[cfg(feature = "postgres")]
+use crate::data_source::database::dtype_postgres;
+#[cfg(feature = "sqlite")]
+use crate::data_source::database::dtype_sqlite::{self, FromStorageValue, StorageValue};
+use crate::data_source::database::{
+    driver::{DatabaseDriver, Placeholder, TableColumn, WriteTarget},
+    engine_type::DatabaseEngineType,
+    passthrough,
+    table_provider::{self, DatabaseScanStream},
+    table_writer,
 };
 
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+const IMPERSONATED_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
 pub enum AnyDatabaseRow {
     #[cfg(feature = "postgres")]
     Postgres(PgRow),
     #[cfg(feature = "mysql")]
     MySql(MySqlRow),
+    #[cfg(feature = "sqlite")]
+    Sqlite(SqliteRow),
+}
+
+// Decodable column types of every enabled database engine.
+
+#[cfg(feature = "postgres")]
+pub trait PostgresDecode:
+    for<'r> sqlx::Decode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>
+{
+}
+#[cfg(feature = "postgres")]
+impl<T> PostgresDecode for T where
+    T: for<'r> sqlx::Decode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres>
+{
 }
+#[cfg(not(feature = "postgres"))]
+pub trait PostgresDecode {}
+#[cfg(not(feature = "postgres"))]
+impl<T> PostgresDecode for T {}
+
+#[cfg(feature = "mysql")]
+pub trait MySqlDecode: for<'r> sqlx::Decode<'r, sqlx::MySql> + sqlx::Type<sqlx::MySql> {}
+#[cfg(feature = "mysql")]
+impl<T> MySqlDecode for T where T: for<'r> sqlx::Decode<'r, sqlx::MySql> + sqlx::Type<sqlx::MySql> {}
+#[cfg(not(feature = "mysql"))]
+pub trait MySqlDecode {}
+#[cfg(not(feature = "mysql"))]
+impl<T> MySqlDecode for T {}
+
+#[cfg(feature = "sqlite")]
+pub trait SqliteDecode:
+    for<'r> sqlx::Decode<'r, sqlx::Sqlite> + sqlx::Type<sqlx::Sqlite> + FromStorageValue
+{
+}
+#[cfg(feature = "sqlite")]
+impl<T> SqliteDecode for T where
+    T: for<'r> sqlx::Decode<'r, sqlx::Sqlite> + sqlx::Type<sqlx::Sqlite> + FromStorageValue
+{
+}
+#[cfg(not(feature = "sqlite"))]
+pub trait SqliteDecode {}
+#[cfg(not(feature = "sqlite"))]
+impl<T> SqliteDecode for T {}
 
 impl AnyDatabaseRow {
-    #[cfg(all(feature = "postgres", feature = "mysql"))]
-    pub fn get<'a, T>(&'a self, column: &str) -> Option<T>
+    /// Value of the column, `None` if NULL. Undecodable values are errors, never read as NULL.
+    pub fn get<'a, T>(&'a self, column: &str) -> Result<Option<T>, DataFusionError>
     where
-        T: for<'r> sqlx::Decode<'r, sqlx::Postgres>
-            + sqlx::Type<sqlx::Postgres>
-            + for<'r> sqlx::Decode<'r, sqlx::MySql>
-            + sqlx::Type<sqlx::MySql>
-            + Send
-            + 'a,
+        T: PostgresDecode + MySqlDecode + SqliteDecode + Send + 'a,
     {
         match self {
-            AnyDatabaseRow::Postgres(row) => row.try_get(column).ok(),
-            AnyDatabaseRow::MySql(row) => row.try_get(column).ok(),
+            #[cfg(feature = "postgres")]
+            AnyDatabaseRow::Postgres(row) => {
+                row.try_get(column).map_err(|e| decode_error(column, &e))
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabaseRow::MySql(row) => row.try_get(column).map_err(|e| decode_error(column, &e)),
+            #[cfg(feature = "sqlite")]
+            AnyDatabaseRow::Sqlite(row) => Self::get_sqlite(row, column),
         }
     }
 
-    #[cfg(all(feature = "postgres", not(feature = "mysql")))]
-    pub fn get<'a, T>(&'a self, column: &str) -> Option<T>
-    where
-        T: for<'r> sqlx::Decode<'r, sqlx::Postgres> + sqlx::Type<sqlx::Postgres> + Send + 'a,
-    {
-        match self {
-            AnyDatabaseRow::Postgres(row) => row.try_get(column).ok(),
+    /// Decodes by the storage class of the value, as the declared type of the column does not constrain it.
+    /// The value of the other class is converted, e.g. INTEGER to REAL or TEXT to INTEGER.
+    #[cfg(feature = "sqlite")]
+    fn get_sqlite<T: SqliteDecode>(
+        row: &SqliteRow,
+        column: &str,
+    ) -> Result<Option<T>, DataFusionError> {
+        let value = row
+            .try_get_raw(column)
+            .map_err(|e| decode_error(column, &e))?;
+        if value.is_null() {
+            return Ok(None);
+        }
+
+        if <T as sqlx::Type<sqlx::Sqlite>>::compatible(&value.type_info()) {
+            return <T as sqlx::Decode<sqlx::Sqlite>>::decode(value)
+                .map(Some)
+                .map_err(|e| decode_error(column, &sqlx::Error::Decode(e)));
         }
+
+        StorageValue::decode(value)
+            .map_err(|e| decode_error(column, &sqlx::Error::Decode(e)))
+            .and_then(|value| {
+                T::from_storage_value(value).map_err(|e| {
+                    DataFusionError::Execution(format!("Can not decode column '{column}': {e}"))
+                })
+            })
+            .map(Some)
     }
 
-    #[cfg(all(feature = "mysql", not(feature = "postgres")))]
-    pub fn get<'a, T>(&'a self, column: &str) -> Option<T>
-    where
-        T: for<'r> sqlx::Decode<'r, sqlx::MySql> + sqlx::Type<sqlx::MySql> + Send + 'a,
-    {
+    /// `SQLite` has no decimal type, the value is decoded from REAL or INTEGER.
+    pub fn get_decimal(
+        &self,
+        column: &str,
+    ) -> Result<Option<sqlx::types::Decimal>, DataFusionError> {
         match self {
-            AnyDatabaseRow::MySql(row) => row.try_get(column).ok(),
+            #[cfg(feature = "postgres")]
+            AnyDatabaseRow::Postgres(row) => {
+                row.try_get(column).map_err(|e| decode_error(column, &e))
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabaseRow::MySql(row) => row.try_get(column).map_err(|e| decode_error(column, &e)),
+            #[cfg(feature = "sqlite")]
+            AnyDatabaseRow::Sqlite(_) => Ok(self
+                .get::<f64>(column)?
+                .and_then(sqlx::types::Decimal::from_f64_retain)),
         }
     }
 
@@ -65,11 +172,13 @@ impl AnyDatabaseRow {
     pub fn get_postgres<'a, T: sqlx::Type<sqlx::Postgres> + sqlx::Decode<'a, sqlx::Postgres>>(
         &'a self,
         column: &str,
-    ) -> Option<T> {
+    ) -> Result<Option<T>, DataFusionError> {
         match self {
-            AnyDatabaseRow::Postgres(row) => row.try_get(column).ok(),
-            #[cfg(feature = "mysql")]
-            AnyDatabaseRow::MySql(_) => None,
+            AnyDatabaseRow::Postgres(row) => {
+                row.try_get(column).map_err(|e| decode_error(column, &e))
+            }
+            #[allow(unreachable_patterns)]
+            _ => Ok(None),
         }
     }
 
@@ -77,23 +186,24 @@ impl AnyDatabaseRow {
     pub fn get_mysql<'a, T: sqlx::Type<sqlx::MySql> + sqlx::Decode<'a, sqlx::MySql>>(
         &'a self,
         column: &str,
-    ) -> Option<T> {
+    ) -> Result<Option<T>, DataFusionError> {
         match self {
-            AnyDatabaseRow::MySql(row) => row.try_get(column).ok(),
-            #[cfg(feature = "postgres")]
-            AnyDatabaseRow::Postgres(_) => None,
+            AnyDatabaseRow::MySql(row) => row.try_get(column).map_err(|e| decode_error(column, &e)),
+            #[allow(unreachable_patterns)]
+            _ => Ok(None),
         }
     }
 }
 
+// e.g. type mismatch, out of range value, and multi-dimensional array
+fn decode_error(column: &str, e: &sqlx::Error) -> DataFusionError {
+    DataFusionError::Execution(format!("Can not decode column '{column}': {e}"))
+}
+
 #[async_trait]
 pub trait DatabaseOperator {
     async fn fetch_all(&self, query: &str) -> Result<Vec<AnyDatabaseRow>, sqlx::Error>;
     async fn fetch_one(&self, query: &str) -> Result<AnyDatabaseRow, sqlx::Error>;
-    fn fetch<'a>(
-        &'a self,
-        query: &'a str,
-    ) -> Pin<Box<dyn Stream<Item = Result<AnyDatabaseRow, sqlx::Error>> + Send + 'a>>;
 }
 
 #[cfg(feature = "postgres")]
@@ -108,16 +218,6 @@ impl DatabaseOperator for Arc<Pool<sqlx::Postgres>> {
         let row: PgRow = sqlx::query(query).fetch_one(&**self).await?;
         Ok(AnyDatabaseRow::Postgres(row))
     }
-
-    fn fetch<'a>(
-        &'a self,
-        query: &'a str,
-    ) -> Pin<Box<dyn Stream<Item = Result<AnyDatabaseRow, sqlx::Error>> + Send + 'a>> {
-        let stream = sqlx::query(query)
-            .fetch(&**self)
-            .map(|row| row.map(AnyDatabaseRow::Postgres));
-        Box::pin(stream)
-    }
 }
 
 #[cfg(feature = "mysql")]
@@ -132,25 +232,120 @@ impl DatabaseOperator for Arc<Pool<sqlx::MySql>> {
         let row: MySqlRow = sqlx::query(query).fetch_one(&**self).await?;
         Ok(AnyDatabaseRow::MySql(row))
     }
+}
 
-    fn fetch<'a>(
-        &'a self,
-        query: &'a str,
-    ) -> Pin<Box<dyn Stream<Item = Result<AnyDatabaseRow, sqlx::Error>> + Send + 'a>> {
-        Box::pin(
-            sqlx::query(query)
-                .fetch(&**self)
-                .map(|row| row.map(AnyDatabaseRow::MySql)),
-        )
+#[cfg(feature = "sqlite")]
+#[async_trait]
+impl DatabaseOperator for Arc<Pool<sqlx::Sqlite>> {
+    async fn fetch_all(&self, query: &str) -> Result<Vec<AnyDatabaseRow>, sqlx::Error> {
+        let rows: Vec<SqliteRow> = sqlx::query(query).fetch_all(&**self).await?;
+        Ok(rows.into_iter().map(AnyDatabaseRow::Sqlite).collect())
+    }
+
+    async fn fetch_one(&self, query: &str) -> Result<AnyDatabaseRow, sqlx::Error> {
+        let row: SqliteRow = sqlx::query(query).fetch_one(&**self).await?;
+        Ok(AnyDatabaseRow::Sqlite(row))
+    }
+}
+
+/// Postgres snapshot shared by the partitions of a scan. The first partition exports the snapshot
+/// of its transaction, which is kept open until the other partitions have imported the snapshot.
+#[cfg(feature = "postgres")]
+#[derive(Debug)]
+pub struct SharedSnapshot {
+    importers: usize,
+    id: tokio::sync::OnceCell<String>,
+    // count of imported partitions, and the finished exporter waiting for them
+    state: std::sync::Mutex<(usize, Option<Transaction<'static, sqlx::Postgres>>)>,
+}
+
+#[cfg(feature = "postgres")]
+impl SharedSnapshot {
+    pub fn new(partitions: usize) -> Self {
+        Self {
+            importers: partitions.saturating_sub(1),
+            id: tokio::sync::OnceCell::new(),
+            state: std::sync::Mutex::new((0, None)),
+        }
+    }
+
+    /// Begins the read-only transaction on the shared snapshot, returns the transaction
+    /// and whether it has exported the snapshot.
+    pub async fn begin(
+        &self,
+        pool: &Pool<sqlx::Postgres>,
+    ) -> Result<(Transaction<'static, sqlx::Postgres>, bool), sqlx::Error> {
+        let mut transaction = pool.begin().await?;
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *transaction)
+            .await?;
+
+        let mut exported = false;
+        let export = async {
+            exported = true;
+            sqlx::query_scalar::<_, String>("SELECT pg_export_snapshot()")
+                .fetch_one(&mut *transaction)
+                .await
+        };
+        let id = self.id.get_or_try_init(|| export).await?.clone();
+
+        if !exported {
+            sqlx::query(&format!("SET TRANSACTION SNAPSHOT '{id}'"))
+                .execute(&mut *transaction)
+                .await?;
+
+            let mut state = self.state.lock().unwrap();
+            state.0 += 1;
+            if state.0 == self.importers {
+                state.1 = None; // the exporter is no longer needed
+            }
+        }
+
+        Ok((transaction, exported))
+    }
+
+    /// Keeps the finished transaction of the exporter until the other partitions have imported the snapshot.
+    pub fn release(&self, transaction: Transaction<'static, sqlx::Postgres>, exported: bool) {
+        let mut state = self.state.lock().unwrap();
+
+        if exported && state.0 < self.importers {
+            state.1 = Some(transaction);
+        }
     }
 }
 
+/// Bind parameter of the native query.
+#[derive(Debug, Clone)]
+pub enum QueryParam {
+    Bool(Option<bool>),
+    Int(Option<i64>),
+    Float(Option<f64>),
+    Text(Option<String>),
+}
+
+macro_rules! bind_params {
+    ($query:expr, $params:expr) => {{
+        let mut query = $query;
+        for param in $params {
+            query = match param {
+                QueryParam::Bool(value) => query.bind(*value),
+                QueryParam::Int(value) => query.bind(*value),
+                QueryParam::Float(value) => query.bind(*value),
+                QueryParam::Text(value) => query.bind(value.clone()),
+            };
+        }
+        query
+    }};
+}
+
 #[derive(Debug, Clone)]
 pub enum AnyDatabasePool {
     #[cfg(feature = "postgres")]
     Postgres(Arc<Pool<sqlx::Postgres>>),
     #[cfg(feature = "mysql")]
     MySql(Arc<Pool<sqlx::MySql>>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(Arc<Pool<sqlx::Sqlite>>),
 }
 
 impl AnyDatabasePool {
@@ -174,11 +369,247 @@ impl AnyDatabasePool {
                 let pool = pool_options.connect_lazy(url)?;
                 Ok(AnyDatabasePool::MySql(Arc::new(pool)))
             }
+            #[cfg(feature = "sqlite")]
+            "sqlite" => {
+                let pool_options: PoolOptions<sqlx::Sqlite> = PoolOptions::new()
+                    .max_connections(max_connections)
+                    .min_connections(1);
+                let pool = pool_options.connect_lazy(url)?;
+                Ok(AnyDatabasePool::Sqlite(Arc::new(pool)))
+            }
             _ => Err(sqlx::Error::Configuration(
                 format!("Unsupported database scheme: {scheme}").into(),
             )),
         }
     }
+
+    /// Maximum number of the connections of the pool.
+    pub fn max_connections(&self) -> u32 {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(pool) => pool.options().get_max_connections(),
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(pool) => pool.options().get_max_connections(),
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(pool) => pool.options().get_max_connections(),
+        }
+    }
+
+    /// Pool of the database file, opens as read-only.
+    #[cfg(feature = "sqlite")]
+    pub fn new_sqlite(file: &std::path::Path, max_connections: u32) -> Self {
+        let pool_options: PoolOptions<sqlx::Sqlite> = PoolOptions::new()
+            .max_connections(max_connections)
+            .min_connections(1);
+        let pool = pool_options.connect_lazy_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(file)
+                .read_only(true),
+        );
+        AnyDatabasePool::Sqlite(Arc::new(pool))
+    }
+
+    /// Pool of the impersonated user, the connections execute `SET ROLE` when established and on each checkout
+    /// if the role is given, as a statement may have changed the role of the pooled connection.
+    /// Idle connections are closed as the pools are created for each user.
+    #[allow(unused_variables)]
+    pub fn new_impersonated(
+        url: &str,
+        max_connections: u32,
+        role: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        let scheme = url.split(':').next().unwrap_or("");
+        let role = role.map(ToString::to_string);
+
+        match scheme {
+            #[cfg(feature = "postgres")]
+            "postgres" => {
+                let mut pool_options: PoolOptions<sqlx::Postgres> = PoolOptions::new()
+                    .max_connections(max_connections)
+                    .idle_timeout(IMPERSONATED_IDLE_TIMEOUT);
+                if let Some(role) = role {
+                    let set_role: Arc<str> =
+                        format!("SET ROLE \"{}\"", role.replace('"', "\"\"")).into();
+                    let on_acquire = set_role.clone();
+                    pool_options = pool_options
+                        .after_connect(move |conn, _meta| {
+                            let set_role = set_role.clone();
+                            Box::pin(async move { conn.execute(&*set_role).await.map(|_| ()) })
+                        })
+                        .before_acquire(move |conn, _meta| {
+                            let set_role = on_acquire.clone();
+                            Box::pin(async move { conn.execute(&*set_role).await.map(|_| true) })
+                        });
+                }
+                let pool = pool_options.connect_lazy(url)?;
+                Ok(AnyDatabasePool::Postgres(Arc::new(pool)))
+            }
+            #[cfg(feature = "mysql")]
+            "mysql" => {
+                let mut pool_options: PoolOptions<sqlx::MySql> = PoolOptions::new()
+                    .max_connections(max_connections)
+                    .idle_timeout(IMPERSONATED_IDLE_TIMEOUT);
+                if let Some(role) = role {
+                    let set_role: Arc<str> =
+                        format!("SET ROLE `{}`", role.replace('`', "``")).into();
+                    let on_acquire = set_role.clone();
+                    pool_options = pool_options
+                        .after_connect(move |conn, _meta| {
+                            let set_role = set_role.clone();
+                            Box::pin(async move { conn.execute(&*set_role).await.map(|_| ()) })
+                        })
+                        .before_acquire(move |conn, _meta| {
+                            let set_role = on_acquire.clone();
+                            Box::pin(async move { conn.execute(&*set_role).await.map(|_| true) })
+                        });
+                }
+                let pool = pool_options.connect_lazy(url)?;
+                Ok(AnyDatabasePool::MySql(Arc::new(pool)))
+            }
+            _ => Err(sqlx::Error::Configuration(
+                format!("Impersonation is not supported by the database scheme: {scheme}").into(),
+            )),
+        }
+    }
+
+    pub fn engine_type(&self) -> DatabaseEngineType {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(_) => DatabaseEngineType::Postgres,
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(_) => DatabaseEngineType::MySQL,
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(_) => DatabaseEngineType::Sqlite,
+        }
+    }
+
+    /// Column type of the type name reported by the database engine, e.g. prepared statement columns.
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn column_type(&self, type_name: &str) -> Option<ColumnType> {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(_) => dtype_postgres::from_type_name(type_name),
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(_) => dtype_mysql::from_type_name(type_name),
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(_) => Some(ColumnType::new(dtype_sqlite::to_arrow_dtype(
+                type_name, None, None, true,
+            ))),
+        }
+    }
+
+    /// Names and type names of the result columns, the query is prepared but not executed.
+    pub async fn prepare_columns(&self, query: &str) -> Result<Vec<(String, String)>, sqlx::Error> {
+        Ok(match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(pool) => {
+                column_types((&**pool).prepare(query).await?.columns())
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(pool) => column_types((&**pool).prepare(query).await?.columns()),
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(pool) => {
+                column_types((&**pool).prepare(query).await?.columns())
+            }
+        })
+    }
+
+    /// Streams the rows of the query with the bind parameters.
+    pub fn fetch_with_params<'a>(
+        &'a self,
+        query: &'a str,
+        params: &'a [QueryParam],
+    ) -> Pin<Box<dyn Stream<Item = Result<AnyDatabaseRow, sqlx::Error>> + Send + 'a>> {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(pool) => Box::pin(
+                bind_params!(sqlx::query(query), params)
+                    .fetch(&**pool)
+                    .map(|row| row.map(AnyDatabaseRow::Postgres)),
+            ),
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(pool) => Box::pin(
+                bind_params!(sqlx::query(query), params)
+                    .fetch(&**pool)
+                    .map(|row| row.map(AnyDatabaseRow::MySql)),
+            ),
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(pool) => Box::pin(
+                bind_params!(sqlx::query(query), params)
+                    .fetch(&**pool)
+                    .map(|row| row.map(AnyDatabaseRow::Sqlite)),
+            ),
+        }
+    }
+
+    /// Begins the read-only transaction, functions of the query can not modify the database either.
+    /// `SQLite` is opened as read-only file.
+    pub async fn begin_read_only(&self) -> Result<AnyTransaction, sqlx::Error> {
+        Ok(match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(pool) => {
+                AnyTransaction::Postgres(pool.begin_with("BEGIN READ ONLY").await?)
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(pool) => {
+                AnyTransaction::MySql(pool.begin_with("START TRANSACTION READ ONLY").await?)
+            }
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(pool) => AnyTransaction::Sqlite(pool.begin().await?),
+        })
+    }
+}
+
+/// Transaction of the native query executed by the configured account.
+#[derive(Debug)]
+pub enum AnyTransaction {
+    #[cfg(feature = "postgres")]
+    Postgres(Transaction<'static, sqlx::Postgres>),
+    #[cfg(feature = "mysql")]
+    MySql(Transaction<'static, sqlx::MySql>),
+    #[cfg(feature = "sqlite")]
+    Sqlite(Transaction<'static, sqlx::Sqlite>),
+}
+
+impl AnyTransaction {
+    /// Streams the rows of the query with the bind parameters in the transaction.
+    pub fn fetch_with_params<'a>(
+        &'a mut self,
+        query: &'a str,
+        params: &'a [QueryParam],
+    ) -> Pin<Box<dyn Stream<Item = Result<AnyDatabaseRow, sqlx::Error>> + Send + 'a>> {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyTransaction::Postgres(transaction) => Box::pin(
+                bind_params!(sqlx::query(query), params)
+                    .fetch(&mut **transaction)
+                    .map(|row| row.map(AnyDatabaseRow::Postgres)),
+            ),
+            #[cfg(feature = "mysql")]
+            AnyTransaction::MySql(transaction) => Box::pin(
+                bind_params!(sqlx::query(query), params)
+                    .fetch(&mut **transaction)
+                    .map(|row| row.map(AnyDatabaseRow::MySql)),
+            ),
+            #[cfg(feature = "sqlite")]
+            AnyTransaction::Sqlite(transaction) => Box::pin(
+                bind_params!(sqlx::query(query), params)
+                    .fetch(&mut **transaction)
+                    .map(|row| row.map(AnyDatabaseRow::Sqlite)),
+            ),
+        }
+    }
+
+    pub async fn rollback(self) -> Result<(), sqlx::Error> {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyTransaction::Postgres(transaction) => transaction.rollback().await,
+            #[cfg(feature = "mysql")]
+            AnyTransaction::MySql(transaction) => transaction.rollback().await,
+            #[cfg(feature = "sqlite")]
+            AnyTransaction::Sqlite(transaction) => transaction.rollback().await,
+        }
+    }
 }
 
 #[async_trait]
@@ -189,6 +620,8 @@ impl DatabaseOperator for AnyDatabasePool {
             AnyDatabasePool::Postgres(pool) => pool.fetch_all(query).await,
             #[cfg(feature = "mysql")]
             AnyDatabasePool::MySql(pool) => pool.fetch_all(query).await,
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(pool) => pool.fetch_all(query).await,
         }
     }
 
@@ -198,18 +631,335 @@ impl DatabaseOperator for AnyDatabasePool {
             AnyDatabasePool::Postgres(pool) => pool.fetch_one(query).await,
             #[cfg(feature = "mysql")]
             AnyDatabasePool::MySql(pool) => pool.fetch_one(query).await,
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(pool) => pool.fetch_one(query).await,
         }
     }
+}
 
-    fn fetch<'a>(
-        &'a self,
-        query: &'a str,
-    ) -> Pin<Box<dyn Stream<Item = Result<AnyDatabaseRow, sqlx::Error>> + Send + 'a>> {
+#[async_trait]
+impl DatabaseDriver for AnyDatabasePool {
+    fn connect(url: &str, max_connections: u32) -> Result<Self, DataFusionError> {
+        Self::new(url, max_connections).map_err(|e| DataFusionError::Configuration(e.to_string()))
+    }
+
+    fn dialect(&self) -> Box<dyn Dialect> {
+        self.engine_type().unparser_dialect()
+    }
+
+    /// Maps `data_type` of `table_columns`, the types converted by the remote expression are not supported.
+    fn to_arrow_type(&self, type_name: &str) -> Option<DataType> {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(_) => {
+                dtype_postgres::to_arrow_dtype(type_name, type_name, None, None, true)
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(_) => dtype_mysql::to_arrow_dtype(type_name, None, None, true),
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(_) => Some(ColumnType::new(dtype_sqlite::to_arrow_dtype(
+                type_name, None, None, true,
+            ))),
+        }
+        .filter(|column_type| column_type.select.is_none() && column_type.extension.is_none())
+        .map(|column_type| column_type.data_type)
+    }
+
+    async fn ping(&self) -> Result<(), DataFusionError> {
+        self.fetch_one("SELECT 1")
+            .await
+            .map(|_| ())
+            .map_err(|e| DataFusionError::Execution(e.to_string()))
+    }
+
+    async fn table_list(&self) -> Result<Vec<(String, String)>, DataFusionError> {
+        let sql = match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(_) => {
+                "SELECT table_schema, table_name \
+                FROM information_schema.tables \
+                WHERE table_schema NOT IN ('pg_catalog', 'information_schema') \
+                ORDER BY table_schema, table_name"
+            }
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(_) => {
+                "SELECT table_schema AS table_schema, table_name AS table_name \
+                FROM information_schema.tables \
+                WHERE table_schema=DATABASE() \
+                ORDER BY table_name"
+            }
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(_) => {
+                "SELECT 'main' AS table_schema, name AS table_name \
+                FROM sqlite_master \
+                WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' \
+                ORDER BY name"
+            }
+        };
+
+        log::debug!("Retrieving table list: {sql}");
+
+        Ok(self
+            .fetch_all(sql)
+            .await
+            .map_err(|e| DataFusionError::Execution(e.to_string()))?
+            .iter()
+            .map(|row| {
+                Ok((
+                    row.get::<String>("table_schema")?.unwrap_or_default(),
+                    row.get::<String>("table_name")?.unwrap_or_default(),
+                ))
+            })
+            .collect::<Result<_, DataFusionError>>()?)
+    }
+
+    /// The table names are bound as parameters.
+    async fn table_columns(
+        &self,
+        table_name: Option<&str>,
+    ) -> Result<Vec<TableColumn>, DataFusionError> {
+        // schema qualified table name, e.g. `public.table`
+        let (table_schema, table) = match table_name.and_then(|name| name.rsplit_once('.')) {
+            Some((table_schema, table)) => (Some(table_schema), Some(table)),
+            None => (None, table_name),
+        };
+
+        let (columns, tables, filter, order) = match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(_) => (
+                "table_schema, table_name, column_name, data_type, is_nullable",
+                "information_schema.columns \
+                WHERE table_schema NOT IN ('pg_catalog', 'information_schema')",
+                [" AND table_name=$1", " AND table_schema=$2"],
+                "table_schema, table_name, ordinal_position",
+            ),
+            #[cfg(feature = "mysql")]
+            AnyDatabasePool::MySql(_) => (
+                "table_schema AS table_schema, table_name AS table_name, column_name AS column_name, \
+                data_type AS data_type, is_nullable AS is_nullable",
+                "information_schema.columns \
+                WHERE table_schema=DATABASE()",
+                [" AND table_name=?", " AND table_schema=?"],
+                "table_name, ordinal_position",
+            ),
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(_) => (
+                "'main' AS table_schema, m.name AS table_name, \
+                p.name AS column_name, p.type AS data_type, \
+                CASE WHEN p.\"notnull\" = 0 THEN 'YES' ELSE 'NO' END AS is_nullable",
+                "sqlite_master m JOIN pragma_table_info(m.name) p \
+                WHERE m.type IN ('table', 'view') AND m.name NOT LIKE 'sqlite_%'",
+                [" AND m.name=?", " AND 'main'=?"],
+                "m.name, p.cid",
+            ),
+        };
+
+        let (filters, params): (Vec<&str>, Vec<QueryParam>) = [table, table_schema]
+            .into_iter()
+            .zip(filter)
+            .filter_map(|(name, filter)| {
+                name.map(|name| (filter, QueryParam::Text(Some(name.to_string()))))
+            })
+            .unzip();
+
+        let sql = format!(
+            "SELECT {columns} FROM {tables}{} ORDER BY {order}",
+            filters.concat()
+        );
+
+        log::debug!("Retrieving table columns: {sql}, parameters: {params:?}");
+
+        self.fetch_with_params(&sql, &params)
+            .map_err(|e| DataFusionError::Execution(e.to_string()))
+            .and_then(|row| async move {
+                Ok(TableColumn {
+                    table_schema: row.get::<String>("table_schema")?.unwrap_or_default(),
+                    table_name: row.get::<String>("table_name")?.unwrap_or_default(),
+                    column_name: row.get::<String>("column_name")?.unwrap_or_default(),
+                    data_type: row.get::<String>("data_type")?.unwrap_or_default(),
+                    nullable: row.get::<String>("is_nullable")?.unwrap_or_default() == "YES",
+                })
+            })
+            .try_collect()
+            .await
+    }
+
+    /// The original type names are given by the schema metadata, see the native query.
+    fn fetch(&self, sql: &str, schema: SchemaRef) -> SendableRecordBatchStream {
+        let original_dtypes = schema.metadata.clone();
+        DatabaseScanStream::new(self.clone(), sql.to_string(), schema, original_dtypes).stream()
+    }
+
+    fn pushdown(&self, expr: &Expr, schema: &DFSchema) -> TableProviderFilterPushDown {
+        table_provider::pushdown_support(&self.engine_type(), expr, schema)
+    }
+
+    fn collates_strings(&self, ordering: bool) -> bool {
+        table_provider::collates_strings(&self.engine_type(), ordering)
+    }
+
+    fn parser_dialect(&self) -> Box<dyn ParserDialect> {
+        self.engine_type().parser_dialect()
+    }
+
+    fn placeholder(&self) -> Placeholder {
         match self {
             #[cfg(feature = "postgres")]
-            AnyDatabasePool::Postgres(pool) => pool.fetch(query),
+            AnyDatabasePool::Postgres(_) => Placeholder::Numbered,
+            #[allow(unreachable_patterns)]
+            _ => Placeholder::Positional,
+        }
+    }
+
+    async fn query(
+        &self,
+        sql: &str,
+        params: Vec<ScalarValue>,
+        read_only: bool,
+    ) -> Result<SendableRecordBatchStream, DataFusionError> {
+        passthrough::native_query(self, sql, params, read_only).await
+    }
+
+    async fn write(
+        &self,
+        target: &WriteTarget,
+        stream: SendableRecordBatchStream,
+    ) -> Result<(), DataFusionError> {
+        table_writer::write_pool(self, target, stream).await
+    }
+
+    /// Number of the open connections and the idle connections.
+    fn connections(&self) -> (u32, usize) {
+        match self {
+            #[cfg(feature = "postgres")]
+            AnyDatabasePool::Postgres(pool) => (pool.size(), pool.num_idle()),
             #[cfg(feature = "mysql")]
-            AnyDatabasePool::MySql(pool) => pool.fetch(query),
+            AnyDatabasePool::MySql(pool) => (pool.size(), pool.num_idle()),
+            #[cfg(feature = "sqlite")]
+            AnyDatabasePool::Sqlite(pool) => (pool.size(), pool.num_idle()),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn column_types<C: Column>(columns: &[C]) -> Vec<(String, String)> {
+    columns
+        .iter()
+        .map(|column| {
+            (
+                column.name().to_string(),
+                column.type_info().name().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use crate::data_source::database::{any_pool::AnyDatabasePool, driver::DatabaseDriver};
+
+    #[tokio::test]
+    async fn table_columns_filtered_by_table() {
+        let data_dir = std::env::temp_dir().join(format!(
+            "datafusion-server-columns-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let file = data_dir.join("sales.db");
+        let writer = sqlx::SqlitePool::connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(&file)
+                .create_if_missing(true),
+        )
+        .await
+        .unwrap();
+        for sql in [
+            "CREATE TABLE sales (id INTEGER NOT NULL, amount REAL)",
+            "CREATE TABLE items (id INTEGER, name TEXT)",
+        ] {
+            sqlx::query(sql).execute(&writer).await.unwrap();
         }
+        writer.close().await;
+
+        let pool = AnyDatabasePool::new_sqlite(&file, 1);
+
+        let columns = pool.table_columns(None).await.unwrap();
+        assert_eq!(columns.len(), 4);
+
+        let columns = pool.table_columns(Some("main.sales")).await.unwrap();
+        assert_eq!(
+            columns
+                .iter()
+                .map(|column| (column.column_name.as_str(), column.nullable))
+                .collect::<Vec<_>>(),
+            vec![("id", false), ("amount", true)]
+        );
+        assert!(pool
+            .table_columns(Some("other.sales"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let schema = pool.table_schema("items").await.unwrap();
+        assert_eq!(schema.fields().len(), 2);
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn native_query_in_read_only_transaction() {
+        use datafusion::{arrow::record_batch::RecordBatch, scalar::ScalarValue};
+        use futures::TryStreamExt;
+
+        let data_dir = std::env::temp_dir().join(format!(
+            "datafusion-server-read-only-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let file = data_dir.join("sales.db");
+        let writer = sqlx::SqlitePool::connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(&file)
+                .create_if_missing(true),
+        )
+        .await
+        .unwrap();
+        sqlx::query("CREATE TABLE sales AS SELECT 1 AS id UNION ALL SELECT 2")
+            .execute(&writer)
+            .await
+            .unwrap();
+        writer.close().await;
+
+        // the connection is released by the rollback and reused
+        let pool = AnyDatabasePool::new_sqlite(&file, 1);
+        for _ in 0..2 {
+            let batches: Vec<_> = pool
+                .query(
+                    "SELECT id FROM sales WHERE id > ?",
+                    vec![ScalarValue::Int64(Some(1))],
+                    true,
+                )
+                .await
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(batches.iter().map(RecordBatch::num_rows).sum::<usize>(), 1);
+        }
+
+        let result: Result<Vec<_>, _> = pool
+            .query("DELETE FROM sales RETURNING id", vec![], true)
+            .await
+            .unwrap()
+            .try_collect()
+            .await;
+        assert!(result.is_err());
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
     }
 }
@@ -0,0 +1,141 @@
+// database/catalog.rs: Catalog and schema providers for external databases
+
+use std::any::Any;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use async_trait::async_trait; // TODO: Replace in the future when the Rust compiler's async trait supports object safety.
+use datafusion::{
+    catalog::{CatalogProvider, SchemaProvider},
+    datasource::TableProvider,
+    error::DataFusionError,
+    execution::context::SessionContext,
+};
+
+use crate::data_source::database::{
+    federation, impersonation::DatabaseIdentity, table_resolver::TableResolver,
+};
+use crate::settings::Settings;
+
+/// Registers each database as the catalog of the namespace, e.g. `pg1.public.table`,
+/// the tables are resolved as the database user of the session.
+pub fn register(ctx: &SessionContext) {
+    let identity = DatabaseIdentity::from_context(ctx);
+    let resolvers = &Settings::global().database_pool_manager.resolvers;
+
+    for (namespace, resolver) in resolvers {
+        ctx.register_catalog(
+            namespace,
+            Arc::new(DatabaseCatalog::new(resolver.clone(), identity.clone())),
+        );
+    }
+
+    // queries of the catalog tables are also pushed down
+    if !resolvers.is_empty() {
+        federation::install(ctx);
+    }
+}
+
+#[derive(Debug)]
+pub struct DatabaseCatalog {
+    resolver: Arc<TableResolver>,
+    identity: Option<Arc<DatabaseIdentity>>,
+}
+
+impl DatabaseCatalog {
+    pub fn new(resolver: Arc<TableResolver>, identity: Option<Arc<DatabaseIdentity>>) -> Self {
+        Self { resolver, identity }
+    }
+}
+
+impl CatalogProvider for DatabaseCatalog {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema_names(&self) -> Vec<String> {
+        // unique names, the table list of the driver may not be ordered by the schema
+        self.resolver
+            .table_list(self.identity.as_deref())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(table_schema, _)| table_schema)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    fn schema(&self, name: &str) -> Option<Arc<dyn SchemaProvider>> {
+        // unknown schemas are also resolved by the database until the table list is loaded
+        if let Some(table_list) = self.resolver.table_list(self.identity.as_deref()) {
+            if !table_list
+                .iter()
+                .any(|(table_schema, _)| table_schema == name)
+            {
+                return None;
+            }
+        }
+
+        Some(Arc::new(DatabaseSchema {
+            resolver: self.resolver.clone(),
+            identity: self.identity.clone(),
+            schema_name: name.to_string(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+pub struct DatabaseSchema {
+    resolver: Arc<TableResolver>,
+    identity: Option<Arc<DatabaseIdentity>>,
+    schema_name: String,
+}
+
+#[async_trait]
+impl SchemaProvider for DatabaseSchema {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn table_names(&self) -> Vec<String> {
+        self.resolver
+            .table_list(self.identity.as_deref())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(table_schema, _)| *table_schema == self.schema_name)
+            .map(|(_, table_name)| table_name)
+            .collect()
+    }
+
+    async fn table(&self, name: &str) -> Result<Option<Arc<dyn TableProvider>>, DataFusionError> {
+        // tables not in the loaded list are never queried to the database
+        if !self.table_exist(name) {
+            return Ok(None);
+        }
+
+        let table = self
+            .resolver
+            .get_table(
+                &format!("{}.{name}", self.schema_name),
+                self.identity.as_deref(),
+            )
+            .await?;
+
+        // schema of the non-existent table has no columns
+        Ok(if table.schema().fields().is_empty() {
+            None
+        } else {
+            Some(table)
+        })
+    }
+
+    fn table_exist(&self, name: &str) -> bool {
+        self.resolver
+            .table_list(self.identity.as_deref())
+            .is_none_or(|table_list| {
+                table_list.iter().any(|(table_schema, table_name)| {
+                    *table_schema == self.schema_name && table_name == name
+                })
+            })
+    }
+}
@@ -0,0 +1,44 @@
+// database/column_type.rs: Arrow data type of database column and its remote expression
+
+use datafusion::arrow::datatypes::DataType;
+
+/// Field metadata key of the Arrow extension type.
+pub const EXTENSION_NAME_KEY: &str = "ARROW:extension:name";
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+pub const JSON_EXTENSION: &str = "arrow.json";
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+pub const WKB_EXTENSION: &str = "geoarrow.wkb";
+
+#[derive(Debug, Clone)]
+pub struct ColumnType {
+    pub data_type: DataType,
+    // selects the column converted by the database, e.g. `{}::text`
+    pub select: Option<&'static str>,
+    // canonical extension type name of Arrow
+    pub extension: Option<&'static str>,
+}
+
+impl ColumnType {
+    pub fn new(data_type: DataType) -> Self {
+        Self {
+            data_type,
+            select: None,
+            extension: None,
+        }
+    }
+
+    pub fn with_select(mut self, select: &'static str) -> Self {
+        self.select = Some(select);
+        self
+    }
+
+    pub fn with_extension(mut self, extension: &'static str) -> Self {
+        self.extension = Some(extension);
+        self
+    }
+
+    /// Selects `column` by the remote expression if converted.
+    pub fn select_expr(&self, column: &str) -> Option<String> {
+        self.select.map(|select| select.replace("{}", column))
+    }
+}
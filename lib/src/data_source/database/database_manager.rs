@@ -3,13 +3,41 @@
 //
 
 use std::collections::{hash_map::Entry, HashMap};
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 
+#[cfg(any(feature = "postgres", feature = "mysql"))]
+use crate::data_source::database::impersonation::ImpersonationMode;
+#[cfg(feature = "postgres")]
+use crate::data_source::database::table_provider::ScanPartitioning;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::data_source::database::{any_pool::AnyDatabasePool, engine_type::DatabaseEngineType};
 use crate::data_source::database::{
-    any_pool::AnyDatabasePool, engine_type::DatabaseEngineType, table_resolver::TableResolver,
+    driver::{self, DatabaseDriver},
+    impersonation::Impersonation,
+    table_provider::TableOptions,
+    table_resolver::TableResolver,
 };
 use crate::settings::Database;
+#[cfg(feature = "postgres")]
+use crate::settings::PartitionedTable;
+
+/// Connection settings of a database, from the configuration file or the environment variables.
+struct PoolConfig {
+    namespace: Option<String>,
+    scheme: String,
+    database: String,
+    /// Connection URL, or the database file path of `SQLite`.
+    url: String,
+    schema_cache: bool,
+    schema_cache_ttl: Option<u64>,
+    max_connections: u32,
+    table_options: TableOptions,
+    impersonation: Option<Impersonation>,
+    allow_native_query: bool,
+}
 
 #[derive(Clone, Default)]
 pub struct DatabaseManager {
@@ -19,31 +47,33 @@ pub struct DatabaseManager {
 impl DatabaseManager {
     pub fn new_with_config(
         database_settings: Option<&Vec<Database>>,
+        data_dir: &str,
     ) -> Result<Self, sqlx::error::Error> {
         let mut resolvers: HashMap<String, Arc<TableResolver>> = HashMap::new();
 
         sqlx::any::install_default_drivers();
 
-        Self::from_env(&mut resolvers)?;
+        Self::from_env(&mut resolvers, data_dir)?;
 
         if let Some(databases) = database_settings {
-            Self::from_config(&mut resolvers, databases)?;
+            Self::from_config(&mut resolvers, databases, data_dir)?;
         }
 
         Ok(Self { resolvers })
     }
 
+    #[allow(clippy::too_many_lines)]
     fn from_config(
         resolvers: &mut HashMap<String, Arc<TableResolver>>,
         databases: &Vec<Database>,
+        #[allow(unused_variables)] data_dir: &str,
     ) -> Result<(), sqlx::error::Error> {
         for database in databases {
-            let (namespace, database_name, url, schema_cache, max_connections) = match database {
+            let config = match database {
                 #[cfg(feature = "postgres")]
                 Database::Postgres(postgres) => {
                     let mut url = format!(
-                        "{}://{}:{}@{}:{}/{}",
-                        database.scheme(),
+                        "postgres://{}:{}@{}:{}/{}",
                         &postgres.user,
                         &postgres.password,
                         &postgres.host,
@@ -55,19 +85,31 @@ impl DatabaseManager {
                         url = format!("{url}?sslmode={ssl_mode}");
                     }
 
-                    (
-                        &postgres.namespace,
-                        &postgres.database,
+                    PoolConfig {
+                        namespace: postgres.namespace.clone(),
+                        scheme: "postgres".to_string(),
+                        database: postgres.database.clone(),
+                        schema_cache: postgres.enable_schema_cache.unwrap_or(false),
+                        schema_cache_ttl: postgres.schema_cache_ttl,
+                        max_connections: postgres.max_connections.unwrap_or(10),
+                        table_options: Self::table_options(
+                            postgres.partitioned_tables.as_deref().unwrap_or_default(),
+                            postgres.max_connections.unwrap_or(10),
+                            postgres.fallback_to_string,
+                        ),
+                        impersonation: Self::impersonation(
+                            postgres.impersonation.as_deref(),
+                            &url,
+                            postgres.impersonation_max_connections,
+                        )?,
+                        allow_native_query: postgres.allow_native_query.unwrap_or(false),
                         url,
-                        postgres.enable_schema_cache.unwrap_or(false),
-                        postgres.max_connections.unwrap_or(10),
-                    )
+                    }
                 }
                 #[cfg(feature = "mysql")]
                 Database::MySQL(mysql) => {
                     let mut url = format!(
-                        "{}://{}:{}@{}:{}/{}",
-                        database.scheme(),
+                        "mysql://{}:{}@{}:{}/{}",
                         &mysql.user,
                         &mysql.password,
                         &mysql.host,
@@ -79,133 +121,329 @@ impl DatabaseManager {
                         url = format!("{url}?ssl-mode={ssl_mode}");
                     }
 
-                    (
-                        &mysql.namespace,
-                        &mysql.database,
+                    PoolConfig {
+                        namespace: mysql.namespace.clone(),
+                        scheme: "mysql".to_string(),
+                        database: mysql.database.clone(),
+                        schema_cache: mysql.enable_schema_cache.unwrap_or(false),
+                        schema_cache_ttl: mysql.schema_cache_ttl,
+                        max_connections: mysql.max_connections.unwrap_or(10),
+                        table_options: TableOptions {
+                            fallback_to_string: mysql.fallback_to_string.unwrap_or(true),
+                            ..TableOptions::default()
+                        },
+                        impersonation: Self::impersonation(
+                            mysql.impersonation.as_deref(),
+                            &url,
+                            mysql.impersonation_max_connections,
+                        )?,
+                        allow_native_query: mysql.allow_native_query.unwrap_or(false),
                         url,
-                        mysql.enable_schema_cache.unwrap_or(false),
-                        mysql.max_connections.unwrap_or(10),
-                    )
+                    }
                 }
+                #[cfg(feature = "sqlite")]
+                Database::Sqlite(sqlite) => PoolConfig {
+                    namespace: sqlite.namespace.clone(),
+                    scheme: "sqlite".to_string(),
+                    database: sqlite.path.clone(),
+                    url: Self::sqlite_file(data_dir, &sqlite.path),
+                    schema_cache: sqlite.enable_schema_cache.unwrap_or(false),
+                    schema_cache_ttl: sqlite.schema_cache_ttl,
+                    max_connections: sqlite.max_connections.unwrap_or(10),
+                    // every declared type has the affinity
+                    table_options: TableOptions {
+                        fallback_to_string: true,
+                        ..TableOptions::default()
+                    },
+                    impersonation: None, // opened as read-only file
+                    allow_native_query: sqlite.allow_native_query.unwrap_or(false),
+                },
+                Database::Driver(driver) => PoolConfig {
+                    namespace: driver.namespace.clone(),
+                    scheme: driver.driver.clone(),
+                    database: driver.driver.clone(),
+                    url: driver.url.clone(),
+                    schema_cache: driver.enable_schema_cache.unwrap_or(false),
+                    schema_cache_ttl: driver.schema_cache_ttl,
+                    max_connections: driver.max_connections.unwrap_or(10),
+                    table_options: TableOptions::default(),
+                    impersonation: None,
+                    allow_native_query: false, // not supported by the registered drivers
+                },
             };
 
-            Self::register(
-                resolvers,
-                namespace.as_ref(),
-                database.scheme(),
-                database_name,
-                &url,
-                schema_cache,
-                max_connections,
-            )?;
+            Self::register(resolvers, config)?;
         }
 
         Ok(())
     }
 
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "mysql", feature = "sqlite")),
+        allow(clippy::unnecessary_wraps)
+    )]
     fn from_env(
-        resolvers: &mut HashMap<String, Arc<TableResolver>>,
+        #[allow(unused_variables)] resolvers: &mut HashMap<String, Arc<TableResolver>>,
+        #[allow(unused_variables)] data_dir: &str,
     ) -> Result<(), sqlx::error::Error> {
         #[cfg(feature = "postgres")]
-        if env::var("POSTGRES_USER").is_ok()
-            && env::var("POSTGRES_PASSWORD").is_ok()
-            && env::var("POSTGRES_HOST").is_ok()
-            && env::var("POSTGRES_DATABASE").is_ok()
-        {
-            let mut url = format!(
-                "postgres://{}:{}@{}:{}/{}",
-                env::var("POSTGRES_USER").unwrap(),
-                env::var("POSTGRES_PASSWORD").unwrap(),
-                env::var("POSTGRES_HOST").unwrap(),
-                env::var("POSTGRES_PORT")
-                    .unwrap_or_default()
-                    .parse::<u16>()
-                    .unwrap_or(5432),
-                env::var("POSTGRES_DATABASE").unwrap(),
-            );
-
-            if let Ok(ssl_mode) = &env::var("POSTGRES_SSL_MODE") {
-                url = format!("{url}?sslmode={ssl_mode}");
-            }
-
-            Self::register(
-                resolvers,
-                Some(&env::var("POSTGRES_NAMESPACE").unwrap_or("postgres".to_string())),
-                "postgres",
-                &env::var("POSTGRES_DATABASE").unwrap(),
-                &url,
-                env::var("POSTGRES_ENABLE_SCHEMA_CACHE")
-                    .unwrap_or_default()
-                    .to_lowercase()
-                    == "true",
-                env::var("POSTGRES_MAX_CONNECTIONS")
-                    .unwrap_or_default()
-                    .parse::<u32>()
-                    .unwrap_or(10),
-            )?;
+        if let Some(config) = Self::postgres_env()? {
+            Self::register(resolvers, config)?;
         }
 
         #[cfg(feature = "mysql")]
-        if env::var("MYSQL_USER").is_ok()
-            && env::var("MYSQL_PASSWORD").is_ok()
-            && env::var("MYSQL_HOST").is_ok()
-            && env::var("MYSQL_DATABASE").is_ok()
-        {
-            let mut url = format!(
-                "mysql://{}:{}@{}:{}/{}",
-                env::var("MYSQL_USER").unwrap(),
-                env::var("MYSQL_PASSWORD").unwrap(),
-                env::var("MYSQL_HOST").unwrap(),
-                env::var("MYSQL_PORT")
-                    .unwrap_or_default()
-                    .parse::<u16>()
-                    .unwrap_or(3306),
-                env::var("MYSQL_DATABASE").unwrap(),
-            );
-
-            if let Ok(ssl_mode) = &env::var("MYSQL_SSL_MODE") {
-                url = format!("{url}?ssl-mode={ssl_mode}");
-            }
+        if let Some(config) = Self::mysql_env()? {
+            Self::register(resolvers, config)?;
+        }
 
+        #[cfg(feature = "sqlite")]
+        if let Ok(path) = env::var("SQLITE_PATH") {
             Self::register(
                 resolvers,
-                Some(&env::var("MYSQL_NAMESPACE").unwrap_or("mysql".to_string())),
-                "mysql",
-                &env::var("MYSQL_DATABASE").unwrap(),
-                &url,
-                env::var("MYSQL_ENABLE_SCHEMA_CACHE")
-                    .unwrap_or_default()
-                    .to_lowercase()
-                    == "true",
-                env::var("MYSQL_MAX_CONNECTIONS")
-                    .unwrap_or_default()
-                    .parse::<u32>()
-                    .unwrap_or(10),
+                PoolConfig {
+                    namespace: Some(env::var("SQLITE_NAMESPACE").unwrap_or("sqlite".to_string())),
+                    scheme: "sqlite".to_string(),
+                    url: Self::sqlite_file(data_dir, &path),
+                    schema_cache: Self::env_flag("SQLITE_ENABLE_SCHEMA_CACHE").unwrap_or(false),
+                    schema_cache_ttl: Self::env_parse("SQLITE_SCHEMA_CACHE_TTL"),
+                    max_connections: Self::env_parse("SQLITE_MAX_CONNECTIONS").unwrap_or(10),
+                    table_options: TableOptions {
+                        fallback_to_string: true,
+                        ..TableOptions::default()
+                    },
+                    impersonation: None,
+                    allow_native_query: Self::env_flag("SQLITE_ALLOW_NATIVE_QUERY")
+                        .unwrap_or(false),
+                    database: path,
+                },
             )?;
         }
 
         Ok(())
     }
 
-    fn register(
-        resolvers: &mut HashMap<String, Arc<TableResolver>>,
-        namespace: Option<&String>,
-        scheme: &str,
-        database: &str,
+    #[cfg(feature = "postgres")]
+    fn postgres_env() -> Result<Option<PoolConfig>, sqlx::error::Error> {
+        let (Ok(user), Ok(password), Ok(host), Ok(database)) = (
+            env::var("POSTGRES_USER"),
+            env::var("POSTGRES_PASSWORD"),
+            env::var("POSTGRES_HOST"),
+            env::var("POSTGRES_DATABASE"),
+        ) else {
+            return Ok(None);
+        };
+
+        let mut url = format!(
+            "postgres://{user}:{password}@{host}:{}/{database}",
+            Self::env_parse::<u16>("POSTGRES_PORT").unwrap_or(5432),
+        );
+
+        if let Ok(ssl_mode) = &env::var("POSTGRES_SSL_MODE") {
+            url = format!("{url}?sslmode={ssl_mode}");
+        }
+
+        Ok(Some(PoolConfig {
+            namespace: Some(env::var("POSTGRES_NAMESPACE").unwrap_or("postgres".to_string())),
+            scheme: "postgres".to_string(),
+            database,
+            schema_cache: Self::env_flag("POSTGRES_ENABLE_SCHEMA_CACHE").unwrap_or(false),
+            schema_cache_ttl: Self::env_parse("POSTGRES_SCHEMA_CACHE_TTL"),
+            max_connections: Self::env_parse("POSTGRES_MAX_CONNECTIONS").unwrap_or(10),
+            table_options: Self::table_options(
+                &Self::partitioned_tables_env("POSTGRES_PARTITIONED_TABLES"),
+                Self::env_parse("POSTGRES_MAX_CONNECTIONS").unwrap_or(10),
+                Self::env_flag("POSTGRES_FALLBACK_TO_STRING"),
+            ),
+            impersonation: Self::impersonation(
+                env::var("POSTGRES_IMPERSONATION").ok().as_deref(),
+                &url,
+                Self::env_parse("POSTGRES_IMPERSONATION_MAX_CONNECTIONS"),
+            )?,
+            allow_native_query: Self::env_flag("POSTGRES_ALLOW_NATIVE_QUERY").unwrap_or(false),
+            url,
+        }))
+    }
+
+    #[cfg(feature = "mysql")]
+    fn mysql_env() -> Result<Option<PoolConfig>, sqlx::error::Error> {
+        let (Ok(user), Ok(password), Ok(host), Ok(database)) = (
+            env::var("MYSQL_USER"),
+            env::var("MYSQL_PASSWORD"),
+            env::var("MYSQL_HOST"),
+            env::var("MYSQL_DATABASE"),
+        ) else {
+            return Ok(None);
+        };
+
+        let mut url = format!(
+            "mysql://{user}:{password}@{host}:{}/{database}",
+            Self::env_parse::<u16>("MYSQL_PORT").unwrap_or(3306),
+        );
+
+        if let Ok(ssl_mode) = &env::var("MYSQL_SSL_MODE") {
+            url = format!("{url}?ssl-mode={ssl_mode}");
+        }
+
+        Ok(Some(PoolConfig {
+            namespace: Some(env::var("MYSQL_NAMESPACE").unwrap_or("mysql".to_string())),
+            scheme: "mysql".to_string(),
+            database,
+            schema_cache: Self::env_flag("MYSQL_ENABLE_SCHEMA_CACHE").unwrap_or(false),
+            schema_cache_ttl: Self::env_parse("MYSQL_SCHEMA_CACHE_TTL"),
+            max_connections: Self::env_parse("MYSQL_MAX_CONNECTIONS").unwrap_or(10),
+            table_options: TableOptions {
+                fallback_to_string: Self::env_flag("MYSQL_FALLBACK_TO_STRING").unwrap_or(true),
+                ..TableOptions::default()
+            },
+            impersonation: Self::impersonation(
+                env::var("MYSQL_IMPERSONATION").ok().as_deref(),
+                &url,
+                Self::env_parse("MYSQL_IMPERSONATION_MAX_CONNECTIONS"),
+            )?,
+            allow_native_query: Self::env_flag("MYSQL_ALLOW_NATIVE_QUERY").unwrap_or(false),
+            url,
+        }))
+    }
+
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+        env::var(name)
+            .ok()
+            .and_then(|value| value.parse::<T>().ok())
+    }
+
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    fn env_flag(name: &str) -> Option<bool> {
+        env::var(name)
+            .ok()
+            .map(|value| value.to_lowercase() == "true")
+    }
+
+    /// Database file is placed under the data directory.
+    #[cfg(feature = "sqlite")]
+    fn sqlite_file(data_dir: &str, path: &str) -> String {
+        std::path::Path::new(data_dir)
+            .join(path.trim_start_matches('/'))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    fn impersonation(
+        mode: Option<&str>,
         url: &str,
-        schema_cache: bool,
+        max_connections: Option<u32>,
+    ) -> Result<Option<Impersonation>, sqlx::error::Error> {
+        mode.map(|mode| {
+            Ok(Impersonation::new(
+                ImpersonationMode::from_name(mode)?,
+                url,
+                max_connections.unwrap_or(2),
+            ))
+        })
+        .transpose()
+    }
+
+    /// The partitions are limited by the connections, one of which holds the snapshot shared by the others.
+    #[cfg(feature = "postgres")]
+    fn table_options(
+        partitioned_tables: &[PartitionedTable],
         max_connections: u32,
+        fallback_to_string: Option<bool>,
+    ) -> TableOptions {
+        let max_partitions =
+            usize::try_from(max_connections.saturating_sub(1)).unwrap_or(usize::MAX);
+
+        TableOptions {
+            partitioning: partitioned_tables
+                .iter()
+                .map(|table| {
+                    (
+                        table.table.clone(),
+                        ScanPartitioning {
+                            partitions: table.partitions.min(max_partitions),
+                            column: table.column.clone(),
+                        },
+                    )
+                })
+                .filter(|(_, partitioning)| partitioning.partitions > 1)
+                .collect(),
+            fallback_to_string: fallback_to_string.unwrap_or(true),
+        }
+    }
+
+    /// Comma separated `table:column:partitions`, e.g. `public.orders:id:4,items:item_id:8`.
+    #[cfg(feature = "postgres")]
+    fn partitioned_tables_env(name: &str) -> Vec<PartitionedTable> {
+        env::var(name)
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|table| {
+                let mut parts = table.trim().rsplitn(3, ':');
+                let partitions = parts.next()?.parse::<usize>().ok()?;
+                let column = parts.next()?.to_string();
+                let table = parts.next()?.to_string();
+
+                Some(PartitionedTable {
+                    table,
+                    column,
+                    partitions,
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    fn connect(config: &PoolConfig) -> Result<Arc<dyn DatabaseDriver>, sqlx::error::Error> {
+        Ok(match DatabaseEngineType::from_scheme(&config.scheme) {
+            // the path is not a part of URL, may contain '?' or '#'
+            #[cfg(feature = "sqlite")]
+            Ok(DatabaseEngineType::Sqlite) => Arc::new(AnyDatabasePool::new_sqlite(
+                std::path::Path::new(&config.url),
+                config.max_connections,
+            )),
+            #[allow(unreachable_patterns)]
+            Ok(_) => Arc::new(AnyDatabasePool::new(&config.url, config.max_connections)?),
+            // not a built-in engine, the registered driver of the name
+            Err(_) => Self::connect_driver(config)?,
+        })
+    }
+
+    #[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+    fn connect(config: &PoolConfig) -> Result<Arc<dyn DatabaseDriver>, sqlx::error::Error> {
+        Self::connect_driver(config)
+    }
+
+    fn connect_driver(config: &PoolConfig) -> Result<Arc<dyn DatabaseDriver>, sqlx::error::Error> {
+        driver::connect(&config.scheme, &config.url, config.max_connections)
+            .map_err(|e| sqlx::Error::Configuration(Box::new(e)))
+    }
+
+    fn register(
+        resolvers: &mut HashMap<String, Arc<TableResolver>>,
+        config: PoolConfig,
     ) -> Result<(), sqlx::error::Error> {
-        let key = namespace.map_or_else(|| scheme.to_string(), std::clone::Clone::clone);
+        let key = config.namespace.clone().unwrap_or(config.scheme.clone());
         log::debug!("Create '{key}' database connection pool");
 
         if let Entry::Vacant(entry) = resolvers.entry(key.clone()) {
-            entry.insert(Arc::new(TableResolver::new(
-                DatabaseEngineType::from_scheme(scheme)?,
-                AnyDatabasePool::new(url, max_connections)?,
-                database,
-                schema_cache,
-            )));
+            let driver = Self::connect(&config)?;
+
+            entry.insert(Arc::new(
+                TableResolver::new(
+                    driver,
+                    &key,
+                    &config.database,
+                    config.schema_cache,
+                    config
+                        .schema_cache_ttl
+                        .filter(|ttl| *ttl > 0)
+                        .map(Duration::from_secs),
+                    config.table_options,
+                    config.impersonation,
+                )
+                .with_native_query(config.allow_native_query),
+            ));
         } else {
             log::error!("Duplicated database connection pool '{key}'");
         }
@@ -213,3 +451,47 @@ impl DatabaseManager {
         Ok(())
     }
 }
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use crate::data_source::database::{
+        any_pool::{AnyDatabasePool, DatabaseOperator},
+        database_manager::DatabaseManager,
+    };
+
+    #[tokio::test]
+    async fn sqlite_file_name_not_parsed_as_url() {
+        let data_dir = std::env::temp_dir().join(format!(
+            "datafusion-server-sqlite-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&data_dir).unwrap();
+
+        let file = DatabaseManager::sqlite_file(data_dir.to_str().unwrap(), "/sales #1?.db");
+        let writer = sqlx::SqlitePool::connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(&file)
+                .create_if_missing(true),
+        )
+        .await
+        .unwrap();
+        sqlx::query("CREATE TABLE sales (id INTEGER)")
+            .execute(&writer)
+            .await
+            .unwrap();
+        writer.close().await;
+
+        let pool = AnyDatabasePool::new_sqlite(std::path::Path::new(&file), 1);
+        let row = pool
+            .fetch_one("SELECT COUNT(*) AS n FROM sales")
+            .await
+            .unwrap();
+        assert_eq!(row.get::<i64>("n").unwrap(), Some(0));
+        assert!(pool
+            .fetch_all("INSERT INTO sales VALUES (1)")
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(&data_dir).unwrap();
+    }
+}
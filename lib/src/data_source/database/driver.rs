@@ -0,0 +1,368 @@
+// database/driver.rs: Pluggable database driver for the external database engines
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::{Debug, Write};
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait; // TODO: Replace in the future when the Rust compiler's async trait supports object safety.
+use datafusion::{
+    arrow::datatypes::{DataType, Field, Schema, SchemaRef},
+    catalog::Session,
+    common::DFSchema,
+    datasource::{TableProvider, TableType},
+    error::DataFusionError,
+    execution::TaskContext,
+    logical_expr::{Expr, TableProviderFilterPushDown},
+    physical_plan::{
+        streaming::{PartitionStream, StreamingTableExec},
+        ExecutionPlan, SendableRecordBatchStream,
+    },
+    scalar::ScalarValue,
+    sql::{
+        sqlparser::dialect::{Dialect as ParserDialect, GenericDialect},
+        unparser::dialect::Dialect,
+    },
+};
+use once_cell::sync::Lazy;
+
+use crate::data_source::database::table_provider::{
+    filter_to_sql, quote_identifier, quote_table_name, unqualify,
+};
+
+pub struct TableColumn {
+    pub table_schema: String,
+    pub table_name: String,
+    pub column_name: String,
+    pub data_type: String,
+    pub nullable: bool,
+}
+
+/// Bind parameter placeholder of the native query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    // `?` for each parameter
+    Positional,
+    // `$1`, `$2`, ... reused by the same parameter
+    Numbered,
+}
+
+/// Save mode of the table written by `write`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteMode {
+    ErrorIfExists,
+    Append,
+    Overwrite,
+    Upsert,
+}
+
+/// Remote table written by `write`, the columns are of the schema of the record batches.
+#[derive(Debug, Clone)]
+pub struct WriteTarget {
+    pub table_name: String,
+    pub columns: Vec<String>,
+    pub primary_keys: Vec<String>,
+    pub mode: WriteMode,
+}
+
+/// Database engine accessed by the namespace, the built-in engines (`PostgreSQL`, `MySQL`, `SQLite`)
+/// are implemented by the connection pool, and other engines can be registered by `register`.
+#[async_trait]
+pub trait DatabaseDriver: Debug + Send + Sync + 'static {
+    /// Creates the connection pool of URL, connections are established by the first query.
+    ///
+    /// # Errors
+    /// * Invalid URL or options of the database engine
+    fn connect(url: &str, max_connections: u32) -> Result<Self, DataFusionError>
+    where
+        Self: Sized;
+
+    /// SQL dialect to unparse the remote queries.
+    fn dialect(&self) -> Box<dyn Dialect>;
+
+    /// Arrow data type of the remote column type, `None` if not supported.
+    fn to_arrow_type(&self, type_name: &str) -> Option<DataType>;
+
+    /// Checks the connectivity by a trivial query.
+    async fn ping(&self) -> Result<(), DataFusionError>;
+
+    /// Pairs of schema and table name.
+    async fn table_list(&self) -> Result<Vec<(String, String)>, DataFusionError>;
+
+    /// Columns of the table (e.g. `table` or `schema.table`), or of every table if `None`,
+    /// ordered by the schema, table and column position.
+    async fn table_columns(
+        &self,
+        table_name: Option<&str>,
+    ) -> Result<Vec<TableColumn>, DataFusionError>;
+
+    /// Schema of the table (e.g. `table` or `schema.table`), columns of unsupported types are excluded.
+    async fn table_schema(&self, table_name: &str) -> Result<SchemaRef, DataFusionError> {
+        let fields = self
+            .table_columns(Some(table_name))
+            .await?
+            .into_iter()
+            .filter_map(|column| {
+                self.to_arrow_type(&column.data_type)
+                    .map(|data_type| Field::new(column.column_name, data_type, column.nullable))
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Arc::new(Schema::new(fields)))
+    }
+
+    /// Streams the result of remote query into record batches of the schema.
+    fn fetch(&self, sql: &str, schema: SchemaRef) -> SendableRecordBatchStream;
+
+    /// Whether the filter or the expression of the federated query (columns unqualified) is
+    /// evaluated by the remote query. `Exact` only if the engine evaluates it the same as `DataFusion`,
+    /// the subplans are federated only when all expressions are `Exact`.
+    #[allow(unused_variables)]
+    fn pushdown(&self, expr: &Expr, schema: &DFSchema) -> TableProviderFilterPushDown {
+        match filter_to_sql(self.dialect().as_ref(), expr) {
+            Ok(_) => TableProviderFilterPushDown::Inexact,
+            Err(_) => TableProviderFilterPushDown::Unsupported,
+        }
+    }
+
+    /// Whether the strings are compared by the collation of the engine, the equality if `ordering` is false.
+    /// Joins, groups and sorts of such strings are not federated.
+    #[allow(unused_variables)]
+    fn collates_strings(&self, ordering: bool) -> bool {
+        true
+    }
+
+    /// SQL dialect to tokenize the native query, the bind parameters in the literals and
+    /// the comments are left as is.
+    fn parser_dialect(&self) -> Box<dyn ParserDialect> {
+        Box::new(GenericDialect {})
+    }
+
+    /// Placeholder of the bind parameters of `query`.
+    fn placeholder(&self) -> Placeholder {
+        Placeholder::Positional
+    }
+
+    /// Executes the native query with the bind parameters, the schema is given by the result columns.
+    /// The query is executed in a read-only transaction if `read_only`, the parser never prevents
+    /// the functions of the query from modifying the database.
+    #[allow(unused_variables)]
+    async fn query(
+        &self,
+        sql: &str,
+        params: Vec<ScalarValue>,
+        read_only: bool,
+    ) -> Result<SendableRecordBatchStream, DataFusionError> {
+        Err(DataFusionError::NotImplemented(
+            "Native query is not supported by the database driver".to_string(),
+        ))
+    }
+
+    /// Writes the record batches to the remote table by the save mode.
+    #[allow(unused_variables)]
+    async fn write(
+        &self,
+        target: &WriteTarget,
+        stream: SendableRecordBatchStream,
+    ) -> Result<(), DataFusionError> {
+        Err(DataFusionError::NotImplemented(
+            "Saving is not supported by the database driver".to_string(),
+        ))
+    }
+
+    /// Number of the open connections and the idle connections.
+    fn connections(&self) -> (u32, usize) {
+        (0, 0)
+    }
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+type Connector = fn(&str, u32) -> Result<Arc<dyn DatabaseDriver>, DataFusionError>;
+
+static DRIVERS: Lazy<RwLock<HashMap<String, Connector>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Registers the driver of the database engine, used by `[[databases]]` of `type = "driver"`
+/// with the same `driver` name. Must be registered before the server is started.
+///
+/// ## Panics
+/// * Driver registry is poisoned
+pub fn register<D: DatabaseDriver>(name: &str) {
+    DRIVERS
+        .write()
+        .unwrap()
+        .insert(name.to_string(), connect_driver::<D>);
+}
+
+fn connect_driver<D: DatabaseDriver>(
+    url: &str,
+    max_connections: u32,
+) -> Result<Arc<dyn DatabaseDriver>, DataFusionError> {
+    Ok(Arc::new(D::connect(url, max_connections)?))
+}
+
+pub(super) fn connect(
+    name: &str,
+    url: &str,
+    max_connections: u32,
+) -> Result<Arc<dyn DatabaseDriver>, DataFusionError> {
+    let Some(connector) = DRIVERS.read().unwrap().get(name).copied() else {
+        return Err(DataFusionError::Configuration(format!(
+            "Unregistered database driver: {name}"
+        )));
+    };
+
+    connector(url, max_connections)
+}
+
+/// Table of the registered driver, scanned by a single remote query with the projection,
+/// filters and limit.
+#[derive(Debug)]
+pub(super) struct DriverTable {
+    driver: Arc<dyn DatabaseDriver>,
+    namespace: String,
+    table_name: String,
+    schema: SchemaRef,
+}
+
+impl DriverTable {
+    pub(super) async fn new(
+        driver: Arc<dyn DatabaseDriver>,
+        namespace: &str,
+        table_name: &str,
+    ) -> Result<Self, DataFusionError> {
+        let schema = driver.table_schema(table_name).await?;
+
+        Ok(Self {
+            driver,
+            namespace: namespace.to_string(),
+            table_name: table_name.to_string(),
+            schema,
+        })
+    }
+
+    pub(super) fn driver(&self) -> &Arc<dyn DatabaseDriver> {
+        &self.driver
+    }
+
+    pub(super) fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub(super) fn table_name(&self) -> &str {
+        &self.table_name
+    }
+}
+
+#[async_trait]
+impl TableProvider for DriverTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let projected_schema = Arc::new(match projection {
+            Some(projection) => self.schema.project(projection)?,
+            None => self.schema.as_ref().clone(),
+        });
+
+        let dialect = self.driver.dialect();
+
+        let select_list = if projected_schema.fields().is_empty() {
+            "1".to_string() // e.g. `COUNT(*)`
+        } else {
+            projected_schema
+                .fields()
+                .iter()
+                .map(|field| quote_identifier(dialect.as_ref(), field.name()))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut sql = format!(
+            "SELECT {select_list} FROM {}",
+            quote_table_name(dialect.as_ref(), &self.table_name)
+        );
+
+        let conditions = filters
+            .iter()
+            .map(|filter| Ok(format!("({})", filter_to_sql(dialect.as_ref(), filter)?)))
+            .collect::<Result<Vec<_>, DataFusionError>>()?;
+
+        if !conditions.is_empty() {
+            write!(&mut sql, " WHERE {}", conditions.join(" AND "))?;
+        }
+
+        if let Some(limit) = limit {
+            write!(&mut sql, " LIMIT {limit}")?;
+        }
+
+        let stream = DriverScanStream::new(self.driver.clone(), sql, projected_schema.clone());
+
+        Ok(Arc::new(StreamingTableExec::try_new(
+            projected_schema,
+            vec![Arc::new(stream) as Arc<dyn PartitionStream>],
+            None,
+            vec![],
+            false,
+            None,
+        )?))
+    }
+
+    /// Filters are re-evaluated unless the driver reports the same comparison semantics.
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>, DataFusionError> {
+        let schema = DFSchema::try_from(self.schema.as_ref().clone())?;
+
+        Ok(filters
+            .iter()
+            .map(|filter| self.driver.pushdown(&unqualify(filter), &schema))
+            .collect())
+    }
+}
+
+/// Result of the remote query of the driver.
+#[derive(Debug)]
+pub(super) struct DriverScanStream {
+    driver: Arc<dyn DatabaseDriver>,
+    sql: String,
+    schema: SchemaRef,
+}
+
+impl DriverScanStream {
+    pub(super) fn new(driver: Arc<dyn DatabaseDriver>, sql: String, schema: SchemaRef) -> Self {
+        Self {
+            driver,
+            sql,
+            schema,
+        }
+    }
+}
+
+impl PartitionStream for DriverScanStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        log::debug!("Remote query: {}", self.sql);
+        self.driver.fetch(&self.sql, self.schema.clone())
+    }
+}
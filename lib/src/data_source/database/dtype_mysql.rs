@@ -5,56 +5,176 @@
 #[cfg(feature = "mysql")]
 use datafusion::arrow::datatypes::{DataType, TimeUnit};
 
+#[cfg(feature = "mysql")]
+use crate::data_source::database::column_type::{ColumnType, JSON_EXTENSION, WKB_EXTENSION};
+
+/// Selects the column as text, used for the types not decodable natively.
+#[cfg(feature = "mysql")]
+pub const TEXT_SELECT: &str = "CAST({} AS CHAR)";
+
+/// Maps `data_type` of `information_schema.columns`, returns `None` if the type is not supported.
 #[cfg(feature = "mysql")]
 pub fn to_arrow_dtype(
     mysql_dtype: &str,
     precision: Option<i16>,
     scale: Option<i8>,
     signed: bool,
-) -> DataType {
-    match mysql_dtype {
-        "tinyint" => {
-            if signed {
-                DataType::Int8
-            } else {
-                DataType::UInt8
+) -> Option<ColumnType> {
+    #[allow(clippy::match_same_arms)]
+    Some(match mysql_dtype {
+        "tinyint" => ColumnType::new(if signed {
+            DataType::Int8
+        } else {
+            DataType::UInt8
+        }),
+        "smallint" => ColumnType::new(if signed {
+            DataType::Int16
+        } else {
+            DataType::UInt16
+        }),
+        "int" | "mediumint" => ColumnType::new(if signed {
+            DataType::Int32
+        } else {
+            DataType::UInt32
+        }),
+        "bigint" => ColumnType::new(if signed {
+            DataType::Int64
+        } else {
+            DataType::UInt64
+        }),
+        "year" => ColumnType::new(DataType::Int64).with_select("CAST({} AS SIGNED)"),
+        "bit" => ColumnType::new(DataType::UInt64).with_select("CAST({} AS UNSIGNED)"),
+        "decimal" => match u8::try_from(precision.unwrap_or(38)) {
+            Ok(precision) if precision <= 38 => {
+                ColumnType::new(DataType::Decimal128(precision, scale.unwrap_or(10)))
             }
+            // exceeds the precision of Decimal128
+            _ => ColumnType::new(DataType::Utf8).with_select(TEXT_SELECT),
+        },
+        "float" => ColumnType::new(DataType::Float32),
+        "double" => ColumnType::new(DataType::Float64),
+        "char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" => {
+            ColumnType::new(DataType::Utf8)
         }
-        "smallint" => {
-            if signed {
-                DataType::Int16
-            } else {
-                DataType::UInt16
-            }
+        "enum" | "set" => ColumnType::new(DataType::Utf8).with_select(TEXT_SELECT),
+        "json" => ColumnType::new(DataType::Utf8)
+            .with_select(TEXT_SELECT)
+            .with_extension(JSON_EXTENSION),
+        "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => {
+            ColumnType::new(DataType::Binary)
         }
-        "int" | "mediumint" => {
-            if signed {
-                DataType::Int32
-            } else {
-                DataType::UInt32
-            }
+        "geometry" | "point" | "linestring" | "polygon" | "multipoint" | "multilinestring"
+        | "multipolygon" | "geometrycollection" | "geomcollection" => {
+            ColumnType::new(DataType::Binary)
+                .with_select("ST_AsBinary({})")
+                .with_extension(WKB_EXTENSION)
         }
-        "bigint" => {
-            if signed {
-                DataType::Int64
-            } else {
-                DataType::UInt64
-            }
+        "timestamp" => ColumnType::new(DataType::Timestamp(
+            TimeUnit::Microsecond,
+            Some("UTC".into()),
+        )),
+        "datetime" => ColumnType::new(DataType::Timestamp(TimeUnit::Microsecond, None)),
+        "date" => ColumnType::new(DataType::Date32),
+        "time" => ColumnType::new(DataType::Time64(TimeUnit::Microsecond)),
+        _ => return None,
+    })
+}
+
+/// Maps the type name of result column (e.g. `INT UNSIGNED`) of native query,
+/// returns `None` if the type is not decodable as is.
+#[cfg(feature = "mysql")]
+pub fn from_type_name(type_name: &str) -> Option<ColumnType> {
+    let (name, signed) = match type_name.strip_suffix(" UNSIGNED") {
+        Some(name) => (name, false),
+        None => (type_name, true),
+    };
+
+    let lowercase_name = name.to_lowercase();
+    let dtype = match name {
+        "BOOLEAN" => "tinyint",
+        // ENUM is decodable as string
+        "ENUM" => "varchar",
+        _ => &lowercase_name,
+    };
+
+    to_arrow_dtype(dtype, None, None, signed).filter(|column_type| column_type.select.is_none())
+}
+
+/// Column type of `CREATE TABLE` for the Arrow data type, key columns require the length.
+#[cfg(feature = "mysql")]
+pub fn from_arrow_dtype(dtype: &DataType, key: bool) -> Option<String> {
+    Some(match dtype {
+        DataType::Boolean => "BOOLEAN".to_string(),
+        DataType::Int8 => "TINYINT".to_string(),
+        DataType::Int16 => "SMALLINT".to_string(),
+        DataType::Int32 => "INT".to_string(),
+        DataType::Int64 => "BIGINT".to_string(),
+        DataType::UInt8 => "TINYINT UNSIGNED".to_string(),
+        DataType::UInt16 => "SMALLINT UNSIGNED".to_string(),
+        DataType::UInt32 => "INT UNSIGNED".to_string(),
+        DataType::UInt64 => "BIGINT UNSIGNED".to_string(),
+        DataType::Float16 | DataType::Float32 => "FLOAT".to_string(),
+        DataType::Float64 => "DOUBLE".to_string(),
+        DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+            format!("DECIMAL({}, {scale})", (*precision).min(65))
+        }
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => {
+            if key { "VARCHAR(255)" } else { "LONGTEXT" }.to_string()
         }
-        #[allow(clippy::cast_sign_loss)]
-        "decimal" => DataType::Decimal128(
-            u8::try_from(precision.unwrap_or(38)).unwrap_or(255),
-            scale.unwrap_or(10),
-        ),
-        "float" => DataType::Float32,
-        "double" => DataType::Float64,
-        // "bit" => DataType::Float64,
-        "char" | "varchar" | "text" => DataType::Utf8,
-        "binary" | "varbinary" | "blob" => DataType::Binary,
-        "timestamp" => DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
-        "datetime" => DataType::Timestamp(TimeUnit::Microsecond, None),
-        "date" => DataType::Date32,
-        "time" => DataType::Time64(TimeUnit::Microsecond),
-        _ => unimplemented!("Unsupported data type: {}", mysql_dtype),
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView => {
+            if key { "VARBINARY(255)" } else { "LONGBLOB" }.to_string()
+        }
+        DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+        DataType::Time32(_) | DataType::Time64(_) => "TIME(6)".to_string(),
+        DataType::Timestamp(_, _) => "DATETIME(6)".to_string(),
+        _ => return None,
+    })
+}
+
+#[cfg(all(test, feature = "mysql"))]
+mod tests {
+    use datafusion::arrow::datatypes::{DataType, TimeUnit};
+
+    use crate::data_source::database::dtype_mysql::from_arrow_dtype;
+
+    #[test]
+    fn create_column_types() {
+        assert_eq!(
+            from_arrow_dtype(&DataType::UInt16, false).unwrap(),
+            "SMALLINT UNSIGNED"
+        );
+        assert_eq!(
+            from_arrow_dtype(&DataType::Float64, false).unwrap(),
+            "DOUBLE"
+        );
+        assert_eq!(
+            from_arrow_dtype(&DataType::Decimal256(76, 10), false).unwrap(),
+            "DECIMAL(65, 10)"
+        );
+        assert_eq!(
+            from_arrow_dtype(&DataType::Timestamp(TimeUnit::Second, None), false).unwrap(),
+            "DATETIME(6)"
+        );
+    }
+
+    #[test]
+    fn create_key_column_types() {
+        assert_eq!(
+            from_arrow_dtype(&DataType::Utf8, false).unwrap(),
+            "LONGTEXT"
+        );
+        assert_eq!(
+            from_arrow_dtype(&DataType::Utf8, true).unwrap(),
+            "VARCHAR(255)"
+        );
+        assert_eq!(
+            from_arrow_dtype(&DataType::Binary, true).unwrap(),
+            "VARBINARY(255)"
+        );
+    }
+
+    #[test]
+    fn no_supported_column_type() {
+        assert!(from_arrow_dtype(&DataType::Null, false).is_none());
     }
 }
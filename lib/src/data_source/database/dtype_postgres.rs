@@ -3,40 +3,226 @@
 //
 
 #[cfg(feature = "postgres")]
-use datafusion::arrow::datatypes::{DataType, TimeUnit};
+use datafusion::arrow::datatypes::{DataType, Field, IntervalUnit, TimeUnit};
 
+#[cfg(feature = "postgres")]
+use crate::data_source::database::column_type::{ColumnType, JSON_EXTENSION, WKB_EXTENSION};
+
+/// Selects the column as text, used for the types not decodable natively.
+#[cfg(feature = "postgres")]
+pub const TEXT_SELECT: &str = "{}::text";
+
+/// Maps `data_type` of `information_schema.columns`, arrays and user-defined types are resolved by `udt_name`.
+/// Returns `None` if the type is not supported.
 #[cfg(feature = "postgres")]
 pub fn to_arrow_dtype(
     postgres_dtype: &str,
+    udt_name: &str,
     precision: Option<i16>,
     scale: Option<i8>,
     _signed: bool,
-) -> DataType {
+) -> Option<ColumnType> {
     #[allow(clippy::match_same_arms)]
-    match postgres_dtype {
-        "smallint" | "smallserial" => DataType::Int16,
-        "integer" | "serial" => DataType::Int32,
-        "bigint" | "bigserial" => DataType::Int64,
-        "numeric" => DataType::Decimal128(
-            u8::try_from(precision.unwrap_or(38)).unwrap_or(255),
-            scale.unwrap_or(10),
-        ),
-        "real" => DataType::Float32,
-        "double precision" | "money" => DataType::Float64,
-        "character varying" | "character" | "bpchar" | "text" => DataType::Utf8,
-        "bytea" => DataType::Binary,
-        "timestamp with time zone" => {
-            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+    Some(match postgres_dtype {
+        "smallint" | "smallserial" => ColumnType::new(DataType::Int16),
+        "integer" | "serial" => ColumnType::new(DataType::Int32),
+        "bigint" | "bigserial" => ColumnType::new(DataType::Int64),
+        "oid" => ColumnType::new(DataType::Int64).with_select("{}::int8"),
+        "numeric" => match u8::try_from(precision.unwrap_or(38)) {
+            Ok(precision) if precision <= 38 => {
+                ColumnType::new(DataType::Decimal128(precision, scale.unwrap_or(10)))
+            }
+            // exceeds the precision of Decimal128
+            _ => ColumnType::new(DataType::Utf8).with_select(TEXT_SELECT),
+        },
+        "real" => ColumnType::new(DataType::Float32),
+        "double precision" => ColumnType::new(DataType::Float64),
+        "money" => ColumnType::new(DataType::Float64).with_select("{}::numeric::float8"),
+        "character varying" | "character" | "bpchar" | "text" | "name" => {
+            ColumnType::new(DataType::Utf8)
         }
+        "\"char\"" => ColumnType::new(DataType::Utf8).with_select(TEXT_SELECT),
+        "bytea" => ColumnType::new(DataType::Binary),
+        "timestamp with time zone" => ColumnType::new(DataType::Timestamp(
+            TimeUnit::Microsecond,
+            Some("UTC".into()),
+        )),
         "timestamp" | "timestamp without time zone" => {
-            DataType::Timestamp(TimeUnit::Microsecond, None)
+            ColumnType::new(DataType::Timestamp(TimeUnit::Microsecond, None))
+        }
+        "date" => ColumnType::new(DataType::Date32),
+        "time with time zone" => {
+            ColumnType::new(DataType::Time64(TimeUnit::Microsecond)).with_select("{}::time")
+        }
+        "time" | "time without time zone" => {
+            ColumnType::new(DataType::Time64(TimeUnit::Microsecond))
+        }
+        "interval" => ColumnType::new(DataType::Interval(IntervalUnit::MonthDayNano)),
+        "boolean" => ColumnType::new(DataType::Boolean),
+        "uuid" => ColumnType::new(DataType::Utf8),
+        "json" | "jsonb" => ColumnType::new(DataType::Utf8)
+            .with_select(TEXT_SELECT)
+            .with_extension(JSON_EXTENSION),
+        "inet" | "cidr" | "macaddr" | "macaddr8" | "xml" | "bit" | "bit varying" | "tsvector"
+        | "tsquery" | "point" | "line" | "lseg" | "box" | "path" | "polygon" | "circle"
+        | "int4range" | "int8range" | "numrange" | "tsrange" | "tstzrange" | "daterange"
+        | "int4multirange" | "int8multirange" | "nummultirange" | "tsmultirange"
+        | "tstzmultirange" | "datemultirange" | "pg_lsn" | "pg_snapshot" | "txid_snapshot" => {
+            ColumnType::new(DataType::Utf8).with_select(TEXT_SELECT)
+        }
+        "ARRAY" => to_arrow_list(udt_name.trim_start_matches('_')),
+        "USER-DEFINED" => match udt_name {
+            // PostGIS
+            "geometry" | "geography" => ColumnType::new(DataType::Binary)
+                .with_select("ST_AsBinary({})")
+                .with_extension(WKB_EXTENSION),
+            "hstore" => ColumnType::new(DataType::Utf8).with_select(TEXT_SELECT),
+            // enum types has the text representation
+            _ => ColumnType::new(DataType::Utf8).with_select(TEXT_SELECT),
+        },
+        _ => return None,
+    })
+}
+
+/// Arrays of numeric, boolean and string are decoded natively, others are decoded as array of text.
+#[cfg(feature = "postgres")]
+fn to_arrow_list(element_udt_name: &str) -> ColumnType {
+    let (element_dtype, select) = match element_udt_name {
+        "bool" => (DataType::Boolean, None),
+        "int2" => (DataType::Int16, None),
+        "int4" => (DataType::Int32, None),
+        "int8" => (DataType::Int64, None),
+        "float4" => (DataType::Float32, None),
+        "float8" => (DataType::Float64, None),
+        "text" | "varchar" | "bpchar" | "name" => (DataType::Utf8, None),
+        _ => (DataType::Utf8, Some("{}::text[]")),
+    };
+
+    let column_type = ColumnType::new(DataType::List(
+        Field::new_list_field(element_dtype, true).into(),
+    ));
+
+    match select {
+        Some(select) => column_type.with_select(select),
+        None => column_type,
+    }
+}
+
+/// Maps the type name of result column (e.g. `INT4`, `TEXT[]`) of native query,
+/// returns `None` if the type is not decodable as is.
+#[cfg(feature = "postgres")]
+pub fn from_type_name(type_name: &str) -> Option<ColumnType> {
+    let (dtype, udt_name) = match type_name.strip_suffix("[]") {
+        Some(element) => (
+            "ARRAY",
+            match element {
+                "BOOL" => "_bool",
+                "INT2" => "_int2",
+                "INT4" => "_int4",
+                "INT8" => "_int8",
+                "FLOAT4" => "_float4",
+                "FLOAT8" => "_float8",
+                "TEXT" => "_text",
+                "VARCHAR" => "_varchar",
+                "CHAR" => "_bpchar",
+                "NAME" => "_name",
+                _ => return None,
+            },
+        ),
+        None => (
+            match type_name {
+                "BOOL" => "boolean",
+                "INT2" => "smallint",
+                "INT4" => "integer",
+                "INT8" => "bigint",
+                "FLOAT4" => "real",
+                "FLOAT8" => "double precision",
+                "NUMERIC" => "numeric",
+                "TEXT" | "UNKNOWN" => "text",
+                "VARCHAR" => "character varying",
+                "CHAR" => "character",
+                "NAME" => "name",
+                "UUID" => "uuid",
+                "BYTEA" => "bytea",
+                "TIMESTAMPTZ" => "timestamp with time zone",
+                "TIMESTAMP" => "timestamp without time zone",
+                "DATE" => "date",
+                "TIME" => "time without time zone",
+                "INTERVAL" => "interval",
+                _ => return None,
+            },
+            "",
+        ),
+    };
+
+    to_arrow_dtype(dtype, udt_name, None, None, true)
+        .filter(|column_type| column_type.select.is_none())
+}
+
+/// Column type of `CREATE TABLE` for the Arrow data type.
+#[cfg(feature = "postgres")]
+pub fn from_arrow_dtype(dtype: &DataType) -> Option<String> {
+    Some(match dtype {
+        DataType::Boolean => "boolean".to_string(),
+        DataType::Int8 | DataType::Int16 | DataType::UInt8 => "smallint".to_string(),
+        DataType::Int32 | DataType::UInt16 => "integer".to_string(),
+        DataType::Int64 | DataType::UInt32 => "bigint".to_string(),
+        DataType::UInt64 => "numeric(20, 0)".to_string(),
+        DataType::Float16 | DataType::Float32 => "real".to_string(),
+        DataType::Float64 => "double precision".to_string(),
+        DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+            format!("numeric({precision}, {scale})")
         }
-        "date" => DataType::Date32,
-        "time with time zone" => DataType::Time64(TimeUnit::Microsecond),
-        "time" | "time without time zone" => DataType::Time64(TimeUnit::Microsecond),
-        // "interval" => DataType::Utf8,
-        "boolean" => DataType::Boolean,
-        "uuid" => DataType::Utf8,
-        _ => unimplemented!("Unsupported data type: {}", postgres_dtype),
+        DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View => "text".to_string(),
+        DataType::Binary | DataType::LargeBinary | DataType::BinaryView => "bytea".to_string(),
+        DataType::Date32 | DataType::Date64 => "date".to_string(),
+        DataType::Time32(_) | DataType::Time64(_) => "time".to_string(),
+        DataType::Timestamp(_, Some(_)) => "timestamp with time zone".to_string(),
+        DataType::Timestamp(_, None) => "timestamp".to_string(),
+        _ => return None,
+    })
+}
+
+#[cfg(all(test, feature = "postgres"))]
+mod tests {
+    use datafusion::arrow::datatypes::{DataType, TimeUnit};
+
+    use crate::data_source::database::dtype_postgres::from_arrow_dtype;
+
+    #[test]
+    fn create_column_types() {
+        assert_eq!(from_arrow_dtype(&DataType::Int32).unwrap(), "integer");
+        assert_eq!(from_arrow_dtype(&DataType::UInt32).unwrap(), "bigint");
+        assert_eq!(
+            from_arrow_dtype(&DataType::UInt64).unwrap(),
+            "numeric(20, 0)"
+        );
+        assert_eq!(
+            from_arrow_dtype(&DataType::Decimal128(10, 2)).unwrap(),
+            "numeric(10, 2)"
+        );
+        assert_eq!(from_arrow_dtype(&DataType::Utf8View).unwrap(), "text");
+        assert_eq!(from_arrow_dtype(&DataType::LargeBinary).unwrap(), "bytea");
+    }
+
+    #[test]
+    fn create_timestamp_column_types() {
+        assert_eq!(
+            from_arrow_dtype(&DataType::Timestamp(TimeUnit::Microsecond, None)).unwrap(),
+            "timestamp"
+        );
+        assert_eq!(
+            from_arrow_dtype(&DataType::Timestamp(
+                TimeUnit::Millisecond,
+                Some("UTC".into())
+            ))
+            .unwrap(),
+            "timestamp with time zone"
+        );
+    }
+
+    #[test]
+    fn no_supported_column_type() {
+        assert!(from_arrow_dtype(&DataType::Null).is_none());
     }
 }
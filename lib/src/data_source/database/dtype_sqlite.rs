@@ -0,0 +1,247 @@
+// database/dtype_sqlite.rs: SQLite data type mapping
+
+#[cfg(feature = "sqlite")]
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
+#[cfg(feature = "sqlite")]
+use sqlx::{error::BoxDynError, sqlite::SqliteValueRef, Decode, Sqlite, TypeInfo, ValueRef};
+
+/// Maps declared column type by the type affinity rules of the database,
+/// date and time types are recognized by name as they are stored as TEXT.
+#[cfg(feature = "sqlite")]
+pub fn to_arrow_dtype(
+    sqlite_dtype: &str,
+    _precision: Option<i16>,
+    _scale: Option<i8>,
+    _signed: bool,
+) -> DataType {
+    let dtype = sqlite_dtype.to_uppercase();
+    let name = dtype.split('(').next().unwrap_or_default().trim();
+
+    match name {
+        "BOOLEAN" | "BOOL" => DataType::Boolean,
+        "DATE" => DataType::Date32,
+        "DATETIME" | "TIMESTAMP" => DataType::Timestamp(TimeUnit::Microsecond, None),
+        "TIME" => DataType::Time64(TimeUnit::Microsecond),
+        _ if name.contains("INT") => DataType::Int64,
+        _ if name.contains("CHAR") || name.contains("CLOB") || name.contains("TEXT") => {
+            DataType::Utf8
+        }
+        _ if name.is_empty() || name.contains("BLOB") => DataType::Binary,
+        // REAL and NUMERIC affinity
+        _ => DataType::Float64,
+    }
+}
+
+/// Value read by the storage class, a column may store the values of any class regardless of the declared type.
+#[cfg(feature = "sqlite")]
+pub enum StorageValue {
+    Integer(i64),
+    Real(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+#[cfg(feature = "sqlite")]
+impl StorageValue {
+    pub fn decode(value: SqliteValueRef<'_>) -> Result<Self, BoxDynError> {
+        Ok(match value.type_info().name() {
+            "INTEGER" => Self::Integer(<i64 as Decode<Sqlite>>::decode(value)?),
+            "REAL" => Self::Real(<f64 as Decode<Sqlite>>::decode(value)?),
+            "TEXT" => Self::Text(<String as Decode<Sqlite>>::decode(value)?),
+            _ => Self::Blob(<Vec<u8> as Decode<Sqlite>>::decode(value)?),
+        })
+    }
+
+    fn class(&self) -> &'static str {
+        match self {
+            Self::Integer(_) => "INTEGER",
+            Self::Real(_) => "REAL",
+            Self::Text(_) => "TEXT",
+            Self::Blob(_) => "BLOB",
+        }
+    }
+
+    fn unconvertible<T>(&self) -> Result<T, String> {
+        Err(format!(
+            "{} value is not convertible to {}",
+            self.class(),
+            std::any::type_name::<T>()
+        ))
+    }
+}
+
+/// Conversion from the storage class not decodable as the type, e.g. TEXT stored in INTEGER affinity column.
+#[cfg(feature = "sqlite")]
+pub trait FromStorageValue: Sized {
+    fn from_storage_value(value: StorageValue) -> Result<Self, String>;
+}
+
+#[cfg(feature = "sqlite")]
+macro_rules! from_storage_integer {
+    ($($type:ty),*) => {$(
+        impl FromStorageValue for $type {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+            fn from_storage_value(value: StorageValue) -> Result<Self, String> {
+                match &value {
+                    StorageValue::Integer(v) => <$type>::try_from(*v).map_err(|e| e.to_string()),
+                    // upper bound i64::MAX as f64 is 2^63, exclusive
+                    StorageValue::Real(v)
+                        if v.fract() == 0.0 && (i64::MIN as f64..i64::MAX as f64).contains(v) =>
+                    {
+                        <$type>::try_from(*v as i64).map_err(|e| e.to_string())
+                    }
+                    StorageValue::Text(v) => v.trim().parse::<$type>().map_err(|e| e.to_string()),
+                    _ => value.unconvertible(),
+                }
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "sqlite")]
+from_storage_integer!(i8, i16, i32, i64);
+
+#[cfg(feature = "sqlite")]
+macro_rules! from_storage_float {
+    ($($type:ty),*) => {$(
+        impl FromStorageValue for $type {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+            fn from_storage_value(value: StorageValue) -> Result<Self, String> {
+                match &value {
+                    StorageValue::Integer(v) => Ok(*v as $type),
+                    StorageValue::Real(v) => Ok(*v as $type),
+                    StorageValue::Text(v) => v.trim().parse::<$type>().map_err(|e| e.to_string()),
+                    StorageValue::Blob(_) => value.unconvertible(),
+                }
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "sqlite")]
+from_storage_float!(f32, f64);
+
+#[cfg(feature = "sqlite")]
+impl FromStorageValue for bool {
+    fn from_storage_value(value: StorageValue) -> Result<Self, String> {
+        match &value {
+            StorageValue::Integer(v) => Ok(*v != 0),
+            StorageValue::Real(v) => Ok(*v != 0.0),
+            StorageValue::Text(v) => match v.trim().to_lowercase().as_str() {
+                "true" | "1" => Ok(true),
+                "false" | "0" => Ok(false),
+                _ => value.unconvertible(),
+            },
+            StorageValue::Blob(_) => value.unconvertible(),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl FromStorageValue for String {
+    fn from_storage_value(value: StorageValue) -> Result<Self, String> {
+        match value {
+            StorageValue::Integer(v) => Ok(v.to_string()),
+            StorageValue::Real(v) => Ok(v.to_string()),
+            StorageValue::Text(v) => Ok(v),
+            StorageValue::Blob(v) => String::from_utf8(v).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl FromStorageValue for Vec<u8> {
+    fn from_storage_value(value: StorageValue) -> Result<Self, String> {
+        // same as CAST(value AS BLOB)
+        match value {
+            StorageValue::Integer(v) => Ok(v.to_string().into_bytes()),
+            StorageValue::Real(v) => Ok(v.to_string().into_bytes()),
+            StorageValue::Text(v) => Ok(v.into_bytes()),
+            StorageValue::Blob(v) => Ok(v),
+        }
+    }
+}
+
+#[cfg(all(feature = "postgres", feature = "sqlite"))]
+impl FromStorageValue for sqlx::types::Uuid {
+    fn from_storage_value(value: StorageValue) -> Result<Self, String> {
+        match &value {
+            StorageValue::Text(v) => Self::parse_str(v.trim()).map_err(|e| e.to_string()),
+            StorageValue::Blob(v) => Self::from_slice(v).map_err(|e| e.to_string()),
+            _ => value.unconvertible(),
+        }
+    }
+}
+
+// dates and times are decodable from TEXT, and from INTEGER or REAL as timestamps
+#[cfg(feature = "sqlite")]
+macro_rules! from_storage_unconvertible {
+    ($($type:ty),*) => {$(
+        impl FromStorageValue for $type {
+            fn from_storage_value(value: StorageValue) -> Result<Self, String> {
+                value.unconvertible()
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "sqlite")]
+from_storage_unconvertible!(
+    chrono::NaiveDate,
+    chrono::NaiveTime,
+    chrono::NaiveDateTime,
+    chrono::DateTime<chrono::Utc>
+);
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_by_storage_class() {
+        assert_eq!(
+            i32::from_storage_value(StorageValue::Text(" 42 ".to_string())),
+            Ok(42)
+        );
+        assert_eq!(i64::from_storage_value(StorageValue::Real(3.0)), Ok(3));
+        assert!(i64::from_storage_value(StorageValue::Real(3.5)).is_err());
+        assert!(i8::from_storage_value(StorageValue::Integer(300)).is_err());
+        assert!(i64::from_storage_value(StorageValue::Text("abc".to_string())).is_err());
+        assert_eq!(f64::from_storage_value(StorageValue::Integer(2)), Ok(2.0));
+        assert_eq!(
+            bool::from_storage_value(StorageValue::Text("TRUE".to_string())),
+            Ok(true)
+        );
+        assert_eq!(
+            String::from_storage_value(StorageValue::Integer(7)),
+            Ok("7".to_string())
+        );
+        assert_eq!(
+            Vec::<u8>::from_storage_value(StorageValue::Text("ab".to_string())),
+            Ok(b"ab".to_vec())
+        );
+        assert!(chrono::NaiveDate::from_storage_value(StorageValue::Blob(vec![0])).is_err());
+    }
+
+    #[tokio::test]
+    async fn decode_any_storage_class() {
+        use crate::data_source::database::any_pool::AnyDatabaseRow;
+
+        let pool = sqlx::SqlitePool::connect("sqlite::memory:").await.unwrap();
+        let rows = sqlx::query(
+            "SELECT CAST('12' AS TEXT) AS i, 1.5 AS r, 3 AS n, 'x' AS s, NULL AS z, 'abc' AS bad",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        let row = AnyDatabaseRow::Sqlite(rows.into_iter().next().unwrap());
+
+        assert_eq!(row.get::<i64>("i").unwrap(), Some(12));
+        assert_eq!(row.get::<f64>("r").unwrap(), Some(1.5));
+        assert_eq!(row.get::<f64>("n").unwrap(), Some(3.0));
+        assert_eq!(row.get::<String>("n").unwrap(), Some("3".to_string()));
+        assert_eq!(row.get::<String>("s").unwrap(), Some("x".to_string()));
+        assert_eq!(row.get::<i64>("z").unwrap(), None);
+        assert!(row.get::<i64>("bad").is_err());
+    }
+}
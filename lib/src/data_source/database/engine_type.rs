@@ -2,6 +2,25 @@
 // Sasaki, Naoki <nsasaki@sal.co.jp> July 27, 2024
 //
 
+#[cfg(feature = "mysql")]
+use datafusion::sql::sqlparser::dialect::MySqlDialect as MySqlParserDialect;
+#[cfg(feature = "postgres")]
+use datafusion::sql::sqlparser::dialect::PostgreSqlDialect as PostgreSqlParserDialect;
+#[cfg(feature = "sqlite")]
+use datafusion::sql::sqlparser::dialect::SQLiteDialect;
+#[cfg(feature = "mysql")]
+use datafusion::sql::unparser::dialect::MySqlDialect;
+#[cfg(feature = "postgres")]
+use datafusion::sql::unparser::dialect::PostgreSqlDialect;
+#[cfg(feature = "sqlite")]
+use datafusion::sql::unparser::dialect::SqliteDialect;
+use datafusion::sql::{sqlparser::dialect::Dialect as ParserDialect, unparser::dialect::Dialect};
+
+#[cfg(feature = "mysql")]
+use crate::data_source::database::dtype_mysql;
+#[cfg(feature = "postgres")]
+use crate::data_source::database::dtype_postgres;
+
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone)]
 pub enum DatabaseEngineType {
@@ -9,6 +28,8 @@ pub enum DatabaseEngineType {
     Postgres,
     #[cfg(feature = "mysql")]
     MySQL,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
 }
 
 impl DatabaseEngineType {
@@ -18,9 +39,47 @@ impl DatabaseEngineType {
             "postgres" => Ok(Self::Postgres),
             #[cfg(feature = "mysql")]
             "mysql" => Ok(Self::MySQL),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(Self::Sqlite),
             _ => Err(sqlx::error::Error::Protocol(format!(
                 "Unsupported external database engine: {scheme}"
             ))),
         }
     }
+
+    /// SQL dialect to unparse the remote query.
+    pub fn unparser_dialect(&self) -> Box<dyn Dialect> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres => Box::new(PostgreSqlDialect {}),
+            #[cfg(feature = "mysql")]
+            Self::MySQL => Box::new(MySqlDialect {}),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => Box::new(SqliteDialect {}),
+        }
+    }
+
+    /// SQL dialect to tokenize the native query.
+    pub fn parser_dialect(&self) -> Box<dyn ParserDialect> {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres => Box::new(PostgreSqlParserDialect {}),
+            #[cfg(feature = "mysql")]
+            Self::MySQL => Box::new(MySqlParserDialect {}),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => Box::new(SQLiteDialect {}),
+        }
+    }
+
+    /// Remote expression to select the column as text.
+    pub fn text_select(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "postgres")]
+            Self::Postgres => dtype_postgres::TEXT_SELECT,
+            #[cfg(feature = "mysql")]
+            Self::MySQL => dtype_mysql::TEXT_SELECT,
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => "CAST({} AS TEXT)",
+        }
+    }
 }
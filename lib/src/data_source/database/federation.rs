@@ -0,0 +1,469 @@
+// database/federation.rs: Pushes down whole subplans of the same database to the remote
+
+use std::sync::Arc;
+
+use async_trait::async_trait; // TODO: Replace in the future when the Rust compiler's async trait supports object safety.
+use datafusion::{
+    arrow::datatypes::{Field, Schema, SchemaRef},
+    catalog::Session,
+    common::{
+        tree_node::{Transformed, TreeNode, TreeNodeRecursion},
+        Column, DFSchema, DFSchemaRef, TableReference,
+    },
+    datasource::{provider_as_source, source_as_provider, TableProvider, TableType},
+    error::DataFusionError,
+    execution::context::SessionContext,
+    logical_expr::{
+        cast, Expr, JoinType, LogicalPlan, LogicalPlanBuilder, Projection, SubqueryAlias,
+        TableProviderFilterPushDown, TableScan,
+    },
+    optimizer::{OptimizerConfig, OptimizerRule},
+    physical_plan::{
+        streaming::{PartitionStream, StreamingTableExec},
+        ExecutionPlan,
+    },
+    sql::unparser::Unparser,
+};
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::data_source::database::table_provider::DatabaseTable;
+use crate::data_source::database::{
+    driver::{DatabaseDriver, DriverScanStream, DriverTable},
+    table_provider::is_string,
+};
+
+const RULE_NAME: &str = "database_federation";
+
+/// Adds the federation rule to the session once, both for the registered tables and the catalogs.
+pub fn install(ctx: &SessionContext) {
+    if !ctx
+        .state()
+        .optimizers()
+        .iter()
+        .any(|rule| rule.name() == RULE_NAME)
+    {
+        ctx.add_optimizer_rule(Arc::new(FederationRule::default()));
+    }
+}
+
+/// Replaces the subplan (aggregates, sorts, limits and joins) consisting only of the tables
+/// in the same namespace with a single remote query.
+#[derive(Debug, Default)]
+pub struct FederationRule {}
+
+impl OptimizerRule for FederationRule {
+    fn name(&self) -> &str {
+        RULE_NAME
+    }
+
+    fn rewrite(
+        &self,
+        plan: LogicalPlan,
+        _config: &dyn OptimizerConfig,
+    ) -> Result<Transformed<LogicalPlan>, DataFusionError> {
+        plan.transform_down(|node| match federate(&node)? {
+            Some(federated) => Ok(Transformed::new(federated, true, TreeNodeRecursion::Jump)),
+            None => Ok(Transformed::no(node)),
+        })
+    }
+}
+
+struct RemoteSource {
+    driver: Arc<dyn DatabaseDriver>,
+    namespace: String,
+}
+
+/// Namespace, driver and remote table name of the database table.
+fn remote_table(
+    provider: &dyn TableProvider,
+    #[allow(unused_variables)] scan: &TableScan,
+) -> Option<(String, Arc<dyn DatabaseDriver>, String)> {
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    if let Some(table) = provider.as_any().downcast_ref::<DatabaseTable>() {
+        // converted columns are decoded only by the table scan
+        if table.has_converted_columns(scan.projection.as_ref(), &scan.filters) {
+            return None;
+        }
+
+        return Some((
+            table.namespace().to_string(),
+            Arc::new(table.pool().clone()),
+            table.table_name().to_string(),
+        ));
+    }
+
+    provider
+        .as_any()
+        .downcast_ref::<DriverTable>()
+        .map(|table| {
+            (
+                table.namespace().to_string(),
+                table.driver().clone(),
+                table.table_name().to_string(),
+            )
+        })
+}
+
+fn federate(plan: &LogicalPlan) -> Result<Option<LogicalPlan>, DataFusionError> {
+    let Some(source) = remote_source(plan)? else {
+        return Ok(None);
+    };
+
+    let federated_table = TableReference::bare(format!("federated@{}", source.namespace));
+
+    // remote columns are renamed to be unique, and casted to the local data types
+    let mut local_exprs = vec![];
+    let mut fields = vec![];
+
+    for (index, (qualifier, field)) in plan.schema().iter().enumerate() {
+        let name = format!("c{index}");
+
+        local_exprs.push(
+            Expr::Column(Column::new(Some(federated_table.clone()), &name))
+                .alias_qualified(qualifier.cloned(), field.name()),
+        );
+        fields.push(Field::new(
+            &name,
+            field.data_type().clone(),
+            field.is_nullable(),
+        ));
+    }
+
+    let Some(remote_plan) = project_remote(to_remote_plan(plan.clone())?)? else {
+        return Ok(None);
+    };
+
+    let dialect = source.driver.dialect();
+    let sql = match Unparser::new(dialect.as_ref()).plan_to_sql(&remote_plan) {
+        Ok(statement) => statement.to_string(),
+        Err(e) => {
+            log::debug!("Can not federate to '{}': {e}", source.namespace);
+            return Ok(None);
+        }
+    };
+
+    log::debug!("Federated to '{}': {sql}", source.namespace);
+
+    let table = DatabaseQueryTable {
+        driver: source.driver,
+        sql,
+        schema: Arc::new(Schema::new(fields)),
+    };
+
+    Ok(Some(
+        LogicalPlanBuilder::scan(federated_table, provider_as_source(Arc::new(table)), None)?
+            .project(local_exprs)?
+            .build()?,
+    ))
+}
+
+/// Database of the subplan when all nodes are evaluated exactly by the same database.
+fn remote_source(plan: &LogicalPlan) -> Result<Option<RemoteSource>, DataFusionError> {
+    let mut source: Option<RemoteSource> = None;
+    let mut federable = true;
+    let mut computes = false;
+    let mut exprs: Vec<(Expr, DFSchemaRef)> = vec![];
+    let mut keys: Vec<(Expr, DFSchemaRef, bool)> = vec![];
+
+    plan.apply(|node| {
+        let schema = match node {
+            LogicalPlan::TableScan(scan) => {
+                let provider = source_as_provider(&scan.source)?;
+                let Some((namespace, driver, _)) = remote_table(provider.as_ref(), scan) else {
+                    federable = false;
+                    return Ok(TreeNodeRecursion::Stop);
+                };
+
+                match &source {
+                    Some(source) if source.namespace != namespace => {
+                        federable = false;
+                        return Ok(TreeNodeRecursion::Stop);
+                    }
+                    Some(_) => {}
+                    None => {
+                        source = Some(RemoteSource { driver, namespace });
+                    }
+                }
+
+                // filters may refer to the columns not projected
+                Arc::new(DFSchema::try_from_qualified_schema(
+                    scan.table_name.clone(),
+                    &scan.source.schema(),
+                )?)
+            }
+            LogicalPlan::Join(join)
+                if matches!(
+                    join.join_type,
+                    JoinType::Inner | JoinType::Left | JoinType::Right | JoinType::Full
+                ) =>
+            {
+                computes = true;
+                for (left, right) in &join.on {
+                    keys.push((left.clone(), join.left.schema().clone(), false));
+                    keys.push((right.clone(), join.right.schema().clone(), false));
+                }
+                join.schema.clone()
+            }
+            LogicalPlan::Aggregate(aggregate) => {
+                computes = true;
+                for expr in &aggregate.group_expr {
+                    keys.push((expr.clone(), aggregate.input.schema().clone(), false));
+                }
+                aggregate.input.schema().clone()
+            }
+            LogicalPlan::Sort(sort) => {
+                computes = true;
+                for expr in &sort.expr {
+                    keys.push((expr.expr.clone(), sort.input.schema().clone(), true));
+                }
+                sort.input.schema().clone()
+            }
+            LogicalPlan::Limit(limit) => {
+                computes = true;
+                limit.input.schema().clone()
+            }
+            LogicalPlan::Projection(projection) => projection.input.schema().clone(),
+            LogicalPlan::Filter(filter) => filter.input.schema().clone(),
+            LogicalPlan::SubqueryAlias(alias) => alias.input.schema().clone(),
+            _ => {
+                federable = false;
+                return Ok(TreeNodeRecursion::Stop);
+            }
+        };
+
+        exprs.extend(
+            node.expressions()
+                .into_iter()
+                .map(|expr| (expr, schema.clone())),
+        );
+        Ok(TreeNodeRecursion::Continue)
+    })?;
+
+    // just scanning a table is pushed down by the table provider
+    let Some(source) = source.filter(|_| federable && computes) else {
+        return Ok(None);
+    };
+
+    // groups, join keys and orders of strings follow the collation of the remote database
+    if keys.iter().any(|(expr, schema, ordering)| {
+        source.driver.collates_strings(*ordering) && is_string(expr, schema)
+    }) {
+        return Ok(None);
+    }
+
+    if exprs.iter().all(|(expr, schema)| {
+        source.driver.pushdown(expr, schema) == TableProviderFilterPushDown::Exact
+    }) {
+        Ok(Some(source))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Renames the output columns to `c0`, `c1`, ... casted to the local data types. The columns of the
+/// tables are not visible outside of the derived table, so the renaming is merged into the projection
+/// under the sorts and limits. `None` if it can not be merged.
+fn project_remote(plan: LogicalPlan) -> Result<Option<LogicalPlan>, DataFusionError> {
+    let names = (0..plan.schema().fields().len())
+        .map(|index| format!("c{index}"))
+        .collect::<Vec<_>>();
+
+    rename_remote(plan, &names)
+}
+
+fn rename_remote(
+    plan: LogicalPlan,
+    names: &[String],
+) -> Result<Option<LogicalPlan>, DataFusionError> {
+    match plan {
+        LogicalPlan::Sort(mut sort) => {
+            let input_schema = sort.input.schema().clone();
+
+            for sort_expr in &mut sort.expr {
+                let Some(index) = column_index(&sort_expr.expr, &input_schema) else {
+                    return Ok(None);
+                };
+                sort_expr.expr = Expr::Column(Column::new_unqualified(&names[index]));
+            }
+
+            let Some(input) = rename_remote(Arc::unwrap_or_clone(sort.input), names)? else {
+                return Ok(None);
+            };
+            sort.input = Arc::new(input);
+            Ok(Some(LogicalPlan::Sort(sort)))
+        }
+        LogicalPlan::Limit(mut limit) => {
+            let Some(input) = rename_remote(Arc::unwrap_or_clone(limit.input), names)? else {
+                return Ok(None);
+            };
+            limit.input = Arc::new(input);
+            Ok(Some(LogicalPlan::Limit(limit)))
+        }
+        // columns selected above the sort, e.g. sorted by the column not selected, are renamed
+        // under the sort, and the other columns of the sort are kept by the internal names
+        LogicalPlan::Projection(projection)
+            if matches!(
+                projection.input.as_ref(),
+                LogicalPlan::Sort(_) | LogicalPlan::Limit(_)
+            ) =>
+        {
+            let input_schema = projection.input.schema().clone();
+            let mut input_names: Vec<Option<String>> = vec![None; input_schema.fields().len()];
+
+            for (expr, name) in projection.expr.iter().zip(names) {
+                match column_index(expr, &input_schema) {
+                    Some(index) if input_names[index].is_none() => {
+                        input_names[index] = Some(name.clone());
+                    }
+                    _ => return Ok(None),
+                }
+            }
+
+            let input_names = input_names
+                .into_iter()
+                .enumerate()
+                .map(|(index, name)| name.unwrap_or_else(|| format!("x{index}")))
+                .collect::<Vec<_>>();
+
+            rename_remote(Arc::unwrap_or_clone(projection.input), &input_names)
+        }
+        LogicalPlan::Projection(projection) => {
+            let exprs = projection
+                .expr
+                .into_iter()
+                .zip(projection.schema.fields())
+                .zip(names)
+                .map(|((expr, field), name)| {
+                    cast(expr.unalias(), field.data_type().clone()).alias(name)
+                })
+                .collect::<Vec<_>>();
+
+            Ok(Some(LogicalPlan::Projection(Projection::try_new(
+                exprs,
+                projection.input,
+            )?)))
+        }
+        plan => {
+            let exprs = plan
+                .schema()
+                .iter()
+                .zip(names)
+                .map(|((qualifier, field), name)| {
+                    cast(
+                        Expr::Column(Column::new(qualifier.cloned(), field.name())),
+                        field.data_type().clone(),
+                    )
+                    .alias(name)
+                })
+                .collect::<Vec<_>>();
+
+            Ok(Some(
+                LogicalPlanBuilder::from(plan).project(exprs)?.build()?,
+            ))
+        }
+    }
+}
+
+/// Index of the output column referred by the expression.
+fn column_index(expr: &Expr, schema: &DFSchema) -> Option<usize> {
+    match expr {
+        Expr::Column(column) => schema.maybe_index_of_column(column),
+        _ => None,
+    }
+}
+
+/// Scans from the remote table name, aliased by the alias of the query or the session table name
+/// (e.g. `table@namespace`) to keep the column references.
+fn to_remote_plan(plan: LogicalPlan) -> Result<LogicalPlan, DataFusionError> {
+    Ok(plan
+        .transform_down(|node| {
+            let (scan, alias) = match &node {
+                // alias of the query (e.g. `FROM table@namespace AS t`) is kept for the remote table
+                LogicalPlan::SubqueryAlias(alias) => match alias.input.as_ref() {
+                    LogicalPlan::TableScan(scan) => (scan, alias.alias.clone()),
+                    _ => return Ok(Transformed::no(node)),
+                },
+                LogicalPlan::TableScan(scan) => (scan, scan.table_name.clone()),
+                _ => return Ok(Transformed::no(node)),
+            };
+
+            Ok(match remote_table_scan(scan, alias)? {
+                Some(remote) => Transformed::new(remote, true, TreeNodeRecursion::Jump),
+                None => Transformed::no(node),
+            })
+        })?
+        .data)
+}
+
+fn remote_table_scan(
+    #[allow(unused_variables)] scan: &TableScan,
+    alias: TableReference,
+) -> Result<Option<LogicalPlan>, DataFusionError> {
+    let provider = source_as_provider(&scan.source)?;
+    let Some((_, _, table_name)) = remote_table(provider.as_ref(), scan) else {
+        return Ok(None);
+    };
+
+    let parts: Vec<&str> = table_name.split('.').collect();
+    let table_reference = match parts[..] {
+        [schema, table] => TableReference::partial(schema, table),
+        [catalog, schema, table] => TableReference::full(catalog, schema, table),
+        _ => TableReference::bare(table_name.as_str()),
+    };
+
+    let remote_scan = TableScan::try_new(
+        table_reference,
+        scan.source.clone(),
+        scan.projection.clone(),
+        scan.filters.clone(),
+        scan.fetch,
+    )?;
+
+    Ok(Some(LogicalPlan::SubqueryAlias(SubqueryAlias::try_new(
+        Arc::new(LogicalPlan::TableScan(remote_scan)),
+        alias,
+    )?)))
+}
+
+/// Result of the federated remote query.
+#[derive(Debug)]
+struct DatabaseQueryTable {
+    driver: Arc<dyn DatabaseDriver>,
+    sql: String,
+    schema: SchemaRef,
+}
+
+#[async_trait]
+impl TableProvider for DatabaseQueryTable {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let stream =
+            DriverScanStream::new(self.driver.clone(), self.sql.clone(), self.schema.clone());
+
+        Ok(Arc::new(StreamingTableExec::try_new(
+            self.schema.clone(),
+            vec![Arc::new(stream) as Arc<dyn PartitionStream>],
+            projection,
+            vec![],
+            false,
+            limit,
+        )?))
+    }
+}
@@ -0,0 +1,297 @@
+// database/impersonation.rs: Database connections on behalf of the request user
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use datafusion::{error::DataFusionError, execution::context::SessionContext};
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::data_source::database::any_pool::AnyDatabasePool;
+use crate::data_source::database::driver::DatabaseDriver;
+
+// pools of the users are closed when unused, and the least recently used is closed over the limit
+const POOL_EXPIRATION: Duration = Duration::from_secs(600);
+const MAX_POOLS: usize = 100;
+
+/// Authenticated user of the request, carried by the session config.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct DatabaseIdentity {
+    // set by the authenticating proxy in `server.identity_header`, for `role`
+    pub user: Option<String>,
+    // user and password of the basic authorization, for `credentials`
+    pub credentials: Option<(String, String)>,
+}
+
+// password never appears in the logs
+impl std::fmt::Debug for DatabaseIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseIdentity")
+            .field("user", &self.user)
+            .field(
+                "credentials",
+                &self.credentials.as_ref().map(|(user, _)| user),
+            )
+            .finish()
+    }
+}
+
+impl DatabaseIdentity {
+    pub fn from_context(ctx: &SessionContext) -> Option<Arc<Self>> {
+        ctx.state().config().get_extension::<Self>()
+    }
+}
+
+/// Database user of the connection pool and the cached schemas.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct DatabaseUser {
+    pub user: String,
+    password: Option<String>,
+}
+
+impl std::fmt::Debug for DatabaseUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DatabaseUser")
+            .field("user", &self.user)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImpersonationMode {
+    // connects by the service account, and executes `SET ROLE` as the user
+    Role,
+    // connects by the user and the password of the request
+    Credentials,
+}
+
+impl ImpersonationMode {
+    pub fn from_name(name: &str) -> Result<Self, sqlx::Error> {
+        match name.to_lowercase().as_str() {
+            "role" => Ok(Self::Role),
+            "credentials" => Ok(Self::Credentials),
+            _ => Err(sqlx::Error::Configuration(
+                format!("Unsupported impersonation mode: {name}").into(),
+            )),
+        }
+    }
+}
+
+// connection pool of the user and the last access
+type Pools = HashMap<DatabaseUser, (Arc<dyn DatabaseDriver>, Instant)>;
+
+/// Connection pools of each user, created by the first access of the user.
+#[derive(Debug)]
+pub struct Impersonation {
+    mode: ImpersonationMode,
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "mysql", feature = "sqlite")),
+        allow(dead_code)
+    )]
+    url: String,
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "mysql", feature = "sqlite")),
+        allow(dead_code)
+    )]
+    max_connections: u32,
+    pools: Mutex<Pools>,
+}
+
+impl Impersonation {
+    pub fn new(mode: ImpersonationMode, url: &str, max_connections: u32) -> Self {
+        Self {
+            mode,
+            url: url.to_string(),
+            max_connections,
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Database user by the mode, `role` accepts only the identity header and `credentials` only
+    /// the basic authorization. Requests without the user are rejected, never falls back to
+    /// the service account.
+    pub fn user(
+        &self,
+        identity: Option<&DatabaseIdentity>,
+    ) -> Result<DatabaseUser, DataFusionError> {
+        let identity = identity.cloned().unwrap_or_default();
+
+        match self.mode {
+            ImpersonationMode::Role => {
+                let Some(user) = identity.user else {
+                    return Err(DataFusionError::Plan(
+                        "Database of impersonation mode 'role' requires the user of the identity header"
+                            .to_string(),
+                    ));
+                };
+
+                Ok(DatabaseUser {
+                    user,
+                    password: None,
+                })
+            }
+            ImpersonationMode::Credentials => {
+                let Some((user, password)) = identity.credentials else {
+                    return Err(DataFusionError::Plan(
+                        "Database of impersonation mode 'credentials' requires the basic authorization"
+                            .to_string(),
+                    ));
+                };
+
+                // the basic authorization never overrides the user asserted by the proxy
+                if identity
+                    .user
+                    .as_ref()
+                    .is_some_and(|asserted| *asserted != user)
+                {
+                    return Err(DataFusionError::Plan(
+                        "User of the basic authorization does not match the identity header"
+                            .to_string(),
+                    ));
+                }
+
+                Ok(DatabaseUser {
+                    user,
+                    password: Some(password),
+                })
+            }
+        }
+    }
+
+    /// Connection pool of the user, created by the first access of the user.
+    pub fn driver(&self, user: &DatabaseUser) -> Result<Arc<dyn DatabaseDriver>, DataFusionError> {
+        let mut pools = self.pools.lock().unwrap();
+        let now = Instant::now();
+
+        pools.retain(|_, (_, used_at)| now.duration_since(*used_at) < POOL_EXPIRATION);
+
+        if let Some((pool, used_at)) = pools.get_mut(user) {
+            *used_at = now;
+            return Ok(pool.clone());
+        }
+
+        if pools.len() >= MAX_POOLS {
+            let least_recently_used = pools
+                .iter()
+                .min_by_key(|(_, (_, used_at))| *used_at)
+                .map(|(user, _)| user.clone());
+
+            if let Some(least_recently_used) = least_recently_used {
+                log::debug!(
+                    "Close connection pool of database user '{}'",
+                    least_recently_used.user
+                );
+                pools.remove(&least_recently_used);
+            }
+        }
+
+        log::debug!("Create connection pool of database user '{}'", user.user);
+
+        let pool = self.connect(user)?;
+        pools.insert(user.clone(), (pool.clone(), now));
+        Ok(pool)
+    }
+
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    fn connect(&self, user: &DatabaseUser) -> Result<Arc<dyn DatabaseDriver>, DataFusionError> {
+        let pool = match self.mode {
+            ImpersonationMode::Role => {
+                AnyDatabasePool::new_impersonated(&self.url, self.max_connections, Some(&user.user))
+            }
+            ImpersonationMode::Credentials => {
+                let mut url = url::Url::parse(&self.url)
+                    .map_err(|e| DataFusionError::Configuration(e.to_string()))?;
+
+                url.set_username(&user.user)
+                    .and_then(|()| url.set_password(user.password.as_deref()))
+                    .map_err(|()| {
+                        DataFusionError::Configuration("Invalid database URL".to_string())
+                    })?;
+
+                AnyDatabasePool::new_impersonated(url.as_str(), self.max_connections, None)
+            }
+        }
+        .map_err(|e| DataFusionError::Configuration(e.to_string()))?;
+
+        Ok(Arc::new(pool))
+    }
+
+    // only the built-in engines are impersonated
+    #[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+    #[allow(clippy::unused_self)]
+    fn connect(&self, _user: &DatabaseUser) -> Result<Arc<dyn DatabaseDriver>, DataFusionError> {
+        Err(DataFusionError::Configuration(
+            "Impersonation is not supported by the database driver".to_string(),
+        ))
+    }
+
+    /// Number of the open connections and the idle connections of every user.
+    pub fn connections(&self) -> (u32, usize) {
+        self.pools
+            .lock()
+            .unwrap()
+            .values()
+            .map(|(pool, _)| pool.connections())
+            .fold((0, 0), |(size, idle), (s, i)| (size + s, idle + i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data_source::database::impersonation::{
+        DatabaseIdentity, Impersonation, ImpersonationMode,
+    };
+
+    fn identity(user: Option<&str>, credentials: Option<(&str, &str)>) -> DatabaseIdentity {
+        DatabaseIdentity {
+            user: user.map(ToString::to_string),
+            credentials: credentials
+                .map(|(user, password)| (user.to_string(), password.to_string())),
+        }
+    }
+
+    #[test]
+    fn role_accepts_only_identity_header() {
+        let impersonation =
+            Impersonation::new(ImpersonationMode::Role, "postgres://localhost/db", 1);
+
+        let user = impersonation
+            .user(Some(&identity(Some("alice"), Some(("mallory", "secret")))))
+            .unwrap();
+        assert_eq!(user.user, "alice");
+        assert!(user.password.is_none());
+
+        assert!(impersonation
+            .user(Some(&identity(None, Some(("mallory", "secret")))))
+            .is_err());
+        assert!(impersonation.user(None).is_err());
+    }
+
+    #[test]
+    fn credentials_never_override_identity_header() {
+        let impersonation =
+            Impersonation::new(ImpersonationMode::Credentials, "postgres://localhost/db", 1);
+
+        let user = impersonation
+            .user(Some(&identity(None, Some(("alice", "secret")))))
+            .unwrap();
+        assert_eq!(user.user, "alice");
+        assert_eq!(user.password.as_deref(), Some("secret"));
+
+        assert!(impersonation
+            .user(Some(&identity(Some("alice"), Some(("alice", "secret")))))
+            .is_ok());
+        assert!(impersonation
+            .user(Some(&identity(Some("alice"), Some(("mallory", "secret")))))
+            .is_err());
+        assert!(impersonation
+            .user(Some(&identity(Some("alice"), None)))
+            .is_err());
+    }
+
+    #[test]
+    fn debug_hides_password() {
+        let identity = identity(None, Some(("alice", "secret")));
+        assert!(!format!("{identity:?}").contains("secret"));
+    }
+}
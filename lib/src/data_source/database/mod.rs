@@ -1,9 +1,24 @@
-mod any_pool;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(crate) mod any_pool;
+pub mod catalog;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+mod column_type;
 #[allow(clippy::module_name_repetitions)]
 pub mod database_manager;
+#[allow(clippy::module_name_repetitions)]
+pub mod driver;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 mod dtype_mysql;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 mod dtype_postgres;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+mod dtype_sqlite;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 mod engine_type;
+mod federation;
+pub mod impersonation;
+pub mod passthrough;
 mod table_provider;
 pub mod table_register;
 mod table_resolver;
+pub mod table_writer;
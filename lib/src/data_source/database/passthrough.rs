@@ -0,0 +1,489 @@
+// database/passthrough.rs: Native queries executed as is by the external database
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use datafusion::{
+    arrow::datatypes::{Field, Schema},
+    physical_plan::SendableRecordBatchStream,
+    scalar::ScalarValue,
+};
+use datafusion::{
+    arrow::record_batch::RecordBatch,
+    error::DataFusionError,
+    sql::sqlparser::{
+        ast::{Query, SetExpr, Statement, Visit, Visitor},
+        dialect::Dialect as ParserDialect,
+        parser::Parser,
+        tokenizer::{Location, Token, TokenWithSpan, Tokenizer},
+    },
+    variable::VarProvider,
+};
+use futures::TryStreamExt;
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::data_source::database::{
+    any_pool::{AnyDatabasePool, QueryParam},
+    table_provider::DatabaseScanStream,
+};
+use crate::data_source::database::{driver::Placeholder, impersonation::DatabaseIdentity};
+use crate::response::http_error::ResponseError;
+use crate::settings::Settings;
+
+/// Executes the native query on the database of `namespace`, the result schema is inferred from the result columns.
+/// Only a read-only query is executed by the configured account, any statement by the user of impersonation mode.
+pub async fn execute(
+    namespace: &str,
+    sql: &str,
+    variables: Option<Arc<dyn VarProvider + Send + Sync>>,
+    identity: Option<&DatabaseIdentity>,
+) -> Result<Vec<RecordBatch>, ResponseError> {
+    let Some(resolver) = Settings::global()
+        .database_pool_manager
+        .resolvers
+        .get(namespace)
+    else {
+        return Err(ResponseError::database_not_found(namespace));
+    };
+
+    if !resolver.allow_native_query() {
+        return Err(ResponseError::native_query_forbidden(namespace));
+    }
+
+    let driver = resolver.driver_as(identity)?;
+
+    let (sql, names) =
+        bind_parameters(driver.parser_dialect().as_ref(), driver.placeholder(), sql)?;
+
+    // the configured account is shared by all users of the server, the query is executed
+    // in a read-only transaction, and the modifying statements are rejected early
+    let read_only = !resolver.impersonates();
+    if read_only {
+        ensure_read_only(driver.parser_dialect().as_ref(), &sql)?;
+    }
+
+    let params = names
+        .iter()
+        .map(|name| match &variables {
+            Some(variables) => Ok(variables.get_value(vec![name.clone()])?),
+            None => Err(ResponseError::request_validation(format!(
+                "Unknown session variable: {name}"
+            ))),
+        })
+        .collect::<Result<Vec<_>, ResponseError>>()?;
+
+    log::debug!("Native query of '{namespace}': {sql}, parameters: {params:?}");
+
+    let stream = driver
+        .query(&sql, params, read_only)
+        .await
+        .map_err(|e| match e {
+            DataFusionError::NotImplemented(_) => ResponseError::unsupported_type(format!(
+                "Native query is not supported by the database driver of '{namespace}'"
+            )),
+            e => ResponseError::from(e),
+        })?;
+
+    let schema = stream.schema();
+    let mut record_batches: Vec<RecordBatch> = stream.try_collect().await?;
+
+    log::debug!(
+        "Result records of native query: {}",
+        record_batches
+            .iter()
+            .map(RecordBatch::num_rows)
+            .sum::<usize>()
+    );
+
+    // keeps the schema of empty result
+    if record_batches.is_empty() {
+        record_batches.push(RecordBatch::new_empty(schema));
+    }
+
+    Ok(record_batches)
+}
+
+/// Native query of the built-in engines, the result columns are typed by the prepared statement
+/// and the rows are streamed into record batches.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(super) async fn native_query(
+    pool: &AnyDatabasePool,
+    sql: &str,
+    params: Vec<ScalarValue>,
+    read_only: bool,
+) -> Result<SendableRecordBatchStream, DataFusionError> {
+    let params: Vec<QueryParam> = params.into_iter().map(to_query_param).collect();
+
+    // stores original dtype name
+    let mut meta_info: HashMap<String, String> = HashMap::new();
+    let mut fields: Vec<Field> = vec![];
+
+    let columns = pool
+        .prepare_columns(sql)
+        .await
+        .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+    for (column_name, type_name) in columns {
+        let Some(column_type) = pool.column_type(&type_name) else {
+            return Err(DataFusionError::External(Box::new(
+                ResponseError::unsupported_type(format!(
+                    "Unsupported data type '{type_name}' of column '{column_name}', cast it in the query"
+                )),
+            )));
+        };
+
+        fields.push(Field::new(&column_name, column_type.data_type, true));
+        meta_info.insert(column_name, type_name.to_lowercase());
+    }
+
+    let schema = Arc::new(Schema::new_with_metadata(fields, meta_info.clone()));
+
+    Ok(
+        DatabaseScanStream::new(pool.clone(), sql.to_string(), schema, meta_info)
+            .with_params(params)
+            .with_read_only(read_only)
+            .stream(),
+    )
+}
+
+/// Single query without the data modifying statements in the common table expressions,
+/// `SELECT ... INTO` and the row locks.
+fn ensure_read_only(dialect: &dyn ParserDialect, sql: &str) -> Result<(), ResponseError> {
+    struct ReadOnly;
+
+    impl Visitor for ReadOnly {
+        type Break = ();
+
+        fn pre_visit_statement(&mut self, statement: &Statement) -> ControlFlow<()> {
+            match statement {
+                Statement::Query(_) => ControlFlow::Continue(()),
+                _ => ControlFlow::Break(()),
+            }
+        }
+
+        fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+            if query.locks.is_empty() && !selects_into(&query.body) {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        }
+    }
+
+    fn selects_into(body: &SetExpr) -> bool {
+        match body {
+            SetExpr::Select(select) => select.into.is_some(),
+            SetExpr::SetOperation { left, right, .. } => selects_into(left) || selects_into(right),
+            _ => false,
+        }
+    }
+
+    let statements = Parser::parse_sql(dialect, sql)
+        .map_err(|e| ResponseError::request_validation(e.to_string()))?;
+
+    match statements.as_slice() {
+        [statement] if statement.visit(&mut ReadOnly).is_continue() => Ok(()),
+        _ => Err(ResponseError::request_validation(
+            "Native query must be a single read-only query without impersonation",
+        )),
+    }
+}
+
+/// Replaces `:name` by the placeholder of the database engine. Literals, quoted identifiers, comments,
+/// casts (e.g. `::text`) and array slices (e.g. `a[1:n]`) are left as is by the tokenizer of the dialect.
+fn bind_parameters(
+    dialect: &dyn ParserDialect,
+    placeholder: Placeholder,
+    sql: &str,
+) -> Result<(String, Vec<String>), ResponseError> {
+    let tokens = Tokenizer::new(dialect, sql)
+        .with_unescape(false)
+        .tokenize_with_location()
+        .map_err(|e| ResponseError::request_validation(e.to_string()))?;
+
+    reject_role_change(&tokens)?;
+
+    let mut statement = String::with_capacity(sql.len());
+    let mut names: Vec<String> = vec![];
+    let mut copied = 0;
+    let mut brackets = 0usize;
+
+    for (index, token) in tokens.iter().enumerate() {
+        match &token.token {
+            Token::LBracket => brackets += 1,
+            Token::RBracket => brackets = brackets.saturating_sub(1),
+            Token::Colon if brackets == 0 => {
+                let Some(next) = tokens.get(index + 1) else {
+                    continue;
+                };
+
+                // the name follows the colon immediately
+                let Token::Word(word) = &next.token else {
+                    continue;
+                };
+                if word.quote_style.is_some() || next.span.start != token.span.end {
+                    continue;
+                }
+
+                statement.push_str(&sql[copied..byte_offset(sql, token.span.start)]);
+                copied = byte_offset(sql, next.span.end);
+
+                match placeholder {
+                    // numbered placeholders are reused by the same name
+                    Placeholder::Numbered => {
+                        let index = names
+                            .iter()
+                            .position(|name| *name == word.value)
+                            .unwrap_or_else(|| {
+                                names.push(word.value.clone());
+                                names.len() - 1
+                            });
+                        let _ = write!(statement, "${}", index + 1);
+                    }
+                    Placeholder::Positional => {
+                        names.push(word.value.clone());
+                        statement.push('?');
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    statement.push_str(&sql[copied..]);
+
+    Ok((statement, names))
+}
+
+/// Rejects the statements changing the role or the session user, which would run the following statements
+/// of the pooled connection as the configured account instead of the impersonated role.
+/// e.g. `SET ROLE`, `RESET ROLE`, `SET SESSION AUTHORIZATION`, `RESET ALL`, `DISCARD ALL` and `set_config()`
+fn reject_role_change(tokens: &[TokenWithSpan]) -> Result<(), ResponseError> {
+    let tokens: Vec<&Token> = tokens
+        .iter()
+        .map(|token| &token.token)
+        .filter(|token| !matches!(token, Token::Whitespace(_)))
+        .collect();
+    let word = |index: usize| match tokens.get(index) {
+        Some(Token::Word(word)) if word.quote_style.is_none() => Some(word.value.to_uppercase()),
+        _ => None,
+    };
+
+    for index in 0..tokens.len() {
+        let starts_statement = index == 0 || tokens[index - 1] == &Token::SemiColon;
+
+        let changes_role = match word(index).as_deref() {
+            Some("SET" | "RESET") if starts_statement => {
+                // `SET SESSION ROLE`, `SET LOCAL ROLE` and `SET DEFAULT ROLE`
+                let target = match word(index + 1).as_deref() {
+                    Some("SESSION" | "LOCAL" | "DEFAULT") => word(index + 2),
+                    _ => word(index + 1),
+                };
+                matches!(target.as_deref(), Some("ROLE" | "AUTHORIZATION" | "ALL"))
+            }
+            Some("DISCARD") => starts_statement,
+            Some("SET_CONFIG") => true,
+            _ => false,
+        };
+
+        if changes_role {
+            return Err(ResponseError::request_validation(
+                "Native query can not change the role or the session user",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Byte offset of the location by line and column of characters.
+fn byte_offset(sql: &str, location: Location) -> usize {
+    let mut offset = 0;
+
+    for (line, text) in sql.split_inclusive('\n').enumerate() {
+        if line as u64 + 1 == location.line {
+            return offset
+                + text
+                    .char_indices()
+                    .nth(usize::try_from(location.column).unwrap_or(usize::MAX) - 1)
+                    .map_or(text.len(), |(index, _)| index);
+        }
+        offset += text.len();
+    }
+
+    sql.len()
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+fn to_query_param(value: ScalarValue) -> QueryParam {
+    match value {
+        ScalarValue::Boolean(v) => QueryParam::Bool(v),
+        ScalarValue::Int8(v) => QueryParam::Int(v.map(i64::from)),
+        ScalarValue::Int16(v) => QueryParam::Int(v.map(i64::from)),
+        ScalarValue::Int32(v) => QueryParam::Int(v.map(i64::from)),
+        ScalarValue::Int64(v) => QueryParam::Int(v),
+        ScalarValue::UInt8(v) => QueryParam::Int(v.map(i64::from)),
+        ScalarValue::UInt16(v) => QueryParam::Int(v.map(i64::from)),
+        ScalarValue::UInt32(v) => QueryParam::Int(v.map(i64::from)),
+        ScalarValue::UInt64(Some(v)) if i64::try_from(v).is_ok() => {
+            QueryParam::Int(i64::try_from(v).ok())
+        }
+        ScalarValue::Float32(v) => QueryParam::Float(v.map(f64::from)),
+        ScalarValue::Float64(v) => QueryParam::Float(v),
+        ScalarValue::Utf8(v) | ScalarValue::LargeUtf8(v) | ScalarValue::Utf8View(v) => {
+            QueryParam::Text(v)
+        }
+        value if value.is_null() => QueryParam::Text(None),
+        value => QueryParam::Text(Some(value.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion::sql::sqlparser::dialect::{GenericDialect, MySqlDialect, PostgreSqlDialect};
+
+    use crate::data_source::database::{
+        driver::Placeholder,
+        passthrough::{bind_parameters, ensure_read_only},
+    };
+
+    fn postgres(sql: &str) -> (String, Vec<String>) {
+        bind_parameters(&PostgreSqlDialect {}, Placeholder::Numbered, sql).unwrap()
+    }
+
+    fn mysql(sql: &str) -> (String, Vec<String>) {
+        bind_parameters(&MySqlDialect {}, Placeholder::Positional, sql).unwrap()
+    }
+
+    #[test]
+    fn numbered_placeholders_reused_by_name() {
+        let (sql, names) = postgres("SELECT * FROM t WHERE a = :a AND b > :b OR a < :a");
+        assert_eq!(sql, "SELECT * FROM t WHERE a = $1 AND b > $2 OR a < $1");
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn positional_placeholders_in_order() {
+        let (sql, names) = mysql("SELECT * FROM t WHERE a = :a AND b > :b OR a < :a");
+        assert_eq!(sql, "SELECT * FROM t WHERE a = ? AND b > ? OR a < ?");
+        assert_eq!(names, vec!["a", "b", "a"]);
+    }
+
+    #[test]
+    fn casts_and_slices_left_as_is() {
+        let (sql, names) = postgres("SELECT a::text, b[1:n], c[:n] FROM t WHERE d = :d::int");
+        assert_eq!(
+            sql,
+            "SELECT a::text, b[1:n], c[:n] FROM t WHERE d = $1::int"
+        );
+        assert_eq!(names, vec!["d"]);
+    }
+
+    #[test]
+    fn comments_left_as_is() {
+        let (sql, names) = postgres("SELECT 1 -- :a\n/* :b\n */ WHERE x = :c");
+        assert_eq!(sql, "SELECT 1 -- :a\n/* :b\n */ WHERE x = $1");
+        assert_eq!(names, vec!["c"]);
+    }
+
+    #[test]
+    fn postgres_strings_left_as_is() {
+        let (sql, names) = postgres(
+            "SELECT $$ :a $$, $tag$ ':b' $tag$, E'\\' :c', 'it''s :d', \":e\" FROM t WHERE x = :f",
+        );
+        assert_eq!(
+            sql,
+            "SELECT $$ :a $$, $tag$ ':b' $tag$, E'\\' :c', 'it''s :d', \":e\" FROM t WHERE x = $1"
+        );
+        assert_eq!(names, vec!["f"]);
+    }
+
+    #[test]
+    fn mysql_backslash_escapes_left_as_is() {
+        let (sql, names) = mysql("SELECT 'a\\' :b', `:c` FROM t WHERE x = :d");
+        assert_eq!(sql, "SELECT 'a\\' :b', `:c` FROM t WHERE x = ?");
+        assert_eq!(names, vec!["d"]);
+    }
+
+    #[test]
+    fn multibyte_characters_before_parameter() {
+        let (sql, names) = bind_parameters(
+            &GenericDialect {},
+            Placeholder::Positional,
+            "SELECT 'é' AS\n\"名前\" WHERE x = :x",
+        )
+        .unwrap();
+        assert_eq!(sql, "SELECT 'é' AS\n\"名前\" WHERE x = ?");
+        assert_eq!(names, vec!["x"]);
+    }
+
+    #[test]
+    fn role_changes_are_error() {
+        for sql in [
+            "RESET ROLE",
+            "SELECT 1; set role admin",
+            "SET SESSION AUTHORIZATION DEFAULT",
+            "SET LOCAL ROLE NONE",
+            "SET role TO 'admin'",
+            "DISCARD ALL",
+            "SELECT set_config('role', 'admin', false)",
+        ] {
+            assert!(
+                bind_parameters(&PostgreSqlDialect {}, Placeholder::Numbered, sql).is_err(),
+                "{sql}"
+            );
+        }
+        assert!(bind_parameters(
+            &MySqlDialect {},
+            Placeholder::Positional,
+            "SET DEFAULT ROLE ALL TO u"
+        )
+        .is_err());
+
+        let (sql, _) = postgres("UPDATE users SET role = 'set role' WHERE \"set\" = 1");
+        assert_eq!(sql, "UPDATE users SET role = 'set role' WHERE \"set\" = 1");
+    }
+
+    #[test]
+    fn unterminated_literal_is_error() {
+        assert!(
+            bind_parameters(&GenericDialect {}, Placeholder::Positional, "SELECT ':a").is_err()
+        );
+    }
+
+    #[test]
+    fn only_read_only_query_without_impersonation() {
+        for sql in [
+            "SELECT id, ST_AsText(geom) FROM stores WHERE category = $1",
+            "WITH t AS (SELECT 1 AS a) SELECT a FROM t UNION SELECT 2",
+            "SELECT * FROM (SELECT a FROM t) AS s WHERE a IN (SELECT b FROM u)",
+        ] {
+            assert!(
+                ensure_read_only(&PostgreSqlDialect {}, sql).is_ok(),
+                "{sql}"
+            );
+        }
+
+        for sql in [
+            "DELETE FROM t",
+            "SELECT 1; DELETE FROM t",
+            "WITH d AS (DELETE FROM t RETURNING *) SELECT * FROM d",
+            "SELECT * INTO copied FROM t",
+            "SELECT * FROM t FOR UPDATE",
+            "CALL refresh_stores()",
+            "COPY t TO '/tmp/t.csv'",
+        ] {
+            assert!(
+                ensure_read_only(&PostgreSqlDialect {}, sql).is_err(),
+                "{sql}"
+            );
+        }
+
+        assert!(ensure_read_only(&MySqlDialect {}, "SELECT * FROM t WHERE a = ?").is_ok());
+        assert!(ensure_read_only(&MySqlDialect {}, "UPDATE t SET a = ?").is_err());
+    }
+}
@@ -3,24 +3,54 @@
 //
 
 use std::collections::HashMap;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use std::fmt::{Debug, Write};
-use std::future::Future;
-use std::pin::Pin;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use std::sync::Arc;
 
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use crate::data_source::database::any_pool::DatabaseOperator;
+#[cfg(feature = "postgres")]
+use crate::data_source::database::any_pool::SharedSnapshot;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::data_source::database::column_type::{ColumnType, EXTENSION_NAME_KEY};
 #[cfg(feature = "mysql")]
 use crate::data_source::database::dtype_mysql;
 #[cfg(feature = "postgres")]
 use crate::data_source::database::dtype_postgres;
+#[cfg(feature = "sqlite")]
+use crate::data_source::database::dtype_sqlite;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use crate::data_source::database::{
-    any_pool::{AnyDatabasePool, AnyDatabaseRow},
+    any_pool::{AnyDatabasePool, AnyDatabaseRow, QueryParam},
+    driver::DriverScanStream,
     engine_type::DatabaseEngineType,
+    table_resolver::SchemaMismatch,
 };
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use async_trait::async_trait; // TODO: Replace in the future when the Rust compiler's async trait supports object safety.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use chrono::{Datelike, Timelike};
 #[cfg(feature = "mysql")]
 use datafusion::arrow::array::{UInt16Builder, UInt32Builder, UInt64Builder, UInt8Builder};
+#[cfg(feature = "postgres")]
+use datafusion::arrow::{
+    array::{IntervalMonthDayNanoBuilder, ListBuilder},
+    datatypes::{IntervalMonthDayNano, IntervalUnit},
+};
+#[cfg(feature = "sqlite")]
+use datafusion::logical_expr::Like;
+use datafusion::{
+    arrow::datatypes::DataType,
+    common::{
+        tree_node::{Transformed, TreeNode},
+        Column, DFSchema,
+    },
+    error::DataFusionError,
+    logical_expr::{Expr, ExprSchemable},
+    sql::unparser::{dialect::Dialect, Unparser},
+};
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 use datafusion::{
     arrow::{
         array::{
@@ -29,28 +59,61 @@ use datafusion::{
             Int64Builder, Int8Builder, StringBuilder, Time64MicrosecondBuilder,
             TimestampMicrosecondBuilder,
         },
-        datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit},
-        record_batch::RecordBatch,
+        datatypes::{Decimal128Type, DecimalType, Field, Schema, SchemaRef, TimeUnit},
+        record_batch::{RecordBatch, RecordBatchOptions},
     },
     catalog::Session,
-    datasource::{memory::MemTable, TableProvider, TableType},
-    error::DataFusionError,
-    execution::context::SessionContext,
-    logical_expr::Expr,
-    physical_plan::ExecutionPlan,
+    common::tree_node::TreeNodeRecursion,
+    datasource::{TableProvider, TableType},
+    execution::TaskContext,
+    logical_expr::{BinaryExpr, Operator, TableProviderFilterPushDown},
+    physical_plan::{
+        stream::RecordBatchReceiverStream,
+        streaming::{PartitionStream, StreamingTableExec},
+        ExecutionPlan, SendableRecordBatchStream,
+    },
 };
-use futures::StreamExt;
-use num_traits::ToPrimitive;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use futures::{Stream, StreamExt, TryStreamExt};
+#[cfg(feature = "postgres")]
+use sqlx::postgres::types::PgInterval;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use tokio::sync::{mpsc::Sender, OnceCell};
 
-const BATCH_SIZE: usize = 1000;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(super) const BATCH_SIZE: usize = 1000;
 
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 #[derive(Debug)]
 pub struct DatabaseTable {
+    engine_type: DatabaseEngineType,
     pool: AnyDatabasePool,
+    namespace: String,
     schema: SchemaRef,
     table_name: String,
+    partitioning: Option<ScanPartitioning>,
+    // minimum and maximum keys of the partition column, retrieved once by the first partitioned scan
+    key_bounds: OnceCell<Option<(i64, i64)>>,
+    // remote expressions of the columns converted by the database
+    selects: HashMap<String, String>,
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct TableOptions {
+    // by the table name
+    pub partitioning: HashMap<String, ScanPartitioning>,
+    // maps the unsupported data types to text instead of an error
+    pub fallback_to_string: bool,
+}
+
+/// Scans the table in parallel by the key ranges of integer column.
+#[derive(Debug, Clone)]
+pub struct ScanPartitioning {
+    pub partitions: usize,
+    pub column: String,
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 #[async_trait]
 impl TableProvider for DatabaseTable {
     fn as_any(&self) -> &dyn std::any::Any {
@@ -65,131 +128,92 @@ impl TableProvider for DatabaseTable {
         TableType::Base
     }
 
-    fn scan<'life0, 'life1, 'life2, 'life3, 'async_trait>(
-        &'life0 self,
-        _state: &'life1 (dyn Session + 'life1),
-        projection: Option<&'life2 Vec<usize>>,
-        filters: &'life3 [Expr],
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
         limit: Option<usize>,
-    ) -> Pin<
-        Box<
-            dyn Future<Output = Result<Arc<dyn ExecutionPlan>, DataFusionError>>
-                + Send
-                + 'async_trait,
-        >,
-    >
-    where
-        Self: 'async_trait,
-        'life0: 'async_trait,
-        'life1: 'async_trait,
-        'life2: 'async_trait,
-        'life3: 'async_trait,
-    {
-        let table_name = self.table_name.clone();
-        let pool = self.pool.clone();
-        let schema = self.schema.clone();
-        let projection = projection.cloned();
-        let filters = filters.to_vec();
-
-        Box::pin(async move {
-            let mut sql = format!("SELECT * FROM {table_name}");
-
-            if !filters.is_empty() {
-                let filter_clauses: Vec<String> = filters.iter().map(Expr::to_string).collect();
-                if !filter_clauses.is_empty() {
-                    write!(&mut sql, " WHERE {}", filter_clauses.join(" AND "))?;
-                }
-            }
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let projected_schema = Arc::new(match projection {
+            Some(projection) => self.schema.project(projection)?,
+            None => self.schema.as_ref().clone(),
+        });
 
-            let projected_fields = if let Some(projection) = projection {
-                let columns: Vec<String> = projection
-                    .iter()
-                    .map(|index| schema.field(*index).name().clone())
-                    .collect();
-                sql = sql.replace('*', &columns.join(", "));
-                columns
-            } else {
-                schema.fields().iter().map(|f| f.name().clone()).collect()
-            };
-
-            let projected_schema = Arc::new(Schema::new(
-                projected_fields
-                    .iter()
-                    .map(|name| schema.field_with_name(name).unwrap().clone())
-                    .collect::<Vec<_>>(),
-            ));
-
-            if let Some(limit) = limit {
-                write!(&mut sql, " LIMIT {limit}")?;
-            }
-
-            // retrieve from external database system
-            let mut stream = pool.fetch(&sql);
-            let mut builders = Self::create_column_builders(&projected_schema)?;
-            let mut row_count = 0;
-            let mut record_batches = vec![];
-
-            while let Some(row) = stream.next().await {
-                let row = row.map_err(|e| DataFusionError::Execution(e.to_string()))?;
-                row_count += 1;
+        let columns: Vec<String> = projected_schema
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect();
+        let select_list = self.select_list(&columns);
 
-                for (index, name) in projected_fields.iter().enumerate() {
-                    Self::append_value_to_builder(
-                        &mut builders[index],
-                        schema.field_with_name(name)?,
-                        schema.metadata.get(name).unwrap_or(&String::new()),
-                        &row,
-                    )?;
-                }
+        let ranges = if limit.is_none() {
+            self.partition_ranges().await?
+        } else {
+            vec![None]
+        };
 
-                if row_count == BATCH_SIZE {
-                    let arrays: Vec<ArrayRef> = builders
-                        .into_iter()
-                        .map(|mut builder| builder.finish())
-                        .collect();
+        // the partitions read the same snapshot of the database
+        #[cfg(feature = "postgres")]
+        let snapshot = (ranges.len() > 1).then(|| Arc::new(SharedSnapshot::new(ranges.len())));
 
-                    record_batches.push(RecordBatch::try_new(projected_schema.clone(), arrays)?);
+        let partitions = ranges
+            .iter()
+            .map(|range| {
+                let sql = self.select_sql(&select_list, filters, range.as_deref(), limit)?;
 
-                    builders = Self::create_column_builders(&projected_schema)?;
-                    row_count = 0;
+                #[cfg(feature = "postgres")]
+                if let Some(snapshot) = &snapshot {
+                    return Ok(Arc::new(
+                        DatabaseScanStream::new(
+                            self.pool.clone(),
+                            sql,
+                            projected_schema.clone(),
+                            projected_schema.metadata.clone(),
+                        )
+                        .with_snapshot(snapshot.clone()),
+                    ) as Arc<dyn PartitionStream>);
                 }
-            }
-
-            if row_count > 0 {
-                let arrays: Vec<ArrayRef> = builders
-                    .into_iter()
-                    .map(|mut builder| builder.finish())
-                    .collect();
 
-                record_batches.push(RecordBatch::try_new(projected_schema.clone(), arrays)?);
-            }
+                Ok(Arc::new(DriverScanStream::new(
+                    Arc::new(self.pool.clone()),
+                    sql,
+                    projected_schema.clone(),
+                )) as Arc<dyn PartitionStream>)
+            })
+            .collect::<Result<Vec<_>, DataFusionError>>()?;
 
-            let memory_table = Arc::new(MemTable::try_new(
-                projected_schema.clone(),
-                vec![record_batches.clone()],
-            )?);
+        Ok(Arc::new(StreamingTableExec::try_new(
+            projected_schema,
+            partitions,
+            None,
+            vec![],
+            false,
+            None,
+        )?))
+    }
 
-            let ctx = SessionContext::new();
-            ctx.register_table("table", memory_table)?;
-            let dataframe = ctx.table("table").await?;
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>, DataFusionError> {
+        let dialect = self.engine_type.unparser_dialect();
 
-            dataframe.create_physical_plan().await
-        })
+        Ok(filters
+            .iter()
+            .map(|filter| self.filter_pushdown(dialect.as_ref(), filter))
+            .collect())
     }
 }
 
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 macro_rules! append_value {
     ($builder:expr, $field:expr, $row:expr, $type:ty, $builder_type:ty) => {{
         let builder = $builder.as_any_mut().downcast_mut::<$builder_type>();
         match builder {
-            Some(builder) => {
-                if let Some(value) = $row.get::<$type>($field.name()) {
-                    builder.append_value(value);
-                } else {
-                    builder.append_null();
-                }
-                Ok(())
-            }
+            Some(builder) => $row
+                .get::<$type>($field.name())
+                .map(|value| builder.append_option(value)),
             None => Err(DataFusionError::Internal(format!(
                 "Failed to downcast builder for field '{}'",
                 $field.name()
@@ -203,14 +227,9 @@ macro_rules! append_mysql_specific_value {
     ($builder:expr, $field:expr, $row:expr, $type:ty, $builder_type:ty) => {{
         let builder = $builder.as_any_mut().downcast_mut::<$builder_type>();
         match builder {
-            Some(builder) => {
-                if let Some(value) = $row.get_mysql::<$type>($field.name()) {
-                    builder.append_value(value);
-                } else {
-                    builder.append_null();
-                }
-                Ok(())
-            }
+            Some(builder) => $row
+                .get_mysql::<$type>($field.name())
+                .map(|value| builder.append_option(value)),
             None => Err(DataFusionError::Internal(format!(
                 "Failed to downcast builder for field '{}'",
                 $field.name()
@@ -219,60 +238,137 @@ macro_rules! append_mysql_specific_value {
     }};
 }
 
+/// List builder of the element builder, the item field keeps its name and nullability.
+#[cfg(feature = "postgres")]
+macro_rules! list_builder {
+    ($item:expr, $values_builder:expr) => {
+        Box::new(ListBuilder::with_capacity($values_builder, BATCH_SIZE).with_field($item.clone()))
+            as Box<dyn ArrayBuilder>
+    };
+}
+
+#[cfg(feature = "postgres")]
+macro_rules! append_list {
+    ($builder:expr, $field:expr, $row:expr, $type:ty, $values_builder_type:ty) => {{
+        let builder = $builder
+            .as_any_mut()
+            .downcast_mut::<ListBuilder<$values_builder_type>>();
+        match builder {
+            Some(builder) => $row
+                .get_postgres::<Vec<Option<$type>>>($field.name())
+                .map(|value| builder.append_option(value)),
+            None => Err(DataFusionError::Internal(format!(
+                "Failed to downcast builder for field '{}'",
+                $field.name()
+            ))),
+        }
+    }};
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
 impl DatabaseTable {
+    #[allow(clippy::too_many_lines)]
     pub async fn new(
         engine_type: &DatabaseEngineType,
         pool: AnyDatabasePool,
+        namespace: &str,
         database: &str,
         table_name: &str,
+        table_options: &TableOptions,
     ) -> Result<Self, DataFusionError> {
         log::debug!("Inspecting external database schema: database={database}, table={table_name}");
 
-        let sql = match engine_type {
+        // schema qualified table name, e.g. `public.table`
+        let (table_schema, table) = match table_name.rsplit_once('.') {
+            Some((table_schema, table)) => (Some(table_schema), table),
+            None => (None, table_name),
+        };
+
+        // names are bound as parameters
+        let (sql, params) = match engine_type {
             #[cfg(feature = "postgres")]
-            DatabaseEngineType::Postgres => format!(
-                "SELECT column_name, data_type, numeric_precision, numeric_scale \
-                FROM information_schema.columns \
-                WHERE table_name='{table_name}'",
+            DatabaseEngineType::Postgres => (
+                format!(
+                    "SELECT column_name, data_type, udt_name, \
+                    numeric_precision::int8 AS numeric_precision, numeric_scale::int8 AS numeric_scale \
+                    FROM information_schema.columns \
+                    WHERE table_name=$1{}",
+                    if table_schema.is_some() {
+                        " AND table_schema=$2"
+                    } else {
+                        ""
+                    },
+                ),
+                [Some(table), table_schema].into_iter().flatten().collect::<Vec<_>>(),
             ),
             #[cfg(feature = "mysql")]
-            DatabaseEngineType::MySQL => format!(
-                "SELECT column_name, data_type, column_type, numeric_precision, numeric_scale \
+            DatabaseEngineType::MySQL => (
+                "SELECT column_name AS column_name, data_type AS data_type, column_type AS column_type, \
+                CAST(numeric_precision AS SIGNED) AS numeric_precision, \
+                CAST(numeric_scale AS SIGNED) AS numeric_scale \
                 FROM information_schema.columns \
-                WHERE table_schema='{database}' AND table_name='{table_name}'",
+                WHERE table_schema=? AND table_name=?"
+                    .to_string(),
+                vec![table_schema.unwrap_or(database), table],
+            ),
+            #[cfg(feature = "sqlite")]
+            DatabaseEngineType::Sqlite => (
+                "SELECT name AS column_name, type AS data_type, \
+                NULL AS numeric_precision, NULL AS numeric_scale \
+                FROM pragma_table_info(?, ?)"
+                    .to_string(),
+                vec![table, table_schema.unwrap_or("main")],
             ),
         };
 
-        log::debug!("Retrieving schema: {sql}");
+        let params: Vec<QueryParam> = params
+            .into_iter()
+            .map(|param: &str| QueryParam::Text(Some(param.to_string())))
+            .collect();
+
+        log::debug!("Retrieving schema: {sql}, parameters: {params:?}");
 
-        let rows = pool
-            .fetch_all(&sql)
+        let rows: Vec<AnyDatabaseRow> = pool
+            .fetch_with_params(&sql, &params)
+            .try_collect()
             .await
             .map_err(|e| DataFusionError::Execution(e.to_string()))?;
 
         log::debug!("Result schema information records: {}", rows.len());
 
         #[allow(clippy::type_complexity)]
-        let columns: Vec<(String, String, Option<i16>, Option<i8>, bool)> = rows
+        let columns: Vec<(String, String, String, Option<i16>, Option<i8>, bool)> = rows
             .iter()
             .map(|row| {
-                (
-                    row.get::<String>("column_name").unwrap_or_default(),
-                    row.get::<String>("data_type").unwrap_or_default(),
-                    row.get::<i16>("numeric_precision"),
-                    row.get::<i8>("numeric_scale"),
+                Ok((
+                    row.get::<String>("column_name")?.unwrap_or_default(),
+                    row.get::<String>("data_type")?.unwrap_or_default(),
+                    match engine_type {
+                        #[cfg(feature = "postgres")]
+                        DatabaseEngineType::Postgres => {
+                            row.get::<String>("udt_name")?.unwrap_or_default()
+                        }
+                        #[allow(unreachable_patterns)]
+                        _ => String::new(),
+                    },
+                    row.get::<i64>("numeric_precision")?
+                        .and_then(|v| i16::try_from(v).ok()),
+                    row.get::<i64>("numeric_scale")?
+                        .and_then(|v| i8::try_from(v).ok()),
                     match engine_type {
                         #[cfg(feature = "postgres")]
                         DatabaseEngineType::Postgres => true,
                         #[cfg(feature = "mysql")]
                         DatabaseEngineType::MySQL => !row
-                            .get::<String>("column_type")
+                            .get::<String>("column_type")?
                             .unwrap_or_default()
                             .ends_with("unsigned"),
+                        #[cfg(feature = "sqlite")]
+                        DatabaseEngineType::Sqlite => true,
                     },
-                )
+                ))
             })
-            .collect();
+            .collect::<Result<_, DataFusionError>>()?;
 
         // stores original dtype name
         let meta_info: HashMap<String, String> = columns
@@ -281,35 +377,280 @@ impl DatabaseTable {
             .map(|(column_name, dtype, ..)| (column_name, dtype))
             .collect();
 
-        let fields: Vec<Field> = columns
-            .into_iter()
-            .map(|(column_name, dtype, precision, scale, signed)| {
-                let arrow_dtype = match engine_type {
-                    #[cfg(feature = "postgres")]
-                    DatabaseEngineType::Postgres => {
-                        dtype_postgres::to_arrow_dtype(&dtype, precision, scale, signed)
-                    }
-                    #[cfg(feature = "mysql")]
-                    DatabaseEngineType::MySQL => {
-                        dtype_mysql::to_arrow_dtype(&dtype, precision, scale, signed)
-                    }
-                };
-                Field::new(&column_name, arrow_dtype, true)
-            })
-            .collect();
+        let dialect = engine_type.unparser_dialect();
+        let mut fields: Vec<Field> = vec![];
+        let mut selects: HashMap<String, String> = HashMap::new();
+
+        #[allow(unused_variables)]
+        for (column_name, dtype, udt_name, precision, scale, signed) in columns {
+            let column_type = match engine_type {
+                #[cfg(feature = "postgres")]
+                DatabaseEngineType::Postgres => {
+                    dtype_postgres::to_arrow_dtype(&dtype, &udt_name, precision, scale, signed)
+                }
+                #[cfg(feature = "mysql")]
+                DatabaseEngineType::MySQL => {
+                    dtype_mysql::to_arrow_dtype(&dtype, precision, scale, signed)
+                }
+                #[cfg(feature = "sqlite")]
+                DatabaseEngineType::Sqlite => Some(ColumnType::new(dtype_sqlite::to_arrow_dtype(
+                    &dtype, precision, scale, signed,
+                ))),
+            };
+
+            let column_type = match column_type {
+                Some(column_type) => column_type,
+                None if table_options.fallback_to_string => {
+                    log::warn!(
+                        "Unsupported data type '{dtype}' of '{table_name}.{column_name}', mapped to string"
+                    );
+                    ColumnType::new(DataType::Utf8).with_select(engine_type.text_select())
+                }
+                None => {
+                    return Err(DataFusionError::NotImplemented(format!(
+                        "Unsupported data type '{dtype}' of '{table_name}.{column_name}'"
+                    )))
+                }
+            };
+
+            if let Some(select) =
+                column_type.select_expr(&quote_identifier(dialect.as_ref(), &column_name))
+            {
+                selects.insert(column_name.clone(), select);
+            }
+
+            let field = Field::new(&column_name, column_type.data_type, true);
+
+            fields.push(match column_type.extension {
+                Some(extension) => field.with_metadata(HashMap::from([(
+                    EXTENSION_NAME_KEY.to_string(),
+                    extension.to_string(),
+                )])),
+                None => field,
+            });
+        }
 
         let schema = Arc::new(Schema::new_with_metadata(fields, meta_info));
 
         log::debug!("Established schema: {schema:?}");
 
         Ok(DatabaseTable {
+            engine_type: engine_type.clone(),
             pool,
+            namespace: namespace.to_string(),
             schema,
             table_name: table_name.to_string(),
+            partitioning: table_options.partitioning.get(table_name).cloned(),
+            key_bounds: OnceCell::new(),
+            selects,
         })
     }
 
-    fn create_column_builders(
+    fn select_list(&self, columns: &[String]) -> String {
+        if columns.is_empty() {
+            return "1".to_string(); // just counts rows, e.g. `count(*)`
+        }
+
+        let dialect = self.engine_type.unparser_dialect();
+
+        columns
+            .iter()
+            .map(|column| {
+                let identifier = quote_identifier(dialect.as_ref(), column);
+                match self.selects.get(column) {
+                    Some(select) => format!("{select} AS {identifier}"),
+                    None => identifier,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Remote query of the table, filters are unparsed by the dialect of database engine.
+    fn select_sql(
+        &self,
+        select_list: &str,
+        filters: &[Expr],
+        range: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<String, DataFusionError> {
+        let dialect = self.engine_type.unparser_dialect();
+
+        let table_name = quote_table_name(dialect.as_ref(), &self.table_name);
+
+        let mut sql = format!("SELECT {select_list} FROM {table_name}");
+
+        let mut conditions = filters
+            .iter()
+            .map(|filter| Ok(format!("({})", filter_to_sql(dialect.as_ref(), filter)?)))
+            .collect::<Result<Vec<_>, DataFusionError>>()?;
+
+        if let Some(range) = range {
+            conditions.push(format!("({range})"));
+        }
+
+        if !conditions.is_empty() {
+            write!(&mut sql, " WHERE {}", conditions.join(" AND "))?;
+        }
+
+        if let Some(limit) = limit {
+            write!(&mut sql, " LIMIT {limit}")?;
+        }
+
+        Ok(sql)
+    }
+
+    /// Splits the scan into the key ranges of partition column, scanned in parallel by each connection.
+    /// The first and last ranges are open-ended, as the bounds of keys are retrieved once for the table.
+    /// One connection of the pool is left for the exporter of the shared snapshot, which is kept open
+    /// until the other partitions have imported it, a single scan if the pool has no spare connection.
+    async fn partition_ranges(&self) -> Result<Vec<Option<String>>, DataFusionError> {
+        let Some(partitioning) = &self.partitioning else {
+            return Ok(vec![None]);
+        };
+
+        let max_partitions =
+            usize::try_from(self.pool.max_connections().saturating_sub(1)).unwrap_or(usize::MAX);
+        let partitions = partitioning.partitions.min(max_partitions);
+        if partitions < 2 {
+            return Ok(vec![None]);
+        }
+
+        match self.schema.field_with_name(&partitioning.column) {
+            Ok(field) if field.data_type().is_integer() => {}
+            _ => return Ok(vec![None]),
+        }
+
+        let dialect = self.engine_type.unparser_dialect();
+        let column = quote_identifier(dialect.as_ref(), &partitioning.column);
+
+        let key_bounds = self
+            .key_bounds
+            .get_or_try_init(|| async {
+                let sql = self.select_sql(
+                    &format!(
+                        "CAST(MIN({column}) AS BIGINT) AS min_key, CAST(MAX({column}) AS BIGINT) AS max_key"
+                    ),
+                    &[],
+                    None,
+                    None,
+                )?;
+
+                let row = self
+                    .pool
+                    .fetch_one(&sql)
+                    .await
+                    .map_err(|e| DataFusionError::Execution(e.to_string()))?;
+
+                Ok::<_, DataFusionError>(
+                    row.get::<i64>("min_key")?
+                        .zip(row.get::<i64>("max_key")?),
+                )
+            })
+            .await?;
+
+        let Some((min, max)) = *key_bounds else {
+            return Ok(vec![None]); // no rows
+        };
+
+        let partitions = i128::try_from(partitions).unwrap_or(1);
+        let step = ((i128::from(max) - i128::from(min)) / partitions + 1).max(1);
+
+        let bounds: Vec<i128> = (1..partitions)
+            .map(|index| i128::from(min) + step * index)
+            .take_while(|bound| *bound <= i128::from(max))
+            .collect();
+
+        let (Some(first), Some(last)) = (bounds.first(), bounds.last()) else {
+            return Ok(vec![None]);
+        };
+
+        let mut ranges = vec![Some(format!("{column} IS NULL OR {column} < {first}"))];
+        ranges.extend(bounds.windows(2).map(|range| {
+            Some(format!(
+                "{column} >= {} AND {column} < {}",
+                range[0], range[1]
+            ))
+        }));
+        ranges.push(Some(format!("{column} >= {last}")));
+
+        log::debug!(
+            "Partitioned scan of '{}' by '{}': {} partitions",
+            self.table_name,
+            partitioning.column,
+            ranges.len()
+        );
+
+        Ok(ranges)
+    }
+
+    fn filter_pushdown(&self, dialect: &dyn Dialect, filter: &Expr) -> TableProviderFilterPushDown {
+        if filter_to_sql(dialect, filter).is_err() {
+            return TableProviderFilterPushDown::Unsupported;
+        }
+
+        // the remote column has the original data type
+        if filter
+            .column_refs()
+            .iter()
+            .any(|column| self.selects.contains_key(&column.name))
+        {
+            return TableProviderFilterPushDown::Unsupported;
+        }
+
+        let Ok(schema) = DFSchema::try_from(self.schema.as_ref().clone()) else {
+            return TableProviderFilterPushDown::Unsupported;
+        };
+
+        pushdown_support(&self.engine_type, &unqualify(filter), &schema)
+    }
+
+    pub(super) fn pool(&self) -> &AnyDatabasePool {
+        &self.pool
+    }
+
+    pub(super) fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub(super) fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Scan references the columns converted by the remote expression, the raw columns are not decodable.
+    pub(super) fn has_converted_columns(
+        &self,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+    ) -> bool {
+        let projected = match projection {
+            Some(projection) => projection
+                .iter()
+                .map(|index| self.schema.field(*index).name())
+                .any(|name| self.selects.contains_key(name)),
+            None => !self.selects.is_empty(),
+        };
+
+        projected
+            || filters
+                .iter()
+                .flat_map(Expr::column_refs)
+                .any(|column| self.selects.contains_key(&column.name))
+    }
+
+    pub(super) fn to_record_batch(
+        schema: &SchemaRef,
+        arrays: Vec<ArrayRef>,
+        row_count: usize,
+    ) -> Result<RecordBatch, DataFusionError> {
+        Ok(RecordBatch::try_new_with_options(
+            schema.clone(),
+            arrays,
+            &RecordBatchOptions::new().with_row_count(Some(row_count)),
+        )?)
+    }
+
+    pub(super) fn create_column_builders(
         projected_schema: &Schema,
     ) -> Result<Vec<Box<dyn ArrayBuilder>>, DataFusionError> {
         let mut builders = vec![];
@@ -378,6 +719,27 @@ impl DatabaseTable {
                     Box::new(Time64MicrosecondBuilder::with_capacity(BATCH_SIZE))
                         as Box<dyn ArrayBuilder>
                 }
+                #[cfg(feature = "postgres")]
+                DataType::Interval(IntervalUnit::MonthDayNano) => {
+                    Box::new(IntervalMonthDayNanoBuilder::with_capacity(BATCH_SIZE))
+                        as Box<dyn ArrayBuilder>
+                }
+                #[cfg(feature = "postgres")]
+                DataType::List(item) => match item.data_type() {
+                    DataType::Boolean => list_builder!(item, BooleanBuilder::new()),
+                    DataType::Int16 => list_builder!(item, Int16Builder::new()),
+                    DataType::Int32 => list_builder!(item, Int32Builder::new()),
+                    DataType::Int64 => list_builder!(item, Int64Builder::new()),
+                    DataType::Float32 => list_builder!(item, Float32Builder::new()),
+                    DataType::Float64 => list_builder!(item, Float64Builder::new()),
+                    DataType::Utf8 => list_builder!(item, StringBuilder::new()),
+                    _ => {
+                        return Err(DataFusionError::NotImplemented(format!(
+                            "Unsupported data type: {:?}",
+                            field.data_type()
+                        )))
+                    }
+                },
                 _ => {
                     return Err(DataFusionError::NotImplemented(format!(
                         "Unsupported data type: {:?}",
@@ -390,7 +752,8 @@ impl DatabaseTable {
         Ok(builders)
     }
 
-    fn append_value_to_builder(
+    #[allow(clippy::too_many_lines)]
+    pub(super) fn append_value_to_builder(
         builder: &mut Box<dyn ArrayBuilder>,
         field: &Field,
         original_dtype: &str,
@@ -420,12 +783,16 @@ impl DatabaseTable {
             }
             DataType::Float32 => append_value!(builder, field, row, f32, Float32Builder)?,
             DataType::Float64 => append_value!(builder, field, row, f64, Float64Builder)?,
-            DataType::Decimal128(_precision, scale) => {
+            DataType::Decimal128(precision, scale) => {
                 if let Some(builder) = builder.as_any_mut().downcast_mut::<Decimal128Builder>() {
-                    if let Some(value) = row.get::<sqlx::types::Decimal>(field.name()) {
-                        #[allow(clippy::cast_sign_loss)]
-                        let scale_factor = sqlx::types::Decimal::new(1, *scale as u32);
-                        let scaled_value = (value / scale_factor).to_i128().unwrap();
+                    if let Some(value) = row.get_decimal(field.name())? {
+                        let Some(scaled_value) = decimal_to_i128(value, *precision, *scale) else {
+                            return Err(DataFusionError::Execution(format!(
+                                "Value {value} of column '{}' overflows {}",
+                                field.name(),
+                                field.data_type()
+                            )));
+                        };
                         builder.append_value(scaled_value);
                     } else {
                         builder.append_null();
@@ -436,7 +803,7 @@ impl DatabaseTable {
                 #[cfg(feature = "postgres")]
                 "uuid" => {
                     if let Some(builder) = builder.as_any_mut().downcast_mut::<StringBuilder>() {
-                        if let Some(uuid) = row.get::<sqlx::types::Uuid>(field.name()) {
+                        if let Some(uuid) = row.get::<sqlx::types::Uuid>(field.name())? {
                             builder.append_value(uuid.to_string());
                         } else {
                             builder.append_null();
@@ -446,21 +813,24 @@ impl DatabaseTable {
                 _ => append_value!(builder, field, row, String, StringBuilder)?,
             },
             DataType::Binary => append_value!(builder, field, row, Vec<u8>, BinaryBuilder)?,
-            DataType::Timestamp(TimeUnit::Microsecond, _) => {
+            DataType::Timestamp(TimeUnit::Microsecond, timezone) => {
                 if let Some(builder) = builder
                     .as_any_mut()
                     .downcast_mut::<TimestampMicrosecondBuilder>()
                 {
-                    if let Some(ts) = row.get::<chrono::DateTime<chrono::Utc>>(field.name()) {
-                        builder.append_value(ts.timestamp_micros());
+                    // timestamps without time zone are not decodable as UTC
+                    let ts = if timezone.is_some() {
+                        row.get::<chrono::DateTime<chrono::Utc>>(field.name())?
                     } else {
-                        builder.append_null();
-                    }
+                        row.get::<chrono::NaiveDateTime>(field.name())?
+                            .map(|ts| ts.and_utc())
+                    };
+                    builder.append_option(ts.map(|ts| ts.timestamp_micros()));
                 }
             }
             DataType::Date32 => {
                 if let Some(builder) = builder.as_any_mut().downcast_mut::<Date32Builder>() {
-                    if let Some(nd) = row.get::<chrono::NaiveDate>(field.name()) {
+                    if let Some(nd) = row.get::<chrono::NaiveDate>(field.name())? {
                         builder.append_value(nd.num_days_from_ce() - 719_163 /* 1970-01-01 */);
                     } else {
                         builder.append_null();
@@ -472,7 +842,7 @@ impl DatabaseTable {
                     .as_any_mut()
                     .downcast_mut::<Time64MicrosecondBuilder>()
                 {
-                    if let Some(nt) = row.get::<chrono::NaiveTime>(field.name()) {
+                    if let Some(nt) = row.get::<chrono::NaiveTime>(field.name())? {
                         builder.append_value(
                             i64::from(nt.num_seconds_from_midnight()) * 1_000_000
                                 + i64::from(nt.nanosecond()) / 1_000,
@@ -482,6 +852,46 @@ impl DatabaseTable {
                     }
                 }
             }
+            #[cfg(feature = "postgres")]
+            DataType::Interval(IntervalUnit::MonthDayNano) => {
+                if let Some(builder) = builder
+                    .as_any_mut()
+                    .downcast_mut::<IntervalMonthDayNanoBuilder>()
+                {
+                    if let Some(interval) = row.get_postgres::<PgInterval>(field.name())? {
+                        let nanoseconds =
+                            interval.microseconds.checked_mul(1_000).ok_or_else(|| {
+                                DataFusionError::Execution(format!(
+                                    "Interval of '{}' out of range",
+                                    field.name()
+                                ))
+                            })?;
+                        builder.append_value(IntervalMonthDayNano::new(
+                            interval.months,
+                            interval.days,
+                            nanoseconds,
+                        ));
+                    } else {
+                        builder.append_null();
+                    }
+                }
+            }
+            #[cfg(feature = "postgres")]
+            DataType::List(item) => match item.data_type() {
+                DataType::Boolean => append_list!(builder, field, row, bool, BooleanBuilder)?,
+                DataType::Int16 => append_list!(builder, field, row, i16, Int16Builder)?,
+                DataType::Int32 => append_list!(builder, field, row, i32, Int32Builder)?,
+                DataType::Int64 => append_list!(builder, field, row, i64, Int64Builder)?,
+                DataType::Float32 => append_list!(builder, field, row, f32, Float32Builder)?,
+                DataType::Float64 => append_list!(builder, field, row, f64, Float64Builder)?,
+                DataType::Utf8 => append_list!(builder, field, row, String, StringBuilder)?,
+                _ => {
+                    return Err(DataFusionError::Internal(format!(
+                        "Unsupported data type for field '{}'",
+                        field.name()
+                    )))
+                }
+            },
             _ => {
                 return Err(DataFusionError::Internal(format!(
                     "Unsupported data type for field '{}'",
@@ -493,3 +903,423 @@ impl DatabaseTable {
         Ok(())
     }
 }
+
+/// Unscaled value of `Decimal128(precision, scale)` built from the mantissa, the fractional digits
+/// beyond the scale are truncated. `None` if the value does not fit in the precision.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+fn decimal_to_i128(value: sqlx::types::Decimal, precision: u8, scale: i8) -> Option<i128> {
+    let scale = u32::try_from(scale).ok()?;
+    let value = value.trunc_with_scale(scale);
+    let unscaled = value
+        .mantissa()
+        .checked_mul(10_i128.checked_pow(scale.checked_sub(value.scale())?)?)?;
+
+    Decimal128Type::is_valid_decimal_precision(unscaled, precision).then_some(unscaled)
+}
+
+/// Remote errors caused by the columns not matched to the cached schema are reported as `SchemaMismatch`,
+/// the caller retries if the remote schemas of the referenced tables have changed since cached.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+fn scan_error(e: &sqlx::Error) -> DataFusionError {
+    let column_mismatch = e.as_database_error().is_some_and(|db_error| {
+        // undefined_column (PostgreSQL), ER_BAD_FIELD_ERROR (MySQL)
+        matches!(db_error.code().as_deref(), Some("42703" | "42S22"))
+            || db_error.message().contains("no such column")
+    });
+
+    if !column_mismatch {
+        return DataFusionError::Execution(e.to_string());
+    }
+
+    DataFusionError::External(Box::new(SchemaMismatch(e.to_string())))
+}
+
+/// Streams the rows of remote query into record batches as they arrive,
+/// the connection is released when the stream is dropped.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+#[derive(Debug)]
+pub(super) struct DatabaseScanStream {
+    pool: AnyDatabasePool,
+    sql: String,
+    params: Vec<QueryParam>,
+    schema: SchemaRef,
+    original_dtypes: HashMap<String, String>,
+    read_only: bool,
+    #[cfg(feature = "postgres")]
+    snapshot: Option<Arc<SharedSnapshot>>,
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+impl DatabaseScanStream {
+    pub(super) fn new(
+        pool: AnyDatabasePool,
+        sql: String,
+        schema: SchemaRef,
+        original_dtypes: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            pool,
+            sql,
+            params: vec![],
+            schema,
+            original_dtypes,
+            read_only: false,
+            #[cfg(feature = "postgres")]
+            snapshot: None,
+        }
+    }
+
+    /// Bind parameters of the native query.
+    pub(super) fn with_params(mut self, params: Vec<QueryParam>) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Native query of the configured account, executed in a read-only transaction.
+    pub(super) fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Snapshot shared by the partitions of the scan.
+    #[cfg(feature = "postgres")]
+    pub(super) fn with_snapshot(mut self, snapshot: Arc<SharedSnapshot>) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+impl PartitionStream for DatabaseScanStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        self.stream()
+    }
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+impl DatabaseScanStream {
+    pub(super) fn stream(&self) -> SendableRecordBatchStream {
+        let mut builder = RecordBatchReceiverStream::builder(self.schema.clone(), 2);
+        let tx = builder.tx();
+
+        let pool = self.pool.clone();
+        let sql = self.sql.clone();
+        let params = self.params.clone();
+        let schema = self.schema.clone();
+        let original_dtypes = self.original_dtypes.clone();
+        let read_only = self.read_only;
+        #[cfg(feature = "postgres")]
+        let snapshot = self.snapshot.clone();
+
+        builder.spawn(async move {
+            log::debug!("Remote query: {sql}");
+
+            if read_only {
+                let mut transaction = pool.begin_read_only().await.map_err(|e| scan_error(&e))?;
+
+                let rows = transaction.fetch_with_params(&sql, &params);
+                Self::send_rows(rows, &schema, &original_dtypes, &tx).await?;
+
+                return transaction.rollback().await.map_err(|e| scan_error(&e));
+            }
+
+            #[cfg(feature = "postgres")]
+            if let (Some(snapshot), AnyDatabasePool::Postgres(pool)) = (snapshot, &pool) {
+                let (mut transaction, exported) =
+                    snapshot.begin(pool).await.map_err(|e| scan_error(&e))?;
+
+                let rows = sqlx::query(&sql)
+                    .fetch(&mut *transaction)
+                    .map(|row| row.map(AnyDatabaseRow::Postgres));
+                Self::send_rows(rows, &schema, &original_dtypes, &tx).await?;
+
+                snapshot.release(transaction, exported);
+                return Ok(());
+            }
+
+            let rows = pool.fetch_with_params(&sql, &params);
+            Self::send_rows(rows, &schema, &original_dtypes, &tx).await
+        });
+
+        builder.build()
+    }
+
+    /// Sends the rows in record batches, stops when the query was cancelled.
+    async fn send_rows(
+        mut rows: impl Stream<Item = Result<AnyDatabaseRow, sqlx::Error>> + Unpin,
+        schema: &SchemaRef,
+        original_dtypes: &HashMap<String, String>,
+        tx: &Sender<Result<RecordBatch, DataFusionError>>,
+    ) -> Result<(), DataFusionError> {
+        let mut builders = DatabaseTable::create_column_builders(schema)?;
+        let mut row_count = 0;
+
+        while let Some(row) = rows.next().await {
+            let row = row.map_err(|e| scan_error(&e))?;
+            row_count += 1;
+
+            for (index, field) in schema.fields().iter().enumerate() {
+                DatabaseTable::append_value_to_builder(
+                    &mut builders[index],
+                    field,
+                    original_dtypes.get(field.name()).map_or("", String::as_str),
+                    &row,
+                )?;
+            }
+
+            if row_count == BATCH_SIZE {
+                let arrays: Vec<ArrayRef> = builders
+                    .into_iter()
+                    .map(|mut builder| builder.finish())
+                    .collect();
+
+                let batch = DatabaseTable::to_record_batch(schema, arrays, row_count)?;
+
+                // waits while the consumer is busy
+                if tx.send(Ok(batch)).await.is_err() {
+                    return Ok(());
+                }
+
+                builders = DatabaseTable::create_column_builders(schema)?;
+                row_count = 0;
+            }
+        }
+
+        if row_count > 0 {
+            let arrays: Vec<ArrayRef> = builders
+                .into_iter()
+                .map(|mut builder| builder.finish())
+                .collect();
+
+            let _ = tx
+                .send(DatabaseTable::to_record_batch(schema, arrays, row_count))
+                .await;
+        }
+
+        Ok(())
+    }
+}
+
+/// Plain comparisons and logical operators of columns and literals, and basic aggregations are evaluated exactly
+/// by the remote database. Casts, arithmetic that overflows differently, and strings compared by the collation
+/// of the remote database are re-evaluated, and other functions are not pushed down.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(super) fn pushdown_support(
+    engine_type: &DatabaseEngineType,
+    expr: &Expr,
+    schema: &DFSchema,
+) -> TableProviderFilterPushDown {
+    let mut pushdown = TableProviderFilterPushDown::Exact;
+    let compares_strings = |exprs: &[&Expr], ordering: bool| {
+        collates_strings(engine_type, ordering) && exprs.iter().any(|expr| is_string(expr, schema))
+    };
+
+    let result = expr.apply(|expr| {
+        match expr {
+            Expr::Column(_)
+            | Expr::Literal(..)
+            | Expr::Not(_)
+            | Expr::Negative(_)
+            | Expr::IsNull(_)
+            | Expr::IsNotNull(_)
+            | Expr::IsTrue(_)
+            | Expr::IsFalse(_)
+            | Expr::IsNotTrue(_)
+            | Expr::IsNotFalse(_)
+            | Expr::Alias(_) => {}
+            Expr::Between(between) => {
+                if compares_strings(&[&between.expr], true) {
+                    pushdown = TableProviderFilterPushDown::Inexact;
+                }
+            }
+            Expr::InList(in_list) => {
+                if compares_strings(&[&in_list.expr], false) {
+                    pushdown = TableProviderFilterPushDown::Inexact;
+                }
+            }
+            Expr::AggregateFunction(aggregate)
+                if matches!(
+                    aggregate.func.name(),
+                    "count" | "sum" | "min" | "max" | "avg"
+                ) && aggregate.params.filter.is_none() =>
+            {
+                if matches!(aggregate.func.name(), "min" | "max")
+                    && compares_strings(&aggregate.params.args.iter().collect::<Vec<_>>(), true)
+                {
+                    pushdown = TableProviderFilterPushDown::Inexact;
+                }
+            }
+            Expr::BinaryExpr(BinaryExpr { left, op, right }) => match op {
+                Operator::And | Operator::Or => {}
+                Operator::Eq | Operator::NotEq => {
+                    if compares_strings(&[left, right], false) {
+                        pushdown = TableProviderFilterPushDown::Inexact;
+                    }
+                }
+                Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq => {
+                    if compares_strings(&[left, right], true) {
+                        pushdown = TableProviderFilterPushDown::Inexact;
+                    }
+                }
+                // overflows are errors in DataFusion, but wrapped or widened by some databases
+                Operator::Plus | Operator::Minus | Operator::Multiply => {
+                    pushdown = TableProviderFilterPushDown::Inexact;
+                }
+                _ => {
+                    pushdown = TableProviderFilterPushDown::Unsupported;
+                    return Ok(TreeNodeRecursion::Stop);
+                }
+            },
+            #[cfg(feature = "sqlite")]
+            Expr::Like(Like {
+                case_insensitive: false,
+                ..
+            }) if matches!(engine_type, DatabaseEngineType::Sqlite) => {
+                pushdown = TableProviderFilterPushDown::Inexact;
+            }
+            Expr::Like(_) => {
+                if collates_strings(engine_type, false) {
+                    pushdown = TableProviderFilterPushDown::Inexact;
+                }
+            }
+            Expr::Cast(_) => pushdown = TableProviderFilterPushDown::Inexact,
+            _ => {
+                pushdown = TableProviderFilterPushDown::Unsupported;
+                return Ok(TreeNodeRecursion::Stop);
+            }
+        }
+        Ok(TreeNodeRecursion::Continue)
+    });
+
+    match result {
+        Ok(_) => pushdown,
+        Err(_) => TableProviderFilterPushDown::Unsupported,
+    }
+}
+
+/// Strings are compared by the collation of the remote database instead of the byte order of `DataFusion`,
+/// `MySQL` collations are case-insensitive, and `PostgreSQL` orders by the locale.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+pub(super) fn collates_strings(
+    engine_type: &DatabaseEngineType,
+    #[allow(unused_variables)] ordering: bool,
+) -> bool {
+    match engine_type {
+        #[cfg(feature = "postgres")]
+        DatabaseEngineType::Postgres => ordering,
+        #[cfg(feature = "mysql")]
+        DatabaseEngineType::MySQL => true,
+        #[cfg(feature = "sqlite")]
+        DatabaseEngineType::Sqlite => false,
+    }
+}
+
+/// Unknown types are also regarded as strings.
+pub(super) fn is_string(expr: &Expr, schema: &DFSchema) -> bool {
+    expr.get_type(schema).map_or(true, |data_type| {
+        matches!(
+            data_type,
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Utf8View
+        )
+    })
+}
+
+pub(super) fn quote_identifier(dialect: &dyn Dialect, identifier: &str) -> String {
+    match dialect.identifier_quote_style(identifier) {
+        Some(quote) => format!(
+            "{quote}{}{quote}",
+            identifier.replace(quote, &format!("{quote}{quote}"))
+        ),
+        None => identifier.to_string(),
+    }
+}
+
+/// Quotes each part of schema qualified table name, e.g. `public.table`.
+pub(super) fn quote_table_name(dialect: &dyn Dialect, table_name: &str) -> String {
+    table_name
+        .split('.')
+        .map(|part| quote_identifier(dialect, part))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Column references are qualified by the session table name (e.g. `table@namespace`), unqualifies them for remote.
+pub(super) fn filter_to_sql(
+    dialect: &dyn Dialect,
+    filter: &Expr,
+) -> Result<String, DataFusionError> {
+    Ok(Unparser::new(dialect)
+        .expr_to_sql(&unqualify(filter))?
+        .to_string())
+}
+
+pub(super) fn unqualify(expr: &Expr) -> Expr {
+    expr.clone()
+        .transform(|expr| {
+            Ok(match expr {
+                Expr::Column(column) => {
+                    Transformed::yes(Expr::Column(Column::new_unqualified(column.name)))
+                }
+                expr => Transformed::no(expr),
+            })
+        })
+        .map_or_else(|_| expr.clone(), |transformed| transformed.data)
+}
+
+#[cfg(all(test, feature = "postgres"))]
+mod tests {
+    use super::*;
+
+    // the key bounds are given, the pool never connects
+    fn table(max_connections: u32) -> DatabaseTable {
+        DatabaseTable {
+            engine_type: DatabaseEngineType::Postgres,
+            pool: AnyDatabasePool::new("postgres://localhost/sales", max_connections).unwrap(),
+            namespace: "postgres".to_string(),
+            schema: Arc::new(Schema::new(vec![Field::new("id", DataType::Int64, false)])),
+            table_name: "public.orders".to_string(),
+            partitioning: Some(ScanPartitioning {
+                partitions: 4,
+                column: "id".to_string(),
+            }),
+            key_bounds: OnceCell::new_with(Some(Some((1, 100)))),
+            selects: HashMap::new(),
+        }
+    }
+
+    // unconstrained `numeric` of PostgreSQL is mapped to Decimal128(38, 10)
+    #[test]
+    fn large_numeric_to_decimal() {
+        let decimal = |v: &str| v.parse::<sqlx::types::Decimal>().unwrap();
+
+        assert_eq!(
+            decimal_to_i128(decimal("7900000000000000000.5"), 38, 10),
+            Some(79_000_000_000_000_000_005_000_000_000)
+        );
+        assert_eq!(
+            decimal_to_i128(decimal("-1.23456789012"), 38, 10),
+            Some(-12_345_678_901)
+        );
+        assert_eq!(
+            decimal_to_i128(decimal("1234567890123456789012345678"), 38, 10),
+            Some(12_345_678_901_234_567_890_123_456_780_000_000_000)
+        );
+        assert_eq!(decimal_to_i128(sqlx::types::Decimal::MAX, 38, 10), None);
+        assert_eq!(decimal_to_i128(decimal("1000"), 5, 2), None);
+    }
+
+    #[tokio::test]
+    async fn single_scan_by_single_connection() {
+        assert_eq!(table(1).partition_ranges().await.unwrap(), vec![None]);
+    }
+
+    #[tokio::test]
+    async fn partitions_leave_connection_for_snapshot() {
+        assert_eq!(table(3).partition_ranges().await.unwrap().len(), 2);
+        assert_eq!(table(10).partition_ranges().await.unwrap().len(), 4);
+    }
+}
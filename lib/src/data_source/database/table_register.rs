@@ -2,74 +2,187 @@
 // Sasaki, Naoki <nsasaki@sal.co.jp> July 27, 2024
 //
 
-use futures::future::BoxFuture;
+use std::ops::ControlFlow;
+use std::sync::Arc;
 
 use datafusion::{
+    catalog::MemorySchemaProvider,
+    common::{SchemaError, TableReference},
     error::DataFusionError,
     execution::context::SessionContext,
     sql::sqlparser::{
-        ast::{Query, Select, SetExpr, Statement, TableFactor},
+        ast::{visit_relations, Statement},
         dialect::GenericDialect,
         parser::Parser,
     },
 };
 
+use crate::data_source::database::{
+    federation,
+    impersonation::DatabaseIdentity,
+    table_resolver::{SchemaMismatch, TableResolver},
+};
 use crate::settings::Settings;
 
 pub async fn from_sql(ctx: &SessionContext, sql: &str) -> Result<(), DataFusionError> {
-    let ast = Parser::parse_sql(&GenericDialect {}, sql)
-        .map_err(|e| DataFusionError::SQL(Box::new(e), None))?;
+    let (table_identifiers, uses_information_schema) = parse_relations(sql)?;
 
-    for statement in ast {
-        if let Statement::Query(query) = statement {
-            process_query(ctx, &query).await?;
+    // lists the tables of database catalogs
+    if uses_information_schema {
+        let identity = DatabaseIdentity::from_context(ctx);
+        for resolver in Settings::global().database_pool_manager.resolvers.values() {
+            resolver.load_table_list(identity.as_deref()).await?;
         }
     }
 
+    for table_identifier in table_identifiers {
+        register_table(ctx, &table_identifier).await?;
+    }
+
     Ok(())
 }
 
-fn process_query<'a>(
-    ctx: &'a SessionContext,
-    query: &'a Query,
-) -> BoxFuture<'a, Result<(), DataFusionError>> {
-    Box::pin(async move {
-        match &*query.body {
-            SetExpr::Select(select) => process_select(ctx, select).await,
-            SetExpr::Query(subquery) => process_query(ctx, subquery).await,
-            _ => Ok(()), // ignores `Insert`, `Update` and `Delete` statement
+/// Reloads the cached schemas of the database tables referenced by the SQL,
+/// returns true if the remote schema of any table has changed since cached.
+pub async fn refresh(ctx: &SessionContext, sql: &str) -> Result<bool, DataFusionError> {
+    let identity = DatabaseIdentity::from_context(ctx);
+    let mut changed = false;
+
+    for table_identifier in parse_relations(sql)?.0 {
+        if let Some((table_name, resolver)) =
+            resolve(&table_identifier).or_else(|| resolve_catalog(&table_identifier))
+        {
+            changed |= resolver.refresh(table_name, identity.as_deref()).await?;
+        }
+    }
+
+    Ok(changed)
+}
+
+/// Column references unknown to the cached schema, or reported by the remote database,
+/// the caller retries only if `refresh` finds the remote schema changed.
+pub fn is_schema_mismatch(e: &DataFusionError) -> bool {
+    match e.find_root() {
+        DataFusionError::SchemaError(error, _) => {
+            matches!(error.as_ref(), SchemaError::FieldNotFound { .. })
         }
-    })
+        DataFusionError::External(error) => error.downcast_ref::<SchemaMismatch>().is_some(),
+        _ => false,
+    }
 }
 
-async fn process_select(ctx: &SessionContext, select: &Select) -> Result<(), DataFusionError> {
-    for table_with_joins in &select.from {
-        if let TableFactor::Table { name, .. } = &table_with_joins.relation {
-            let table_identifier = name.to_string();
-            let (table_name, namespace) = if let Some(pos) = table_identifier.rfind('@') {
-                let (left, right) = table_identifier.split_at(pos);
-                (left.to_string(), Some(right[1..].to_string()))
-            } else {
-                (table_identifier.to_string(), None)
-            };
-
-            if let Some(namespace) = namespace {
-                if let Some(resolver) = Settings::global()
-                    .database_pool_manager
-                    .resolvers
-                    .get(&namespace)
-                {
-                    let full_table_name = format!("{table_name}@{namespace}");
-
-                    if ctx.table_exist(&full_table_name)? {
-                        ctx.deregister_table(&full_table_name)?;
+/// Every table reference in the `FROM` clauses, joins, CTEs, derived tables, set operations and subqueries,
+/// and whether the information schema is used.
+fn parse_relations(sql: &str) -> Result<(Vec<String>, bool), DataFusionError> {
+    let ast = Parser::parse_sql(&GenericDialect {}, sql)
+        .map_err(|e| DataFusionError::SQL(Box::new(e), None))?;
+
+    let mut table_identifiers: Vec<String> = vec![];
+    let mut uses_information_schema = false;
+
+    for statement in &ast {
+        match statement {
+            Statement::Query(query) => {
+                let _ = visit_relations(query, |name| {
+                    let table_identifier = name.to_string();
+
+                    if table_identifier
+                        .to_lowercase()
+                        .starts_with("information_schema.")
+                    {
+                        uses_information_schema = true;
                     }
 
-                    ctx.register_table(&full_table_name, resolver.get_table(&table_name).await?)?;
-                }
+                    if !table_identifiers.contains(&table_identifier) {
+                        table_identifiers.push(table_identifier);
+                    }
+
+                    ControlFlow::<()>::Continue(())
+                });
+            }
+            Statement::ShowTables { .. } | Statement::ShowColumns { .. } => {
+                uses_information_schema = true;
             }
+            _ => {}
         }
     }
 
+    Ok((table_identifiers, uses_information_schema))
+}
+
+/// Splits `table@namespace` into the table name and the resolver of namespace.
+fn resolve(table_identifier: &str) -> Option<(&str, &'static Arc<TableResolver>)> {
+    let pos = table_identifier.rfind('@')?;
+    let (table_name, namespace) = table_identifier.split_at(pos);
+
+    Settings::global()
+        .database_pool_manager
+        .resolvers
+        .get(&namespace[1..])
+        .map(|resolver| (table_name, resolver))
+}
+
+/// Splits `namespace.schema.table` of the database catalog into the schema qualified table name and the resolver.
+fn resolve_catalog(table_identifier: &str) -> Option<(&str, &'static Arc<TableResolver>)> {
+    let (namespace, table_name) = table_identifier.split_once('.')?;
+
+    if !table_name.contains('.') {
+        return None;
+    }
+
+    Settings::global()
+        .database_pool_manager
+        .resolvers
+        .get(namespace)
+        .map(|resolver| (table_name, resolver))
+}
+
+async fn register_table(
+    ctx: &SessionContext,
+    table_identifier: &str,
+) -> Result<(), DataFusionError> {
+    // not an external database table, or unknown namespace
+    if let Some((table_name, resolver)) = resolve(table_identifier) {
+        let table_reference =
+            TableReference::from(format!("{table_name}@{}", resolver.namespace()));
+
+        if let Some(schema_name) = table_reference.schema() {
+            register_schema(ctx, schema_name)?;
+        }
+
+        if ctx.table_exist(table_reference.clone())? {
+            ctx.deregister_table(table_reference.clone())?;
+        }
+
+        let identity = DatabaseIdentity::from_context(ctx);
+        let table = resolver.get_table(table_name, identity.as_deref()).await?;
+
+        ctx.register_table(table_reference, table)?;
+        federation::install(ctx);
+    }
+
+    Ok(())
+}
+
+/// Schema qualified table (e.g. `public.table@namespace`) is placed in the schema of the same name.
+fn register_schema(ctx: &SessionContext, schema_name: &str) -> Result<(), DataFusionError> {
+    let catalog_name = ctx
+        .state()
+        .config()
+        .options()
+        .catalog
+        .default_catalog
+        .clone();
+
+    let Some(catalog) = ctx.catalog(&catalog_name) else {
+        return Err(DataFusionError::Plan(format!(
+            "Catalog '{catalog_name}' does not exist"
+        )));
+    };
+
+    if catalog.schema(schema_name).is_none() {
+        catalog.register_schema(schema_name, Arc::new(MemorySchemaProvider::new()))?;
+    }
+
     Ok(())
 }
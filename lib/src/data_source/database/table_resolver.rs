@@ -3,65 +3,302 @@
 //
 
 use std::collections::HashMap;
+use std::fmt;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use datafusion::{datasource::TableProvider, error::DataFusionError};
 
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::data_source::database::{any_pool::AnyDatabasePool, table_provider::DatabaseTable};
 use crate::data_source::database::{
-    any_pool::AnyDatabasePool, engine_type::DatabaseEngineType, table_provider::DatabaseTable,
+    driver::{DatabaseDriver, DriverTable},
+    impersonation::{DatabaseIdentity, DatabaseUser, Impersonation},
+    table_provider::TableOptions,
 };
 
-#[derive(Clone)]
+// cached by the table name and the impersonated user
+type Tables = HashMap<(String, Option<DatabaseUser>), (Arc<dyn TableProvider>, Instant)>;
+// pairs of schema and table name visible to the impersonated user
+type TableLists = HashMap<Option<DatabaseUser>, (Vec<(String, String)>, Instant)>;
+
+#[derive(Debug, Clone)]
 pub struct TableResolver {
-    engine_type: DatabaseEngineType,
-    pool: AnyDatabasePool,
+    driver: Arc<dyn DatabaseDriver>,
+    namespace: String,
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "mysql", feature = "sqlite")),
+        allow(dead_code)
+    )]
     database: String,
-    tables: Arc<RwLock<HashMap<String, Arc<dyn TableProvider>>>>,
+    tables: Arc<RwLock<Tables>>,
+    table_lists: Arc<RwLock<TableLists>>,
     schema_cache: bool,
+    schema_cache_ttl: Option<Duration>,
+    #[cfg_attr(
+        not(any(feature = "postgres", feature = "mysql", feature = "sqlite")),
+        allow(dead_code)
+    )]
+    table_options: TableOptions,
+    impersonation: Option<Arc<Impersonation>>,
+    allow_native_query: bool,
+}
+
+/// Remote database reports the columns which are not matched to the cached schema.
+#[derive(Debug)]
+pub struct SchemaMismatch(pub String);
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Schema mismatch: {}", self.0)
+    }
 }
 
+impl std::error::Error for SchemaMismatch {}
+
 impl TableResolver {
     pub fn new(
-        engine_type: DatabaseEngineType,
-        pool: AnyDatabasePool,
+        driver: Arc<dyn DatabaseDriver>,
+        namespace: &str,
         database: &str,
         schema_cache: bool,
+        schema_cache_ttl: Option<Duration>,
+        table_options: TableOptions,
+        impersonation: Option<Impersonation>,
     ) -> Self {
         TableResolver {
-            engine_type,
-            pool,
+            driver,
+            namespace: namespace.to_string(),
             database: database.to_string(),
             tables: Arc::new(RwLock::new(HashMap::new())),
+            table_lists: Arc::new(RwLock::new(HashMap::new())),
             schema_cache,
+            schema_cache_ttl,
+            table_options,
+            impersonation: impersonation.map(Arc::new),
+            allow_native_query: false,
+        }
+    }
+
+    /// Native queries are executed as is by the database, disabled by default.
+    #[must_use]
+    pub fn with_native_query(mut self, allow: bool) -> Self {
+        self.allow_native_query = allow;
+        self
+    }
+
+    pub fn allow_native_query(&self) -> bool {
+        self.allow_native_query
+    }
+
+    /// Queries are executed as the user of the request, not by the configured account.
+    /// Only the built-in engines are impersonated.
+    pub fn impersonates(&self) -> bool {
+        self.impersonation.is_some()
+    }
+
+    pub fn driver(&self) -> &Arc<dyn DatabaseDriver> {
+        &self.driver
+    }
+
+    /// Connection pool of the built-in engines, `None` for the registered drivers.
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    pub fn pool(&self) -> Option<&AnyDatabasePool> {
+        self.driver.as_any().downcast_ref::<AnyDatabasePool>()
+    }
+
+    /// Driver of the user of impersonation mode, or the configured account.
+    pub fn driver_as(
+        &self,
+        identity: Option<&DatabaseIdentity>,
+    ) -> Result<Arc<dyn DatabaseDriver>, DataFusionError> {
+        match &self.impersonation {
+            Some(impersonation) => impersonation.driver(&impersonation.user(identity)?),
+            None => Ok(self.driver.clone()),
+        }
+    }
+
+    // `None` for the configured account
+    fn user(
+        &self,
+        identity: Option<&DatabaseIdentity>,
+    ) -> Result<Option<DatabaseUser>, DataFusionError> {
+        match &self.impersonation {
+            Some(impersonation) => impersonation.user(identity).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Number of the open connections and the idle connections, including the pools of impersonated users.
+    pub fn connections(&self) -> (u32, usize) {
+        let (size, idle) = self.driver.connections();
+
+        match &self.impersonation {
+            Some(impersonation) => {
+                let (impersonated_size, impersonated_idle) = impersonation.connections();
+                (size + impersonated_size, idle + impersonated_idle)
+            }
+            None => (size, idle),
         }
     }
 
+    pub async fn ping(&self) -> Result<(), DataFusionError> {
+        self.driver.ping().await
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn schema_cache(&self) -> bool {
+        self.schema_cache
+    }
+
+    /// Pairs of schema and table name of the user retrieved by `load_table_list`.
+    pub fn table_list(&self, identity: Option<&DatabaseIdentity>) -> Option<Vec<(String, String)>> {
+        let user = self.user(identity).ok()?;
+
+        self.table_lists
+            .read()
+            .unwrap()
+            .get(&user)
+            .map(|(table_list, _)| table_list.clone())
+    }
+
+    pub async fn load_table_list(
+        &self,
+        identity: Option<&DatabaseIdentity>,
+    ) -> Result<(), DataFusionError> {
+        // not listed for the request without the user of impersonation mode
+        let Ok(user) = self.user(identity) else {
+            return Ok(());
+        };
+
+        if let Some((_, cached_at)) = self.table_lists.read().unwrap().get(&user) {
+            if self.is_fresh(cached_at) {
+                return Ok(());
+            }
+        }
+
+        let table_list = self.driver_as(identity)?.table_list().await?;
+        self.table_lists
+            .write()
+            .unwrap()
+            .insert(user, (table_list, Instant::now()));
+
+        Ok(())
+    }
+
     pub async fn get_table(
         &self,
         table_name: &str,
+        identity: Option<&DatabaseIdentity>,
     ) -> Result<Arc<dyn TableProvider>, DataFusionError> {
-        if self.schema_cache {
-            let tables = self.tables.read().unwrap();
-            if let Some(table) = tables.get(table_name) {
+        let key = (table_name.to_string(), self.user(identity)?);
+
+        if let Some((table, cached_at)) = self.tables.read().unwrap().get(&key) {
+            if self.is_fresh(cached_at) {
                 return Ok(table.clone());
             }
         }
 
-        let table = Arc::new(
-            DatabaseTable::new(
-                &self.engine_type,
-                self.pool.clone(),
-                &self.database,
-                table_name,
-            )
-            .await?,
-        );
+        let table = self.load_table(table_name, identity).await?;
 
         if self.schema_cache {
             let mut tables = self.tables.write().unwrap();
-            tables.insert(table_name.to_string(), table.clone());
+            tables.insert(key, (table.clone(), Instant::now()));
         }
 
         Ok(table)
     }
+
+    /// Reloads the schema of the cached table, the cached table is replaced and returns true
+    /// only if the remote schema has changed since cached.
+    pub async fn refresh(
+        &self,
+        table_name: &str,
+        identity: Option<&DatabaseIdentity>,
+    ) -> Result<bool, DataFusionError> {
+        let key = (table_name.to_string(), self.user(identity)?);
+
+        let Some(cached_schema) = self
+            .tables
+            .read()
+            .unwrap()
+            .get(&key)
+            .map(|(table, _)| table.schema())
+        else {
+            return Ok(false);
+        };
+
+        let table = self.load_table(table_name, identity).await?;
+        if table.schema() == cached_schema {
+            return Ok(false);
+        }
+
+        log::debug!(
+            "Remote schema of '{}.{table_name}' has changed",
+            self.namespace
+        );
+        self.tables
+            .write()
+            .unwrap()
+            .insert(key, (table, Instant::now()));
+
+        Ok(true)
+    }
+
+    /// Discards the cached schema of the table, or entire namespace if `None`.
+    pub fn invalidate(&self, table_name: Option<&str>) {
+        let mut tables = self.tables.write().unwrap();
+
+        if let Some(table_name) = table_name {
+            tables.retain(|(name, _), _| name != table_name);
+        } else {
+            tables.clear();
+            self.table_lists.write().unwrap().clear();
+        }
+
+        log::debug!(
+            "Invalidated schema cache of '{}{}'",
+            self.namespace,
+            table_name
+                .map(|name| format!(".{name}"))
+                .unwrap_or_default()
+        );
+    }
+
+    async fn load_table(
+        &self,
+        table_name: &str,
+        identity: Option<&DatabaseIdentity>,
+    ) -> Result<Arc<dyn TableProvider>, DataFusionError> {
+        let driver = self.driver_as(identity)?;
+
+        #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+        if let Some(pool) = driver.as_any().downcast_ref::<AnyDatabasePool>() {
+            return Ok(Arc::new(
+                DatabaseTable::new(
+                    &pool.engine_type(),
+                    pool.clone(),
+                    &self.namespace,
+                    &self.database,
+                    table_name,
+                    &self.table_options,
+                )
+                .await?,
+            ));
+        }
+
+        Ok(Arc::new(
+            DriverTable::new(driver, &self.namespace, table_name).await?,
+        ))
+    }
+
+    fn is_fresh(&self, cached_at: &Instant) -> bool {
+        self.schema_cache
+            && self
+                .schema_cache_ttl
+                .is_none_or(|ttl| cached_at.elapsed() < ttl)
+    }
 }
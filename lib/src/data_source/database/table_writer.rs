@@ -0,0 +1,712 @@
+// database/table_writer.rs: Writes session tables to external databases
+
+#![cfg_attr(
+    not(any(feature = "postgres", feature = "mysql")),
+    allow(dead_code, unused_imports, clippy::unused_async)
+)]
+
+#[cfg(feature = "postgres")]
+use datafusion::arrow::datatypes::DataType;
+#[cfg(feature = "mysql")]
+use datafusion::scalar::ScalarValue;
+use datafusion::{
+    arrow::{
+        array::Array,
+        datatypes::SchemaRef,
+        record_batch::RecordBatch,
+        util::display::{ArrayFormatter, FormatOptions},
+    },
+    error::DataFusionError,
+    execution::{context::SessionContext, SendableRecordBatchStream},
+};
+use futures::StreamExt;
+
+#[cfg(feature = "mysql")]
+use crate::data_source::database::dtype_mysql;
+#[cfg(feature = "postgres")]
+use crate::data_source::database::dtype_postgres;
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::data_source::database::{any_pool::AnyDatabasePool, engine_type::DatabaseEngineType};
+use crate::data_source::database::{
+    driver::{WriteMode, WriteTarget},
+    impersonation::DatabaseIdentity,
+    table_provider,
+};
+use crate::request::body::{DataSource, DataSourceOption, SaveMode};
+use crate::response::http_error::ResponseError;
+use crate::settings::Settings;
+
+pub async fn write(ctx: &SessionContext, data_source: &DataSource) -> Result<(), ResponseError> {
+    log::debug!("database::table_writer::write(): {data_source:?}");
+
+    let Some(resolver) = Settings::global()
+        .database_pool_manager
+        .resolvers
+        .get(&data_source.location)
+    else {
+        return Err(ResponseError::database_not_found(&data_source.location));
+    };
+
+    let options = match &data_source.options {
+        Some(options) => options.clone(),
+        None => DataSourceOption::new(),
+    };
+
+    let df = ctx.table(&data_source.name).await?;
+
+    let target = WriteTarget {
+        table_name: options
+            .table
+            .clone()
+            .unwrap_or_else(|| data_source.name.clone()),
+        columns: df
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| field.name().clone())
+            .collect(),
+        primary_keys: options.primary_keys.clone().unwrap_or_default(),
+        mode: match options.save_mode(SaveMode::ErrorIfExists) {
+            SaveMode::ErrorIfExists => WriteMode::ErrorIfExists,
+            SaveMode::Append => WriteMode::Append,
+            SaveMode::Overwrite => WriteMode::Overwrite,
+            SaveMode::Upsert => WriteMode::Upsert,
+        },
+    };
+
+    if let Some(key) = target
+        .primary_keys
+        .iter()
+        .find(|key| !target.columns.contains(key))
+    {
+        return Err(ResponseError::request_validation(format!(
+            "Primary key '{key}' not found in data source '{}'",
+            data_source.name
+        )));
+    }
+
+    if target.mode == WriteMode::Upsert && target.primary_keys.is_empty() {
+        return Err(ResponseError::request_validation(
+            "Save mode 'upsert' requires 'primaryKeys' option",
+        ));
+    }
+
+    let identity = DatabaseIdentity::from_context(ctx);
+    let driver = resolver.driver_as(identity.as_deref())?;
+
+    let stream = df.execute_stream().await?;
+
+    driver.write(&target, stream).await.map_err(|e| match e {
+        DataFusionError::NotImplemented(_) => ResponseError::unsupported_type(format!(
+            "Saving to the database driver of '{}' is not supported",
+            data_source.location
+        )),
+        e => ResponseError::from(e),
+    })
+}
+
+/// Writes to the built-in engines, the response errors are kept by `DataFusionError::External`.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+#[allow(unused_variables)]
+pub(super) async fn write_pool(
+    pool: &AnyDatabasePool,
+    target: &WriteTarget,
+    stream: SendableRecordBatchStream,
+) -> Result<(), DataFusionError> {
+    let schema = stream.schema();
+
+    let result = match pool {
+        #[cfg(feature = "postgres")]
+        AnyDatabasePool::Postgres(pool) => write_postgres(pool, target, &schema, stream).await,
+        #[cfg(feature = "mysql")]
+        AnyDatabasePool::MySql(pool) => write_mysql(pool, target, &schema, stream).await,
+        #[cfg(feature = "sqlite")]
+        AnyDatabasePool::Sqlite(_) => Err(ResponseError::unsupported_type(
+            "SQLite database is opened as read-only",
+        )),
+    };
+
+    result.map_err(|e| DataFusionError::External(Box::new(e)))
+}
+
+/// Whether the target table is created (or replaced) by the save mode.
+fn creates_table(target: &WriteTarget, exists: bool) -> Result<bool, ResponseError> {
+    match (&target.mode, exists) {
+        (WriteMode::ErrorIfExists, true) => Err(ResponseError::already_existing(format!(
+            "Already exists table '{}' in database",
+            target.table_name
+        ))),
+        (WriteMode::Append | WriteMode::Upsert, true) => Ok(false),
+        (WriteMode::Overwrite, true) | (_, false) => Ok(true),
+    }
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+fn create_table_statement(
+    engine_type: &DatabaseEngineType,
+    target: &WriteTarget,
+    schema: &SchemaRef,
+    table_name: &str,
+) -> Result<String, ResponseError> {
+    let dialect = engine_type.unparser_dialect();
+    let mut columns = vec![];
+
+    for field in schema.fields() {
+        let key = target.primary_keys.contains(field.name());
+
+        let dtype = match engine_type {
+            #[cfg(feature = "postgres")]
+            DatabaseEngineType::Postgres => dtype_postgres::from_arrow_dtype(field.data_type()),
+            #[cfg(feature = "mysql")]
+            DatabaseEngineType::MySQL => dtype_mysql::from_arrow_dtype(field.data_type(), key),
+            #[allow(unreachable_patterns)]
+            _ => None::<String>,
+        }
+        .ok_or_else(|| {
+            ResponseError::unsupported_type(format!(
+                "Can not create column '{}' of data type {} in database",
+                field.name(),
+                field.data_type()
+            ))
+        })?;
+
+        columns.push(format!(
+            "{} {dtype}{}",
+            table_provider::quote_identifier(dialect.as_ref(), field.name()),
+            if key || !field.is_nullable() {
+                " NOT NULL"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    if !target.primary_keys.is_empty() {
+        columns.push(format!(
+            "PRIMARY KEY ({})",
+            quote_columns(engine_type, &target.primary_keys)
+        ));
+    }
+
+    Ok(format!(
+        "CREATE TABLE {table_name} ({})",
+        columns.join(", ")
+    ))
+}
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+fn quote_columns(engine_type: &DatabaseEngineType, columns: &[String]) -> String {
+    let dialect = engine_type.unparser_dialect();
+
+    columns
+        .iter()
+        .map(|column| table_provider::quote_identifier(dialect.as_ref(), column))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Non-key columns updated by upsert.
+fn update_columns(target: &WriteTarget) -> Vec<&String> {
+    target
+        .columns
+        .iter()
+        .filter(|column| !target.primary_keys.contains(column))
+        .collect()
+}
+
+fn split_table_name(table_name: &str) -> (Option<&str>, &str) {
+    match table_name.rsplit_once('.') {
+        Some((table_schema, table)) => (Some(table_schema), table),
+        None => (None, table_name),
+    }
+}
+
+#[cfg(feature = "postgres")]
+async fn write_postgres(
+    pool: &sqlx::Pool<sqlx::Postgres>,
+    target: &WriteTarget,
+    schema: &SchemaRef,
+    mut stream: SendableRecordBatchStream,
+) -> Result<(), ResponseError> {
+    let engine_type = DatabaseEngineType::Postgres;
+    let dialect = engine_type.unparser_dialect();
+    let table_name = table_provider::quote_table_name(dialect.as_ref(), &target.table_name);
+    let columns = quote_columns(&engine_type, &target.columns);
+
+    let mut tx = pool.begin().await?;
+
+    let (table_schema, table) = split_table_name(&target.table_name);
+    let exists = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM information_schema.tables \
+        WHERE table_schema = COALESCE($1, current_schema()) AND table_name = $2",
+    )
+    .bind(table_schema)
+    .bind(table)
+    .fetch_one(&mut *tx)
+    .await?
+        > 0;
+
+    // DDL statements of PostgreSQL are rolled back with the transaction
+    if creates_table(target, exists)? {
+        if exists {
+            sqlx::query(&format!("DROP TABLE {table_name}"))
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let statement = create_table_statement(&engine_type, target, schema, &table_name)?;
+        log::debug!("Preparing table: {statement}");
+        sqlx::query(&statement).execute(&mut *tx).await?;
+    }
+
+    // upsert copies to the temporary table and merges into the target table
+    let copy_table = if target.mode == WriteMode::Upsert {
+        let temp_table =
+            table_provider::quote_identifier(dialect.as_ref(), "datafusion_server_upsert");
+        sqlx::query(&format!(
+            "CREATE TEMPORARY TABLE {temp_table} (LIKE {table_name} INCLUDING DEFAULTS) ON COMMIT DROP"
+        ))
+        .execute(&mut *tx)
+        .await?;
+        temp_table
+    } else {
+        table_name.clone()
+    };
+
+    let mut copy = tx
+        .copy_in_raw(&format!(
+            "COPY {copy_table} ({columns}) FROM STDIN WITH (FORMAT csv)"
+        ))
+        .await?;
+
+    while let Some(batch) = stream.next().await {
+        let csv = batch
+            .map_err(ResponseError::from)
+            .and_then(|batch| to_postgres_csv(&batch));
+
+        match csv {
+            Ok(csv) => {
+                copy.send(csv).await?;
+            }
+            Err(e) => {
+                let _ = copy.abort(e.to_string()).await;
+                return Err(e);
+            }
+        }
+    }
+
+    let rows = copy.finish().await?;
+
+    if target.mode == WriteMode::Upsert {
+        let updates = update_columns(target);
+
+        let on_conflict = if updates.is_empty() {
+            "DO NOTHING".to_string()
+        } else {
+            format!(
+                "DO UPDATE SET {}",
+                updates
+                    .iter()
+                    .map(|column| {
+                        let column = table_provider::quote_identifier(dialect.as_ref(), column);
+                        format!("{column} = EXCLUDED.{column}")
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+
+        sqlx::query(&format!(
+            "INSERT INTO {table_name} ({columns}) SELECT {columns} FROM {copy_table} \
+            ON CONFLICT ({}) {on_conflict}",
+            quote_columns(&engine_type, &target.primary_keys)
+        ))
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    log::debug!("Copied {rows} rows to '{}'", target.table_name);
+
+    Ok(())
+}
+
+/// CSV rows of `COPY`, values are always quoted to be distinguished from NULL of the unquoted
+/// empty field, binary values are encoded as hex format of `bytea`.
+#[cfg(feature = "postgres")]
+fn to_postgres_csv(batch: &RecordBatch) -> Result<Vec<u8>, ResponseError> {
+    let formatters = batch
+        .columns()
+        .iter()
+        .map(|array| ArrayFormatter::try_new(array.as_ref(), &FormatOptions::default()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut csv = String::new();
+
+    for index in 0..batch.num_rows() {
+        for (column, (array, formatter)) in batch.columns().iter().zip(&formatters).enumerate() {
+            if column > 0 {
+                csv.push(',');
+            }
+
+            if array.is_null(index) {
+                continue;
+            }
+
+            let value = formatter.value(index).to_string();
+
+            csv.push('"');
+            if matches!(
+                array.data_type(),
+                DataType::Binary | DataType::LargeBinary | DataType::BinaryView
+            ) {
+                csv.push_str("\\x");
+            }
+            csv.push_str(&value.replace('"', "\"\""));
+            csv.push('"');
+        }
+
+        csv.push('\n');
+    }
+
+    Ok(csv.into_bytes())
+}
+
+/// Bind value of `MySQL` `INSERT` statement.
+#[cfg(feature = "mysql")]
+enum BindValue {
+    Bool(Option<bool>),
+    Int(Option<i64>),
+    UInt(Option<u64>),
+    Float(Option<f64>),
+    Text(Option<String>),
+    Bytes(Option<Vec<u8>>),
+}
+
+#[cfg(feature = "mysql")]
+async fn write_mysql(
+    pool: &sqlx::Pool<sqlx::MySql>,
+    target: &WriteTarget,
+    schema: &SchemaRef,
+    stream: SendableRecordBatchStream,
+) -> Result<(), ResponseError> {
+    let engine_type = DatabaseEngineType::MySQL;
+    let dialect = engine_type.unparser_dialect();
+    let table_name = table_provider::quote_table_name(dialect.as_ref(), &target.table_name);
+
+    let (table_schema, table) = split_table_name(&target.table_name);
+    let exists = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM information_schema.tables \
+        WHERE table_schema = COALESCE(?, DATABASE()) AND table_name = ?",
+    )
+    .bind(table_schema)
+    .bind(table)
+    .fetch_one(pool)
+    .await?
+        > 0;
+
+    if !creates_table(target, exists)? {
+        return insert_mysql(pool, target, &table_name, stream).await;
+    }
+
+    // DDL statements of MySQL implicitly commit the transaction, so rows are loaded into the
+    // staging table and swapped with the target table by atomic `RENAME TABLE`
+    let staging_name = format!("{}__datafusion_server_staging", target.table_name);
+    let staging_table = table_provider::quote_table_name(dialect.as_ref(), &staging_name);
+
+    sqlx::query(&format!("DROP TABLE IF EXISTS {staging_table}"))
+        .execute(pool)
+        .await?;
+
+    let statement = create_table_statement(&engine_type, target, schema, &staging_table)?;
+    log::debug!("Preparing table: {statement}");
+    sqlx::query(&statement).execute(pool).await?;
+
+    if let Err(e) = insert_mysql(pool, target, &staging_table, stream).await {
+        if let Err(e) = sqlx::query(&format!("DROP TABLE IF EXISTS {staging_table}"))
+            .execute(pool)
+            .await
+        {
+            log::warn!("Failed to drop staging table '{staging_name}': {e}");
+        }
+        return Err(e);
+    }
+
+    if exists {
+        let previous_table = table_provider::quote_table_name(
+            dialect.as_ref(),
+            &format!("{}__datafusion_server_previous", target.table_name),
+        );
+
+        sqlx::query(&format!("DROP TABLE IF EXISTS {previous_table}"))
+            .execute(pool)
+            .await?;
+        sqlx::query(&format!(
+            "RENAME TABLE {table_name} TO {previous_table}, {staging_table} TO {table_name}"
+        ))
+        .execute(pool)
+        .await?;
+        sqlx::query(&format!("DROP TABLE {previous_table}"))
+            .execute(pool)
+            .await?;
+    } else {
+        sqlx::query(&format!("RENAME TABLE {staging_table} TO {table_name}"))
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Inserts all rows in a transaction by multi-row `INSERT` statements.
+#[cfg(feature = "mysql")]
+async fn insert_mysql(
+    pool: &sqlx::Pool<sqlx::MySql>,
+    target: &WriteTarget,
+    table_name: &str,
+    mut stream: SendableRecordBatchStream,
+) -> Result<(), ResponseError> {
+    // number of placeholders in a statement is limited to 65,535
+    const MAX_PLACEHOLDERS: usize = 65_535;
+    const MAX_ROWS: usize = 1_000;
+
+    let engine_type = DatabaseEngineType::MySQL;
+    let dialect = engine_type.unparser_dialect();
+    let columns = quote_columns(&engine_type, &target.columns);
+    let chunk_rows = (MAX_PLACEHOLDERS / target.columns.len().max(1)).clamp(1, MAX_ROWS);
+
+    let on_duplicate = if target.mode == WriteMode::Upsert {
+        let updates = update_columns(target);
+        let updates = if updates.is_empty() {
+            vec![&target.primary_keys[0]]
+        } else {
+            updates
+        };
+
+        format!(
+            " ON DUPLICATE KEY UPDATE {}",
+            updates
+                .iter()
+                .map(|column| {
+                    let column = table_provider::quote_identifier(dialect.as_ref(), column);
+                    format!("{column} = VALUES({column})")
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    } else {
+        String::new()
+    };
+
+    let mut tx = pool.begin().await?;
+    let mut rows = 0;
+
+    while let Some(batch) = stream.next().await {
+        // formatters of the batch are not `Send`, values are owned before awaiting
+        let mut values = to_bind_rows(&batch?)?.into_iter();
+
+        loop {
+            let chunk = values.by_ref().take(chunk_rows).collect::<Vec<_>>();
+            if chunk.is_empty() {
+                break;
+            }
+            rows += chunk.len();
+
+            let mut query_builder: sqlx::QueryBuilder<sqlx::MySql> =
+                sqlx::QueryBuilder::new(format!("INSERT INTO {table_name} ({columns}) "));
+
+            query_builder.push_values(chunk, |mut builder, row| {
+                for value in row {
+                    match value {
+                        BindValue::Bool(v) => builder.push_bind(v),
+                        BindValue::Int(v) => builder.push_bind(v),
+                        BindValue::UInt(v) => builder.push_bind(v),
+                        BindValue::Float(v) => builder.push_bind(v),
+                        BindValue::Text(v) => builder.push_bind(v),
+                        BindValue::Bytes(v) => builder.push_bind(v),
+                    };
+                }
+            });
+
+            query_builder.push(&on_duplicate);
+            query_builder.build().execute(&mut *tx).await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    log::debug!("Inserted {rows} rows to '{}'", target.table_name);
+
+    Ok(())
+}
+
+#[cfg(feature = "mysql")]
+fn to_bind_rows(batch: &RecordBatch) -> Result<Vec<Vec<BindValue>>, ResponseError> {
+    // `DATETIME` literal without time zone
+    let format_options = FormatOptions::default()
+        .with_timestamp_format(Some("%Y-%m-%d %H:%M:%S%.f"))
+        .with_timestamp_tz_format(Some("%Y-%m-%d %H:%M:%S%.f"));
+
+    let formatters = batch
+        .columns()
+        .iter()
+        .map(|array| ArrayFormatter::try_new(array.as_ref(), &format_options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    (0..batch.num_rows())
+        .map(|index| {
+            batch
+                .columns()
+                .iter()
+                .zip(&formatters)
+                .map(|(array, formatter)| to_bind_value(array.as_ref(), index, formatter))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(feature = "mysql")]
+fn to_bind_value(
+    array: &dyn Array,
+    index: usize,
+    formatter: &ArrayFormatter,
+) -> Result<BindValue, ResponseError> {
+    Ok(match ScalarValue::try_from_array(array, index)? {
+        ScalarValue::Boolean(v) => BindValue::Bool(v),
+        ScalarValue::Int8(v) => BindValue::Int(v.map(i64::from)),
+        ScalarValue::Int16(v) => BindValue::Int(v.map(i64::from)),
+        ScalarValue::Int32(v) => BindValue::Int(v.map(i64::from)),
+        ScalarValue::Int64(v) => BindValue::Int(v),
+        ScalarValue::UInt8(v) => BindValue::UInt(v.map(u64::from)),
+        ScalarValue::UInt16(v) => BindValue::UInt(v.map(u64::from)),
+        ScalarValue::UInt32(v) => BindValue::UInt(v.map(u64::from)),
+        ScalarValue::UInt64(v) => BindValue::UInt(v),
+        ScalarValue::Float32(v) => BindValue::Float(v.map(f64::from)),
+        ScalarValue::Float64(v) => BindValue::Float(v),
+        ScalarValue::Utf8(v) | ScalarValue::LargeUtf8(v) | ScalarValue::Utf8View(v) => {
+            BindValue::Text(v)
+        }
+        ScalarValue::Binary(v) | ScalarValue::LargeBinary(v) | ScalarValue::BinaryView(v) => {
+            BindValue::Bytes(v)
+        }
+        // decimals, dates and times are converted by MySQL from the string representation
+        value if value.is_null() => BindValue::Text(None),
+        _ => BindValue::Text(Some(formatter.value(index).to_string())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    use std::sync::Arc;
+
+    #[cfg(feature = "postgres")]
+    use datafusion::arrow::array::{BinaryArray, Int32Array, StringArray};
+    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    use datafusion::arrow::datatypes::{DataType, Field, Schema};
+
+    #[cfg(feature = "postgres")]
+    use crate::data_source::database::table_writer::{to_postgres_csv, RecordBatch};
+    use crate::data_source::database::{
+        driver::{WriteMode, WriteTarget},
+        table_writer::{creates_table, split_table_name},
+    };
+    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    use crate::data_source::database::{
+        engine_type::DatabaseEngineType, table_writer::create_table_statement,
+    };
+
+    fn target(mode: WriteMode, primary_keys: &[&str]) -> WriteTarget {
+        WriteTarget {
+            table_name: "public.sales".to_string(),
+            columns: vec!["id".to_string(), "name".to_string()],
+            primary_keys: primary_keys.iter().map(ToString::to_string).collect(),
+            mode,
+        }
+    }
+
+    #[test]
+    fn split_schema_qualified_table_name() {
+        assert_eq!(split_table_name("public.sales"), (Some("public"), "sales"));
+        assert_eq!(split_table_name("sales"), (None, "sales"));
+    }
+
+    #[test]
+    fn creates_table_by_save_mode() {
+        assert!(creates_table(&target(WriteMode::ErrorIfExists, &[]), true).is_err());
+        assert!(creates_table(&target(WriteMode::ErrorIfExists, &[]), false).unwrap());
+        assert!(creates_table(&target(WriteMode::Overwrite, &[]), true).unwrap());
+        assert!(!creates_table(&target(WriteMode::Append, &[]), true).unwrap());
+        assert!(!creates_table(&target(WriteMode::Upsert, &["id"]), true).unwrap());
+        assert!(creates_table(&target(WriteMode::Upsert, &["id"]), false).unwrap());
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn create_postgres_table_statement() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, true),
+            Field::new("na\"me", DataType::Utf8, false),
+        ]));
+
+        let statement = create_table_statement(
+            &DatabaseEngineType::Postgres,
+            &target(WriteMode::Overwrite, &["id"]),
+            &schema,
+            "\"public\".\"sales\"",
+        )
+        .unwrap();
+
+        assert_eq!(
+            statement,
+            "CREATE TABLE \"public\".\"sales\" (\"id\" integer NOT NULL, \
+            \"na\"\"me\" text NOT NULL, PRIMARY KEY (\"id\"))"
+        );
+    }
+
+    #[cfg(feature = "mysql")]
+    #[test]
+    fn create_mysql_table_statement() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+
+        let statement = create_table_statement(
+            &DatabaseEngineType::MySQL,
+            &target(WriteMode::ErrorIfExists, &["id"]),
+            &schema,
+            "`sales`",
+        )
+        .unwrap();
+
+        assert_eq!(
+            statement,
+            "CREATE TABLE `sales` (`id` VARCHAR(255) NOT NULL, `name` LONGTEXT, PRIMARY KEY (`id`))"
+        );
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn postgres_csv_distinguishes_null_and_empty_string() {
+        let batch = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![
+                Field::new("id", DataType::Int32, true),
+                Field::new("name", DataType::Utf8, true),
+                Field::new("data", DataType::Binary, true),
+            ])),
+            vec![
+                Arc::new(Int32Array::from(vec![Some(1), None])),
+                Arc::new(StringArray::from(vec![Some(""), Some("\\N \"quoted\"")])),
+                Arc::new(BinaryArray::from(vec![Some(&b"\x01\xff"[..]), None])),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(to_postgres_csv(&batch).unwrap()).unwrap(),
+            "\"1\",\"\",\"\\x01ff\"\n,\"\\N \"\"quoted\"\"\",\n"
+        );
+    }
+}
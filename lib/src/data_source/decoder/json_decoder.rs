@@ -796,7 +796,7 @@ impl Decoder {
         for row in rows {
             if let Some(value) = row.get(col_name) {
                 let vals: Vec<Option<String>> = if let Value::String(v) = value {
-                    vec![Some(v.to_string())]
+                    vec![Some(v.clone())]
                 } else if let Value::Array(n) = value {
                     n.iter()
                         .map(|v: &Value| {
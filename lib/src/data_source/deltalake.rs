@@ -2,98 +2,516 @@
 // Sasaki, Naoki <nsasaki@sal.co.jp> June 15, 2024
 //
 
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 
-use datafusion::arrow::array::RecordBatch;
+use async_trait::async_trait; // TODO: Replace in the future when the Rust compiler's async trait supports object safety.
+use chrono::{DateTime, Utc};
+use datafusion::{
+    arrow::{
+        array::{RecordBatch, RecordBatchOptions},
+        datatypes::{Schema, SchemaRef},
+    },
+    catalog::Session,
+    datasource::{TableProvider, TableType},
+    error::DataFusionError,
+    execution::{SendableRecordBatchStream, TaskContext},
+    logical_expr::{BinaryExpr, Expr, Operator, TableProviderFilterPushDown},
+    physical_plan::{
+        stream::RecordBatchStreamAdapter,
+        streaming::{PartitionStream, StreamingTableExec},
+        ExecutionPlan,
+    },
+    scalar::ScalarValue,
+};
 use delta_kernel::{
     engine::{
+        arrow_conversion::TryIntoArrow,
         arrow_data::ArrowEngineData,
         default::{executor::tokio::TokioBackgroundExecutor, DefaultEngine},
     },
-    scan::ScanBuilder,
-    snapshot::Snapshot,
-    DeltaResult,
+    expressions::{Expression, Predicate, Scalar},
+    scan::{Scan, ScanBuilder},
+    snapshot::{Snapshot, SnapshotRef},
+    DeltaResult, Engine, EngineData,
 };
-use itertools::Itertools;
-use object_store::{local::LocalFileSystem, DynObjectStore};
+use futures::StreamExt;
+use object_store::{local::LocalFileSystem, path::Path, DynObjectStore, ObjectMeta};
 use url::Url;
 
 use crate::data_source::location::{
     file,
     uri::{self, SupportedScheme},
 };
-use crate::data_source::object_store::credential_manager::ObjectStoreManager;
 use crate::request::body::DataSourceOption;
 use crate::response::http_error::ResponseError;
 use crate::settings::Settings;
 
-pub fn to_record_batch(
-    uri: &str,
-    _options: &DataSourceOption,
-) -> Result<Vec<RecordBatch>, ResponseError> {
-    log::debug!("deltalake::to_record_batch(): uri={uri:?}");
+/// Delta table snapshot scanned lazily, prunes files by partition values and statistics.
+pub struct DeltaTable {
+    engine: Arc<dyn Engine>,
+    snapshot: SnapshotRef,
+    schema: SchemaRef,
+}
 
-    let parts = uri::to_parts(uri)?;
-    let scheme = uri::scheme(&parts).unwrap_or(SupportedScheme::File);
+impl Debug for DeltaTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeltaTable")
+            .field("location", &self.snapshot.table_root().as_str())
+            .field("version", &self.snapshot.version())
+            .finish_non_exhaustive()
+    }
+}
 
-    let location = format!(
-        "{}/",
-        if scheme == SupportedScheme::File {
-            file::create_data_file_path(uri)?
-        } else {
-            uri.to_string()
+impl DeltaTable {
+    pub async fn try_new(uri: &str, options: &DataSourceOption) -> Result<Self, ResponseError> {
+        log::debug!("deltalake::DeltaTable::try_new(): uri={uri:?}");
+
+        if options.version.is_some() && options.timestamp.is_some() {
+            return Err(ResponseError::request_validation(
+                "Delta table can not specify both 'version' and 'timestamp'",
+            ));
         }
-        .trim_end_matches('/')
-    );
-    let parsed_url = Url::parse(&location)
-        .map_err(|e| ResponseError::request_validation(format!("invalid delta table url: {e}")))?;
 
-    let object_store: Arc<DynObjectStore> = if scheme == SupportedScheme::File {
-        Arc::new(LocalFileSystem::new()) as Arc<DynObjectStore>
-    } else {
-        build_store(
-            &scheme,
-            parts.authority.as_ref().map_or("", |auth| auth.as_str()),
-        )?
-    };
-
-    let engine = Arc::new(DefaultEngine::new_with_executor(
-        object_store,
-        Arc::new(TokioBackgroundExecutor::new()),
-    ));
-
-    let snapshot = Snapshot::builder_for(parsed_url)
-        .build(engine.as_ref())
-        .map_err(|e| ResponseError::request_validation(e.to_string()))?;
-    let scan = ScanBuilder::new(snapshot).build()?;
-
-    let batches: Vec<RecordBatch> = scan
-        .execute(engine.clone())?
-        .map(|scan_result| -> DeltaResult<_> {
-            let data = scan_result?;
-            let record_batch: RecordBatch = data
-                .into_any()
-                .downcast::<ArrowEngineData>()
-                .map_err(|_| delta_kernel::Error::EngineDataType("ArrowEngineData".to_string()))?
-                .into();
-            Ok(record_batch)
+        let (table_url, object_store) = Self::table_location(uri)?;
+
+        let version = match &options.timestamp {
+            Some(timestamp) => {
+                let timestamp = DateTime::parse_from_rfc3339(timestamp)
+                    .map_err(|e| {
+                        ResponseError::request_validation(format!(
+                            "Invalid timestamp '{timestamp}' of delta table: {e}"
+                        ))
+                    })?
+                    .with_timezone(&Utc);
+                Some(Self::version_at(&object_store, &table_url, timestamp).await?)
+            }
+            None => options.version,
+        };
+
+        let engine: Arc<dyn Engine> = Arc::new(DefaultEngine::new_with_executor(
+            object_store,
+            Arc::new(TokioBackgroundExecutor::new()),
+        ));
+
+        let mut builder = Snapshot::builder_for(table_url);
+        if let Some(version) = version {
+            builder = builder.at_version(version);
+        }
+
+        let snapshot: SnapshotRef = builder
+            .build(engine.as_ref())
+            .map_err(|e| ResponseError::request_validation(e.to_string()))?;
+
+        log::debug!(
+            "Opened delta table {} at version {}",
+            snapshot.table_root(),
+            snapshot.version()
+        );
+
+        let schema: Schema = snapshot.schema().as_ref().try_into_arrow()?;
+
+        Ok(Self {
+            engine,
+            snapshot,
+            schema: Arc::new(schema),
         })
-        .try_collect()?;
+    }
 
-    Ok(batches)
-}
+    fn table_location(uri: &str) -> Result<(Url, Arc<DynObjectStore>), ResponseError> {
+        let parts = uri::to_parts(uri)?;
+        let scheme = uri::scheme(&parts).unwrap_or(SupportedScheme::File);
+        let table_url = table_url(uri)?;
 
-fn build_store(
-    scheme: &SupportedScheme,
-    authority: &str,
-) -> Result<Arc<DynObjectStore>, ResponseError> {
-    let key = ObjectStoreManager::store_key(scheme, authority);
+        if scheme == SupportedScheme::File {
+            return Ok((table_url, Arc::new(LocalFileSystem::new())));
+        }
+
+        let store = build_store(&table_url)?;
+
+        Ok((table_url, store))
+    }
+
+    /// Latest version committed at or before the timestamp, searched from the latest commit.
+    async fn version_at(
+        object_store: &Arc<DynObjectStore>,
+        table_url: &Url,
+        timestamp: DateTime<Utc>,
+    ) -> Result<u64, ResponseError> {
+        let log_dir = Path::from_url_path(table_url.path())
+            .map_err(object_store::Error::from)?
+            .child("_delta_log");
+
+        let mut listing = object_store.list(Some(&log_dir));
+        let mut commits = vec![];
+
+        while let Some(meta) = listing.next().await {
+            let meta = meta?;
+            if let Some(commit_version) = meta
+                .location
+                .filename()
+                .and_then(|name| name.strip_suffix(".json"))
+                .filter(|name| name.len() == 20)
+                .and_then(|name| name.parse::<u64>().ok())
+            {
+                commits.push((commit_version, meta));
+            }
+        }
+
+        commits.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        for (commit_version, meta) in commits {
+            if Self::commit_timestamp(object_store, &meta).await? <= timestamp {
+                return Ok(commit_version);
+            }
+        }
 
-    if let Some(store) = Settings::global().object_store_manager.stores.get(&key) {
-        Ok(store.clone())
-    } else {
         Err(ResponseError::request_validation(format!(
-            "Object store '{key}' credential not configured"
+            "No version of delta table {table_url} committed at {timestamp}"
         )))
     }
+
+    /// `inCommitTimestamp` or `timestamp` of the commit info, the modification time of the commit file
+    /// only if neither exists, as copying the table changes the modification time.
+    async fn commit_timestamp(
+        object_store: &Arc<DynObjectStore>,
+        meta: &ObjectMeta,
+    ) -> Result<DateTime<Utc>, ResponseError> {
+        let data = object_store.get(&meta.location).await?.bytes().await?;
+
+        let millis = data.split(|byte| *byte == b'\n').find_map(|line| {
+            let action = serde_json::from_slice::<serde_json::Value>(line).ok()?;
+            let commit_info = action.get("commitInfo")?;
+            commit_info
+                .get("inCommitTimestamp")
+                .or_else(|| commit_info.get("timestamp"))?
+                .as_i64()
+        });
+
+        Ok(millis
+            .and_then(DateTime::from_timestamp_millis)
+            .unwrap_or(meta.last_modified))
+    }
+}
+
+#[async_trait]
+impl TableProvider for DeltaTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&Expr],
+    ) -> Result<Vec<TableProviderFilterPushDown>, DataFusionError> {
+        // used only for skipping files, filters are still applied to the scanned rows
+        Ok(filters
+            .iter()
+            .map(|filter| {
+                if to_predicate(filter).is_some() {
+                    TableProviderFilterPushDown::Inexact
+                } else {
+                    TableProviderFilterPushDown::Unsupported
+                }
+            })
+            .collect())
+    }
+
+    async fn scan(
+        &self,
+        _state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        _limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>, DataFusionError> {
+        let schema = match projection {
+            Some(projection) => Arc::new(self.schema.project(projection)?),
+            None => self.schema.clone(),
+        };
+
+        let mut builder = ScanBuilder::new(self.snapshot.clone());
+
+        if let Some(projection) = projection {
+            // reads at least one column to count rows, e.g. `SELECT COUNT(*)`
+            let fields = if projection.is_empty() {
+                &self.schema.fields()[..self.schema.fields().len().min(1)]
+            } else {
+                &schema.fields()[..]
+            };
+            let names: Vec<&str> = fields.iter().map(|field| field.name().as_str()).collect();
+
+            builder = builder.with_schema(
+                self.snapshot
+                    .schema()
+                    .project(&names)
+                    .map_err(to_datafusion_error)?,
+            );
+        }
+
+        if let Some(predicate) = filters
+            .iter()
+            .filter_map(to_predicate)
+            .reduce(Predicate::and)
+        {
+            log::debug!("Delta table scan predicate: {predicate:?}");
+            builder = builder.with_predicate(Arc::new(predicate));
+        }
+
+        let partition = DeltaScanStream {
+            engine: self.engine.clone(),
+            scan: Arc::new(builder.build().map_err(to_datafusion_error)?),
+            schema: schema.clone(),
+        };
+
+        Ok(Arc::new(StreamingTableExec::try_new(
+            schema,
+            vec![Arc::new(partition)],
+            None,
+            vec![],
+            false,
+            None,
+        )?))
+    }
+}
+
+struct DeltaScanStream {
+    engine: Arc<dyn Engine>,
+    scan: Arc<Scan>,
+    schema: SchemaRef,
+}
+
+impl Debug for DeltaScanStream {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeltaScanStream")
+            .field("schema", &self.schema)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartitionStream for DeltaScanStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let (tx, rx) = tokio::sync::mpsc::channel(2);
+        let engine = self.engine.clone();
+        let scan = self.scan.clone();
+        let schema = self.schema.clone();
+
+        // kernel reads data files by blocking iterator
+        tokio::task::spawn_blocking(move || {
+            let results = match scan.execute(engine) {
+                Ok(results) => results,
+                Err(e) => {
+                    let _ = tx.blocking_send(Err(to_datafusion_error(e)));
+                    return;
+                }
+            };
+
+            for result in results {
+                let batch = result
+                    .and_then(to_record_batch)
+                    .map_err(to_datafusion_error)
+                    .and_then(|batch| conform_batch(&schema, &batch));
+                let failed = batch.is_err();
+
+                if tx.blocking_send(batch).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Box::pin(RecordBatchStreamAdapter::new(
+            self.schema.clone(),
+            futures::stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|batch| (batch, rx))
+            }),
+        ))
+    }
+}
+
+fn to_record_batch(data: Box<dyn EngineData>) -> DeltaResult<RecordBatch> {
+    Ok(data
+        .into_any()
+        .downcast::<ArrowEngineData>()
+        .map_err(|_| delta_kernel::Error::EngineDataType("ArrowEngineData".to_string()))?
+        .into())
+}
+
+fn conform_batch(schema: &SchemaRef, batch: &RecordBatch) -> Result<RecordBatch, DataFusionError> {
+    Ok(if schema.fields().is_empty() {
+        RecordBatch::try_new_with_options(
+            schema.clone(),
+            vec![],
+            &RecordBatchOptions::new().with_row_count(Some(batch.num_rows())),
+        )?
+    } else {
+        RecordBatch::try_new(schema.clone(), batch.columns().to_vec())?
+    })
+}
+
+fn to_datafusion_error(e: delta_kernel::Error) -> DataFusionError {
+    DataFusionError::External(Box::new(e))
+}
+
+/// Converts simple comparisons of column and literal to kernel predicate for data skipping.
+fn to_predicate(expr: &Expr) -> Option<Predicate> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr { left, op, right }) => match op {
+            Operator::And => Some(Predicate::and(to_predicate(left)?, to_predicate(right)?)),
+            Operator::Or => Some(Predicate::or(to_predicate(left)?, to_predicate(right)?)),
+            _ => match (left.as_ref(), right.as_ref()) {
+                (Expr::Column(column), Expr::Literal(value, _)) => {
+                    to_comparison(&column.name, *op, value)
+                }
+                (Expr::Literal(value, _), Expr::Column(column)) => {
+                    to_comparison(&column.name, op.swap()?, value)
+                }
+                _ => None,
+            },
+        },
+        Expr::IsNull(expr) => match expr.as_ref() {
+            Expr::Column(column) => Some(Predicate::is_null(Expression::column([&column.name]))),
+            _ => None,
+        },
+        Expr::IsNotNull(expr) => match expr.as_ref() {
+            Expr::Column(column) => {
+                Some(Predicate::is_not_null(Expression::column([&column.name])))
+            }
+            _ => None,
+        },
+        Expr::Not(expr) => Some(Predicate::not(to_predicate(expr)?)),
+        _ => None,
+    }
+}
+
+fn to_comparison(name: &str, op: Operator, value: &ScalarValue) -> Option<Predicate> {
+    let column = Expression::column([name]);
+    let literal = Expression::literal(to_scalar(value)?);
+
+    Some(match op {
+        Operator::Eq => Predicate::eq(column, literal),
+        Operator::NotEq => Predicate::ne(column, literal),
+        Operator::Lt => Predicate::lt(column, literal),
+        Operator::LtEq => Predicate::le(column, literal),
+        Operator::Gt => Predicate::gt(column, literal),
+        Operator::GtEq => Predicate::ge(column, literal),
+        _ => return None,
+    })
+}
+
+fn to_scalar(value: &ScalarValue) -> Option<Scalar> {
+    Some(match value {
+        ScalarValue::Boolean(Some(v)) => Scalar::Boolean(*v),
+        ScalarValue::Int8(Some(v)) => Scalar::Byte(*v),
+        ScalarValue::Int16(Some(v)) => Scalar::Short(*v),
+        ScalarValue::Int32(Some(v)) => Scalar::Integer(*v),
+        ScalarValue::Int64(Some(v)) => Scalar::Long(*v),
+        ScalarValue::Float32(Some(v)) => Scalar::Float(*v),
+        ScalarValue::Float64(Some(v)) => Scalar::Double(*v),
+        ScalarValue::Utf8(Some(v))
+        | ScalarValue::LargeUtf8(Some(v))
+        | ScalarValue::Utf8View(Some(v)) => Scalar::String(v.clone()),
+        ScalarValue::Date32(Some(v)) => Scalar::Date(*v),
+        ScalarValue::TimestampMicrosecond(Some(v), Some(_)) => Scalar::Timestamp(*v),
+        ScalarValue::TimestampMicrosecond(Some(v), None) => Scalar::TimestampNtz(*v),
+        _ => return None,
+    })
+}
+
+/// Root URL of delta table with trailing slash, local table is placed under the data directory.
+pub fn table_url(uri: &str) -> Result<Url, ResponseError> {
+    let parts = uri::to_parts(uri)?;
+
+    if uri::scheme(&parts).unwrap_or(SupportedScheme::File) == SupportedScheme::File {
+        let path = std::path::absolute(file::create_data_file_path(uri)?)?;
+        return Url::from_directory_path(&path).map_err(|()| {
+            ResponseError::request_validation(format!(
+                "invalid delta table path: {}",
+                path.display()
+            ))
+        });
+    }
+
+    Url::parse(&format!("{}/", uri.trim_end_matches('/')))
+        .map_err(|e| ResponseError::request_validation(format!("invalid delta table url: {e}")))
+}
+
+fn build_store(url: &Url) -> Result<Arc<DynObjectStore>, ResponseError> {
+    Settings::global()
+        .object_store_manager
+        .store(url)
+        .ok_or_else(|| {
+            ResponseError::request_validation(format!(
+                "Object store '{url}' credential not configured"
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::{DateTime, Utc};
+    use object_store::{memory::InMemory, path::Path, DynObjectStore, PutPayload};
+    use url::Url;
+
+    use crate::data_source::deltalake::DeltaTable;
+
+    #[tokio::test]
+    async fn version_at_commit_timestamp() {
+        let store: Arc<DynObjectStore> = Arc::new(InMemory::new());
+        let table_url = Url::parse("memory:///table/").unwrap();
+
+        for (version, commit_info) in [
+            (0, r#"{"commitInfo":{"timestamp":1000}}"#),
+            (
+                1,
+                r#"{"commitInfo":{"timestamp":9000,"inCommitTimestamp":2000}}"#,
+            ),
+            (2, r#"{"commitInfo":{"timestamp":3000}}"#),
+        ] {
+            store
+                .put(
+                    &Path::from(format!("table/_delta_log/{version:020}.json")),
+                    PutPayload::from(format!("{commit_info}\n{{\"add\":{{}}}}\n")),
+                )
+                .await
+                .unwrap();
+        }
+
+        let at = |millis| DateTime::<Utc>::from_timestamp_millis(millis).unwrap();
+
+        assert_eq!(
+            DeltaTable::version_at(&store, &table_url, at(1500))
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            DeltaTable::version_at(&store, &table_url, at(2500))
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            DeltaTable::version_at(&store, &table_url, at(3000))
+                .await
+                .unwrap(),
+            2
+        );
+        assert!(DeltaTable::version_at(&store, &table_url, at(500))
+            .await
+            .is_err());
+    }
 }
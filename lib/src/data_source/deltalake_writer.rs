@@ -0,0 +1,1203 @@
+// data_source/deltalake_writer.rs: Append and overwrite commits to delta tables
+
+use std::collections::{hash_map::Entry, HashMap};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use datafusion::{
+    arrow::{
+        array::{
+            new_null_array, Array, ArrayRef, AsArray, Int64Array, MapBuilder, MapFieldNames,
+            RecordBatch, StringArray, StringBuilder, StructArray,
+        },
+        compute::{cast, partition},
+        datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit, TimestampMicrosecondType},
+        json::{writer::JsonArray, Writer},
+        util::display::array_value_to_string,
+    },
+    dataframe::DataFrame,
+    error::DataFusionError,
+    execution::{context::SessionContext, object_store::ObjectStoreUrl},
+    functions_aggregate::min_max::{MaxAccumulator, MinAccumulator},
+    logical_expr::{ident, Accumulator},
+    parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties},
+    scalar::ScalarValue,
+};
+use delta_kernel::{
+    actions::get_commit_schema,
+    committer::{CommitMetadata, CommitResponse, Committer, FileSystemCommitter},
+    engine::{
+        arrow_conversion::{TryFromArrow, TryIntoArrow},
+        arrow_data::ArrowEngineData,
+        default::{executor::tokio::TokioBackgroundExecutor, DefaultEngine},
+    },
+    schema::StructType,
+    snapshot::{Snapshot, SnapshotRef},
+    table_features::{ColumnMappingMode, TableFeature},
+    transaction::CommitResult,
+    DeltaResult, Engine, FilteredEngineData, Version,
+};
+use futures::StreamExt;
+use itertools::Itertools;
+use object_store::{path::Path, DynObjectStore, PutPayload};
+use serde_json::{json, Value};
+use url::Url;
+
+use crate::data_source::deltalake;
+use crate::request::body::{DataSource, DataSourceOption, SaveMode};
+use crate::response::http_error::ResponseError;
+
+// rolls over to the next data file after this size
+const TARGET_FILE_SIZE: usize = 128 * 1024 * 1024;
+
+// partition directory name of null value, compatible with Hive and Spark
+const NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+// longer strings are not recorded in the statistics, prefixes need the tie-breaker for max values
+const MAX_STATS_STRING_LENGTH: usize = 32;
+
+/// Protocol and metadata of the commit, which the transaction of delta kernel does not write yet.
+struct MetadataChange {
+    protocol: Option<Value>,
+    metadata: Value,
+}
+
+/// Data file being written, with the statistics of the columns.
+struct DataFile {
+    writer: ArrowWriter<Vec<u8>>,
+    rows: usize,
+    stats: Vec<ColumnStats>,
+}
+
+struct ColumnStats {
+    field: Field,
+    min: Option<MinAccumulator>,
+    max: Option<MaxAccumulator>,
+    null_count: usize,
+}
+
+impl ColumnStats {
+    fn try_new(field: &Field) -> Result<Self, DataFusionError> {
+        let (min, max) = if has_min_max(field.data_type()) {
+            (
+                Some(MinAccumulator::try_new(field.data_type())?),
+                Some(MaxAccumulator::try_new(field.data_type())?),
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(Self {
+            field: field.clone(),
+            min,
+            max,
+            null_count: 0,
+        })
+    }
+
+    fn update(&mut self, array: &ArrayRef) -> Result<(), DataFusionError> {
+        if let Some(min) = self.min.as_mut() {
+            min.update_batch(std::slice::from_ref(array))?;
+        }
+        if let Some(max) = self.max.as_mut() {
+            max.update_batch(std::slice::from_ref(array))?;
+        }
+        self.null_count += array.null_count();
+
+        Ok(())
+    }
+}
+
+/// Written data file to be added by the commit.
+struct AddFile {
+    path: Path,
+    action: RecordBatch,
+}
+
+/// Writes the data files, then commits them by the transaction of delta kernel.
+/// Creating the table and changing the schema are committed with the data in a single version,
+/// the written files are removed if the commit is not completed.
+pub async fn write(
+    ctx: &SessionContext,
+    df: DataFrame,
+    data_source: &DataSource,
+    options: &DataSourceOption,
+) -> Result<(), ResponseError> {
+    log::debug!("deltalake_writer::write(): {}", data_source.location);
+
+    let table_url = deltalake::table_url(&data_source.location)?;
+    let store = ctx.runtime_env().object_store(ObjectStoreUrl::parse(
+        &table_url[..url::Position::BeforePath],
+    )?)?;
+    let table_path = Path::from_url_path(table_url.path()).map_err(object_store::Error::from)?;
+    let engine: Arc<dyn Engine> = Arc::new(DefaultEngine::new_with_executor(
+        store.clone(),
+        Arc::new(TokioBackgroundExecutor::new()),
+    ));
+
+    let mode = options.save_mode(SaveMode::ErrorIfExists);
+
+    if mode == SaveMode::Upsert {
+        return Err(ResponseError::unsupported_type(
+            "Save mode 'upsert' is not supported for delta table",
+        ));
+    }
+
+    let snapshot = load_snapshot(&engine, &table_url).await?;
+    let input_schema = Schema::new(
+        df.schema()
+            .fields()
+            .iter()
+            .map(|field| {
+                field
+                    .as_ref()
+                    .clone()
+                    .with_data_type(delta_type(field.data_type()))
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    let (schema, partition_columns, metadata) = if let Some(snapshot) = &snapshot {
+        check_writable(snapshot, &mode, data_source)?;
+        let resolved = resolve_schema(snapshot, &input_schema, &mode, options)?;
+        if resolved.2.is_some() {
+            check_schema_change(snapshot, &resolved.0, data_source)?;
+        }
+        resolved
+    } else {
+        let partition_columns = options.partition_by.clone().unwrap_or_default();
+        (input_schema, partition_columns, None)
+    };
+
+    // normalizes to data types of delta table
+    let delta_schema = StructType::try_from_arrow(&schema)?;
+    let schema: SchemaRef = Arc::new((&delta_schema).try_into_arrow()?);
+
+    for column in &partition_columns {
+        if schema.field_with_name(column).is_err() {
+            return Err(ResponseError::request_validation(format!(
+                "Partition column '{column}' not found in '{}'",
+                data_source.name
+            )));
+        }
+    }
+
+    if partition_columns.len() >= schema.fields().len() {
+        return Err(ResponseError::request_validation(
+            "Delta table requires at least one non-partition column",
+        ));
+    }
+
+    let metadata_change = metadata_change(
+        snapshot.is_none(),
+        metadata,
+        &delta_schema,
+        &partition_columns,
+    )?;
+
+    let files = write_data_files(&store, &table_path, df, &schema, &partition_columns).await?;
+
+    let result = commit(
+        &engine,
+        &table_url,
+        snapshot,
+        metadata_change,
+        mode == SaveMode::Overwrite,
+        &files,
+    )
+    .await;
+
+    match result {
+        Ok(version) => {
+            log::info!(
+                "Committed version {version} of delta table {table_url}, {} files",
+                files.len()
+            );
+            Ok(())
+        }
+        Err(e) => {
+            for file in &files {
+                if let Err(e) = store.delete(&file.path).await {
+                    log::error!("Can not remove uncommitted data file '{}': {e}", file.path);
+                }
+            }
+            Err(e)
+        }
+    }
+}
+
+fn metadata_change(
+    creates_table: bool,
+    metadata: Option<Value>,
+    delta_schema: &StructType,
+    partition_columns: &[String],
+) -> Result<Option<MetadataChange>, ResponseError> {
+    let schema_string = serde_json::to_string(delta_schema)?;
+
+    Ok(match metadata {
+        _ if creates_table => Some(MetadataChange {
+            protocol: Some(protocol(&delta_schema.try_into_arrow()?)),
+            metadata: json!({
+                "id": uuid::Uuid::new_v4().to_string(),
+                "format": { "provider": "parquet", "options": {} },
+                "schemaString": schema_string,
+                "partitionColumns": partition_columns,
+                "configuration": {},
+                "createdTime": Utc::now().timestamp_millis(),
+            }),
+        }),
+        Some(mut metadata) => {
+            metadata["schemaString"] = Value::String(schema_string);
+            metadata["partitionColumns"] = json!(partition_columns);
+            Some(MetadataChange {
+                protocol: None,
+                metadata,
+            })
+        }
+        None => None,
+    })
+}
+
+async fn commit(
+    engine: &Arc<dyn Engine>,
+    table_url: &Url,
+    snapshot: Option<SnapshotRef>,
+    metadata_change: Option<MetadataChange>,
+    overwrite: bool,
+    files: &[AddFile],
+) -> Result<Version, ResponseError> {
+    let mut metadata_actions = vec![];
+
+    if let Some(change) = metadata_change {
+        if let Some(protocol) = change.protocol {
+            metadata_actions.push(json!({ "protocol": protocol }));
+        }
+        metadata_actions.push(json!({ "metaData": change.metadata }));
+    }
+
+    match snapshot {
+        Some(snapshot) => {
+            commit_files(
+                engine,
+                snapshot,
+                overwrite,
+                files.iter().map(|file| file.action.clone()).collect(),
+                metadata_actions,
+            )
+            .await
+        }
+        None => create_table(engine, table_url, metadata_actions, files).await,
+    }
+}
+
+/// Adds the data files with the metadata actions, and removes all existing files of the snapshot if `remove_existing`.
+async fn commit_files(
+    engine: &Arc<dyn Engine>,
+    snapshot: SnapshotRef,
+    remove_existing: bool,
+    adds: Vec<RecordBatch>,
+    metadata_actions: Vec<Value>,
+) -> Result<Version, ResponseError> {
+    let engine = engine.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let operation = if metadata_actions.is_empty() {
+            "WRITE"
+        } else {
+            "CHANGE SCHEMA"
+        };
+
+        let mut transaction = snapshot
+            .clone()
+            .transaction(Box::new(MetadataCommitter {
+                actions: metadata_actions,
+            }))?
+            .with_operation(operation.to_string())
+            .with_engine_info(engine_info());
+
+        if remove_existing {
+            let scan = snapshot.scan_builder().build()?;
+            for scan_metadata in scan.scan_metadata(engine.as_ref())? {
+                transaction.remove_files(scan_metadata?.scan_files);
+            }
+        }
+
+        for add in adds {
+            transaction.add_files(Box::new(ArrowEngineData::new(add)));
+        }
+
+        match transaction.commit(engine.as_ref())? {
+            CommitResult::CommittedTransaction(committed) => Ok(committed.commit_version()),
+            CommitResult::ConflictedTransaction(conflicted) => {
+                Err(ResponseError::already_existing(format!(
+                    "Conflicted with concurrent commit of version {} to delta table",
+                    conflicted.conflict_version()
+                )))
+            }
+            CommitResult::RetryableTransaction(retryable) => Err(retryable.error.into()),
+        }
+    })
+    .await
+    .map_err(|e| ResponseError::internal_server_error(e.to_string()))?
+}
+
+/// Commits the first version with the protocol, the metadata and the data files,
+/// as the transaction of delta kernel requires the existing table.
+async fn create_table(
+    engine: &Arc<dyn Engine>,
+    table_url: &Url,
+    metadata_actions: Vec<Value>,
+    files: &[AddFile],
+) -> Result<Version, ResponseError> {
+    let mut actions = vec![json!({
+        "commitInfo": {
+            "timestamp": Utc::now().timestamp_millis(),
+            "operation": "CREATE TABLE",
+            "engineInfo": engine_info(),
+        }
+    })];
+    actions.extend(metadata_actions);
+    for file in files {
+        actions.push(json!({ "add": add_action(&file.action)? }));
+    }
+
+    let engine = engine.clone();
+    let commit_url = table_url.join(&format!("_delta_log/{:020}.json", 0))?;
+
+    tokio::task::spawn_blocking(move || {
+        let actions = parse_actions(engine.as_ref(), &actions)?;
+
+        match engine.json_handler().write_json_file(
+            &commit_url,
+            Box::new(std::iter::once(Ok(actions))),
+            false,
+        ) {
+            Ok(()) => Ok(0),
+            Err(delta_kernel::Error::FileAlreadyExists(_)) => Err(ResponseError::already_existing(
+                "Conflicted with concurrent commit of version 0 to delta table",
+            )),
+            Err(e) => Err(e.into()),
+        }
+    })
+    .await
+    .map_err(|e| ResponseError::internal_server_error(e.to_string()))?
+}
+
+/// Add action of the data file, the statistics are serialized as JSON string.
+fn add_action(action: &RecordBatch) -> Result<Value, ResponseError> {
+    let mut writer = Writer::<_, JsonArray>::new(Vec::new());
+    writer.write(action)?;
+    writer.finish()?;
+
+    let mut add = serde_json::from_slice::<Vec<Value>>(&writer.into_inner())?
+        .pop()
+        .unwrap_or_default();
+    add["stats"] = Value::String(add["stats"].to_string());
+    add["dataChange"] = Value::Bool(true);
+
+    Ok(add)
+}
+
+/// Actions in the JSON format of the delta log, as the data of delta kernel.
+fn parse_actions(engine: &dyn Engine, actions: &[Value]) -> DeltaResult<FilteredEngineData> {
+    let json = StringArray::from(actions.iter().map(Value::to_string).collect::<Vec<_>>());
+    let batch = RecordBatch::try_from_iter(vec![("json", Arc::new(json) as ArrayRef)])?;
+    let data = engine.json_handler().parse_json(
+        Box::new(ArrowEngineData::new(batch)),
+        get_commit_schema().clone(),
+    )?;
+
+    Ok(FilteredEngineData::with_all_rows_selected(data))
+}
+
+/// Protocol and metadata are committed with the actions of the transaction, which delta kernel does not write yet.
+struct MetadataCommitter {
+    actions: Vec<Value>,
+}
+
+impl Committer for MetadataCommitter {
+    fn commit(
+        &self,
+        engine: &dyn Engine,
+        actions: Box<dyn Iterator<Item = DeltaResult<FilteredEngineData>> + Send + '_>,
+        commit_metadata: CommitMetadata,
+    ) -> DeltaResult<CommitResponse> {
+        let actions: Box<dyn Iterator<Item = _> + Send> = if self.actions.is_empty() {
+            actions
+        } else {
+            Box::new(actions.chain(std::iter::once(parse_actions(engine, &self.actions))))
+        };
+
+        FileSystemCommitter::new().commit(engine, actions, commit_metadata)
+    }
+}
+
+/// Latest snapshot of the table, `None` if the table not exists.
+async fn load_snapshot(
+    engine: &Arc<dyn Engine>,
+    table_url: &Url,
+) -> Result<Option<SnapshotRef>, ResponseError> {
+    let engine = engine.clone();
+    let table_url = table_url.clone();
+
+    tokio::task::spawn_blocking(move || {
+        match Snapshot::builder_for(table_url).build(engine.as_ref()) {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(delta_kernel::Error::FileNotFound(_) | delta_kernel::Error::MissingVersion) => {
+                Ok(None)
+            }
+            Err(e) if e.to_string().contains("No files in log segment") => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    })
+    .await
+    .map_err(|e| ResponseError::internal_server_error(e.to_string()))?
+}
+
+fn check_writable(
+    snapshot: &SnapshotRef,
+    mode: &SaveMode,
+    data_source: &DataSource,
+) -> Result<(), ResponseError> {
+    if *mode == SaveMode::ErrorIfExists {
+        return Err(ResponseError::already_existing(format!(
+            "Already exists delta table '{}'",
+            data_source.location
+        )));
+    }
+
+    // protocol, writer features and invariants are checked by delta kernel
+    snapshot
+        .clone()
+        .transaction(Box::new(FileSystemCommitter::new()))
+        .map_err(|e| {
+            ResponseError::unsupported_type(format!(
+                "Can not write to delta table '{}': {e}",
+                data_source.location
+            ))
+        })?;
+
+    let properties = snapshot.table_properties();
+
+    if properties
+        .column_mapping_mode
+        .is_some_and(|mode| mode != ColumnMappingMode::None)
+    {
+        return Err(ResponseError::unsupported_type(format!(
+            "Not supported delta table with column mapping '{}'",
+            data_source.location
+        )));
+    }
+
+    if *mode == SaveMode::Overwrite && properties.append_only == Some(true) {
+        return Err(ResponseError::request_validation(format!(
+            "Can not overwrite append-only delta table '{}'",
+            data_source.location
+        )));
+    }
+
+    Ok(())
+}
+
+// protocol is not upgraded by the schema change
+fn check_schema_change(
+    snapshot: &SnapshotRef,
+    schema: &Schema,
+    data_source: &DataSource,
+) -> Result<(), ResponseError> {
+    if schema
+        .fields()
+        .iter()
+        .any(|field| has_timestamp_ntz(field.data_type()))
+        && !snapshot
+            .table_configuration()
+            .is_feature_supported(&TableFeature::TimestampWithoutTimezone)
+    {
+        return Err(ResponseError::request_validation(format!(
+            "Delta table '{}' does not support timestamp without time zone",
+            data_source.location
+        )));
+    }
+
+    Ok(())
+}
+
+/// Decides schema and partition columns of the commit, metadata is returned if it changes.
+fn resolve_schema(
+    snapshot: &SnapshotRef,
+    input_schema: &Schema,
+    mode: &SaveMode,
+    options: &DataSourceOption,
+) -> Result<(Schema, Vec<String>, Option<Value>), ResponseError> {
+    let table_schema: Schema = snapshot.schema().as_ref().try_into_arrow()?;
+    let table_metadata = snapshot.table_configuration().metadata();
+    let table_partition_columns = table_metadata.partition_columns().clone();
+
+    if *mode == SaveMode::Overwrite && options.overwrite_schema.unwrap_or(false) {
+        let partition_columns = options
+            .partition_by
+            .clone()
+            .unwrap_or(table_partition_columns);
+        return Ok((
+            input_schema.clone(),
+            partition_columns,
+            Some(serde_json::to_value(table_metadata)?),
+        ));
+    }
+
+    if let Some(partition_by) = &options.partition_by {
+        if *partition_by != table_partition_columns {
+            return Err(ResponseError::request_validation(format!(
+                "Partition columns {partition_by:?} differs from delta table {table_partition_columns:?}, use 'overwriteSchema'"
+            )));
+        }
+    }
+
+    let new_fields: Vec<Field> = input_schema
+        .fields()
+        .iter()
+        .filter(|field| table_schema.field_with_name(field.name()).is_err())
+        .map(|field| field.as_ref().clone().with_nullable(true))
+        .collect();
+
+    if new_fields.is_empty() {
+        return Ok((table_schema, table_partition_columns, None));
+    }
+
+    if !options.merge_schema.unwrap_or(false) {
+        return Err(ResponseError::request_validation(format!(
+            "Columns {:?} not found in delta table, use 'mergeSchema'",
+            new_fields.iter().map(Field::name).collect::<Vec<_>>()
+        )));
+    }
+
+    let merged = Schema::new(
+        table_schema
+            .fields()
+            .iter()
+            .map(|field| field.as_ref().clone())
+            .chain(new_fields)
+            .collect::<Vec<_>>(),
+    );
+
+    Ok((
+        merged,
+        table_partition_columns,
+        Some(serde_json::to_value(table_metadata)?),
+    ))
+}
+
+async fn write_data_files(
+    store: &Arc<DynObjectStore>,
+    table_path: &Path,
+    df: DataFrame,
+    schema: &SchemaRef,
+    partition_columns: &[String],
+) -> Result<Vec<AddFile>, ResponseError> {
+    let data_indices: Vec<usize> = schema
+        .fields()
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| !partition_columns.contains(field.name()))
+        .map(|(index, _)| index)
+        .collect();
+    let data_schema = Arc::new(schema.project(&data_indices)?);
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .set_created_by(format!("datafusion-server v{}", env!("CARGO_PKG_VERSION")))
+        .build();
+
+    // groups rows by partition values
+    let df = if partition_columns.is_empty() {
+        df
+    } else {
+        df.sort(
+            partition_columns
+                .iter()
+                .map(|column| ident(column).sort(true, true))
+                .collect(),
+        )?
+    };
+
+    let mut files: HashMap<Vec<Option<String>>, DataFile> = HashMap::new();
+    let mut added = vec![];
+    let mut stream = df.execute_stream().await?;
+
+    let result: Result<(), ResponseError> = async {
+        while let Some(batch) = stream.next().await {
+            let batch = conform_batch(schema, &batch?)?;
+
+            let ranges = if partition_columns.is_empty() {
+                std::iter::once(0..batch.num_rows()).collect()
+            } else {
+                let mut columns: Vec<ArrayRef> = vec![];
+                for column in partition_columns {
+                    columns.push(batch.column(schema.index_of(column)?).clone());
+                }
+                partition(&columns)?.ranges()
+            };
+
+            for range in ranges {
+                let slice = batch.slice(range.start, range.len());
+
+                let mut values = vec![];
+                for column in partition_columns {
+                    values.push(partition_value(slice.column(schema.index_of(column)?))?);
+                }
+
+                let file = match files.entry(values.clone()) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(DataFile {
+                        writer: ArrowWriter::try_new(
+                            Vec::new(),
+                            data_schema.clone(),
+                            Some(properties.clone()),
+                        )
+                        .map_err(DataFusionError::from)?,
+                        rows: 0,
+                        stats: data_schema
+                            .fields()
+                            .iter()
+                            .map(|field| ColumnStats::try_new(field))
+                            .collect::<Result<_, _>>()?,
+                    }),
+                };
+
+                let data = slice.project(&data_indices)?;
+                for (stats, array) in file.stats.iter_mut().zip(data.columns()) {
+                    stats.update(array)?;
+                }
+                file.writer.write(&data).map_err(DataFusionError::from)?;
+                file.rows += data.num_rows();
+
+                if file.writer.bytes_written() + file.writer.in_progress_size() >= TARGET_FILE_SIZE
+                {
+                    if let Some(file) = files.remove(&values) {
+                        added.push(
+                            put_data_file(store, table_path, partition_columns, &values, file)
+                                .await?,
+                        );
+                    }
+                }
+            }
+        }
+
+        for (values, file) in files.drain() {
+            added.push(put_data_file(store, table_path, partition_columns, &values, file).await?);
+        }
+
+        Ok(())
+    }
+    .await;
+
+    // data files already put are not referenced by any commit
+    if let Err(e) = result {
+        for file in &added {
+            if let Err(e) = store.delete(&file.path).await {
+                log::error!("Can not remove uncommitted data file '{}': {e}", file.path);
+            }
+        }
+        return Err(e);
+    }
+
+    Ok(added)
+}
+
+fn conform_batch(schema: &SchemaRef, batch: &RecordBatch) -> Result<RecordBatch, ResponseError> {
+    let mut columns = vec![];
+
+    for field in schema.fields() {
+        columns.push(match batch.column_by_name(field.name()) {
+            Some(array) => cast(array, field.data_type())?,
+            None => new_null_array(field.data_type(), batch.num_rows()),
+        });
+    }
+
+    Ok(RecordBatch::try_new(schema.clone(), columns)?)
+}
+
+async fn put_data_file(
+    store: &Arc<DynObjectStore>,
+    table_path: &Path,
+    partition_columns: &[String],
+    values: &[Option<String>],
+    mut file: DataFile,
+) -> Result<AddFile, ResponseError> {
+    let mut relative_path = partition_columns
+        .iter()
+        .zip(values)
+        .map(|(column, value)| {
+            format!(
+                "{}={}",
+                escape_partition(column),
+                value
+                    .as_deref()
+                    .map_or(NULL_PARTITION.to_string(), escape_partition)
+            )
+        })
+        .join("/");
+
+    if !relative_path.is_empty() {
+        relative_path.push('/');
+    }
+    write!(
+        &mut relative_path,
+        "part-{}.snappy.parquet",
+        uuid::Uuid::new_v4()
+    )
+    .map_err(|e| ResponseError::internal_server_error(e.to_string()))?;
+
+    let stats = file_stats(file.rows, &mut file.stats)?;
+    let data = file.writer.into_inner().map_err(DataFusionError::from)?;
+    let size = data.len();
+
+    let path =
+        Path::parse(format!("{table_path}/{relative_path}")).map_err(object_store::Error::from)?;
+    store.put(&path, PutPayload::from(data)).await?;
+
+    let mut partition_values = MapBuilder::new(
+        Some(MapFieldNames {
+            entry: "key_value".to_string(),
+            key: "key".to_string(),
+            value: "value".to_string(),
+        }),
+        StringBuilder::new(),
+        StringBuilder::new(),
+    );
+    for (column, value) in partition_columns.iter().zip(values) {
+        partition_values.keys().append_value(column);
+        partition_values.values().append_option(value.as_deref());
+    }
+    partition_values.append(true)?;
+
+    let action = RecordBatch::try_from_iter(vec![
+        // relative URI, escaped characters of partition values are to be kept as is
+        (
+            "path",
+            Arc::new(StringArray::from(vec![relative_path.replace('%', "%25")])) as ArrayRef,
+        ),
+        ("partitionValues", Arc::new(partition_values.finish())),
+        (
+            "size",
+            Arc::new(Int64Array::from(vec![
+                i64::try_from(size).unwrap_or(i64::MAX)
+            ])),
+        ),
+        (
+            "modificationTime",
+            Arc::new(Int64Array::from(vec![Utc::now().timestamp_millis()])),
+        ),
+        ("stats", Arc::new(stats)),
+    ])?;
+
+    Ok(AddFile { path, action })
+}
+
+// `numRecords`, `minValues`, `maxValues` and `nullCount` of the data file
+fn file_stats(rows: usize, columns: &mut [ColumnStats]) -> Result<StructArray, ResponseError> {
+    let mut min_values: Vec<(Arc<Field>, ArrayRef)> = vec![];
+    let mut max_values: Vec<(Arc<Field>, ArrayRef)> = vec![];
+    let mut null_counts: Vec<(Arc<Field>, ArrayRef)> = vec![];
+
+    for column in columns {
+        let name = column.field.name();
+
+        if let (Some(min), Some(max)) = (column.min.as_mut(), column.max.as_mut()) {
+            let (min, max) = (min.evaluate()?, max.evaluate()?);
+
+            if is_recordable(&min) && is_recordable(&max) {
+                let field = Arc::new(Field::new(name, min.data_type(), true));
+                min_values.push((field.clone(), min.to_array()?));
+                max_values.push((field, max.to_array()?));
+            }
+        }
+
+        null_counts.push((
+            Arc::new(Field::new(name, DataType::Int64, true)),
+            Arc::new(Int64Array::from(vec![
+                i64::try_from(column.null_count).unwrap_or(i64::MAX)
+            ])),
+        ));
+    }
+
+    let mut stats: Vec<(Arc<Field>, ArrayRef)> = vec![(
+        Arc::new(Field::new("numRecords", DataType::Int64, true)),
+        Arc::new(Int64Array::from(vec![
+            i64::try_from(rows).unwrap_or(i64::MAX)
+        ])),
+    )];
+
+    for (name, values) in [
+        ("minValues", min_values),
+        ("maxValues", max_values),
+        ("nullCount", null_counts),
+    ] {
+        if values.is_empty() {
+            continue;
+        }
+        let array = StructArray::from(values);
+        stats.push((
+            Arc::new(Field::new(name, array.data_type().clone(), true)),
+            Arc::new(array),
+        ));
+    }
+
+    Ok(StructArray::from(stats))
+}
+
+// timestamps without time zone require the table feature
+fn protocol(schema: &Schema) -> Value {
+    if schema
+        .fields()
+        .iter()
+        .any(|field| has_timestamp_ntz(field.data_type()))
+    {
+        json!({
+            "minReaderVersion": 3,
+            "minWriterVersion": 7,
+            "readerFeatures": ["timestampNtz"],
+            "writerFeatures": ["timestampNtz"],
+        })
+    } else {
+        json!({ "minReaderVersion": 1, "minWriterVersion": 2 })
+    }
+}
+
+fn has_timestamp_ntz(data_type: &DataType) -> bool {
+    match data_type {
+        DataType::Timestamp(_, None) => true,
+        DataType::List(field) => has_timestamp_ntz(field.data_type()),
+        DataType::Struct(fields) => fields
+            .iter()
+            .any(|field| has_timestamp_ntz(field.data_type())),
+        _ => false,
+    }
+}
+
+// arrow types without the counterpart of delta table are widened, e.g. timestamps of DataFusion in nanoseconds
+fn delta_type(data_type: &DataType) -> DataType {
+    match data_type {
+        DataType::UInt8 => DataType::Int16,
+        DataType::UInt16 => DataType::Int32,
+        DataType::UInt32 | DataType::UInt64 => DataType::Int64,
+        DataType::Float16 => DataType::Float32,
+        DataType::LargeUtf8 | DataType::Utf8View => DataType::Utf8,
+        DataType::LargeBinary | DataType::BinaryView => DataType::Binary,
+        DataType::Timestamp(_, timezone) => DataType::Timestamp(
+            TimeUnit::Microsecond,
+            timezone.as_ref().map(|_| "UTC".into()),
+        ),
+        DataType::Dictionary(_, value_type) => delta_type(value_type),
+        DataType::List(field) | DataType::LargeList(field) | DataType::ListView(field) => {
+            DataType::List(Arc::new(
+                field
+                    .as_ref()
+                    .clone()
+                    .with_data_type(delta_type(field.data_type())),
+            ))
+        }
+        DataType::Struct(fields) => DataType::Struct(
+            fields
+                .iter()
+                .map(|field| {
+                    field
+                        .as_ref()
+                        .clone()
+                        .with_data_type(delta_type(field.data_type()))
+                })
+                .collect(),
+        ),
+        data_type => data_type.clone(),
+    }
+}
+
+// min and max values are used for skipping files by the readers
+fn has_min_max(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Int8
+            | DataType::Int16
+            | DataType::Int32
+            | DataType::Int64
+            | DataType::Float32
+            | DataType::Float64
+            | DataType::Decimal128(_, _)
+            | DataType::Date32
+            | DataType::Timestamp(TimeUnit::Microsecond, _)
+            | DataType::Utf8
+            | DataType::LargeUtf8
+            | DataType::Utf8View
+    )
+}
+
+// NaN is not representable in JSON, long strings would need truncation
+fn is_recordable(value: &ScalarValue) -> bool {
+    match value {
+        ScalarValue::Float32(Some(v)) => !v.is_nan(),
+        ScalarValue::Float64(Some(v)) => !v.is_nan(),
+        ScalarValue::Utf8(Some(v))
+        | ScalarValue::LargeUtf8(Some(v))
+        | ScalarValue::Utf8View(Some(v)) => v.chars().count() <= MAX_STATS_STRING_LENGTH,
+        value => !value.is_null(),
+    }
+}
+
+// serialized partition value of the first row by the delta protocol
+fn partition_value(array: &ArrayRef) -> Result<Option<String>, ResponseError> {
+    if array.is_null(0) {
+        return Ok(None);
+    }
+
+    Ok(Some(match array.data_type() {
+        DataType::Timestamp(_, timezone) => {
+            let micros = cast(
+                &array.slice(0, 1),
+                &DataType::Timestamp(TimeUnit::Microsecond, timezone.clone()),
+            )?
+            .as_primitive::<TimestampMicrosecondType>()
+            .value(0);
+
+            let Some(timestamp) = DateTime::from_timestamp_micros(micros) else {
+                return Err(ResponseError::request_validation(format!(
+                    "Partition value out of range: {micros}"
+                )));
+            };
+
+            if timezone.is_some() {
+                // ISO 8601 adjusted to UTC
+                timestamp.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string()
+            } else {
+                timestamp.format("%Y-%m-%d %H:%M:%S%.6f").to_string()
+            }
+        }
+        _ => array_value_to_string(array, 0)?,
+    }))
+}
+
+// Hive style escaping of partition directory names
+fn escape_partition(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'.' | b'_' | b'-') {
+                (byte as char).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+fn engine_info() -> String {
+    format!("datafusion-server/{}", env!("CARGO_PKG_VERSION"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use datafusion::{
+        arrow::{
+            array::{
+                ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray,
+                TimestampMicrosecondArray,
+            },
+            datatypes::{DataType, Field, Schema},
+            json::{writer::JsonArray, Writer},
+        },
+        execution::context::SessionContext,
+    };
+    use delta_kernel::{
+        engine::{
+            arrow_conversion::TryFromArrow,
+            default::{executor::tokio::TokioBackgroundExecutor, DefaultEngine},
+        },
+        schema::StructType,
+        Engine,
+    };
+    use object_store::{local::LocalFileSystem, path::Path, DynObjectStore};
+    use url::Url;
+
+    use crate::data_source::deltalake_writer::{
+        commit, escape_partition, file_stats, load_snapshot, metadata_change, partition_value,
+        write_data_files, ColumnStats,
+    };
+
+    async fn commit_batch(
+        engine: &Arc<dyn Engine>,
+        store: &Arc<DynObjectStore>,
+        table_url: &Url,
+        batch: RecordBatch,
+        overwrite: bool,
+    ) -> u64 {
+        let snapshot = load_snapshot(engine, table_url).await.unwrap();
+        let metadata = snapshot.as_ref().map(|snapshot| {
+            serde_json::to_value(snapshot.table_configuration().metadata()).unwrap()
+        });
+        let delta_schema = StructType::try_from_arrow(batch.schema().as_ref()).unwrap();
+        let change = metadata_change(snapshot.is_none(), metadata, &delta_schema, &[]).unwrap();
+
+        let table_path = Path::from_url_path(table_url.path()).unwrap();
+        let df = SessionContext::new().read_batch(batch.clone()).unwrap();
+        let files = write_data_files(store, &table_path, df, &batch.schema(), &[])
+            .await
+            .unwrap();
+
+        commit(engine, table_url, snapshot, change, overwrite, &files)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn schema_replaced_in_single_commit() {
+        let dir = std::env::temp_dir().join(format!(
+            "datafusion-server-delta-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let table_url = Url::from_directory_path(&dir).unwrap();
+        let store: Arc<DynObjectStore> = Arc::new(LocalFileSystem::new());
+        let engine: Arc<dyn Engine> = Arc::new(DefaultEngine::new_with_executor(
+            store.clone(),
+            Arc::new(TokioBackgroundExecutor::new()),
+        ));
+
+        let created = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)])),
+            vec![Arc::new(Int64Array::from(vec![1, 2]))],
+        )
+        .unwrap();
+        assert_eq!(
+            commit_batch(&engine, &store, &table_url, created, false).await,
+            0
+        );
+
+        let replaced = RecordBatch::try_new(
+            Arc::new(Schema::new(vec![Field::new("b", DataType::Utf8, true)])),
+            vec![Arc::new(StringArray::from(vec!["x"]))],
+        )
+        .unwrap();
+        assert_eq!(
+            commit_batch(&engine, &store, &table_url, replaced, true).await,
+            1
+        );
+
+        let mut commits = std::fs::read_dir(dir.join("_delta_log"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| !name.starts_with('.') && name.contains(".json"))
+            .collect::<Vec<_>>();
+        commits.sort();
+        assert_eq!(
+            commits,
+            vec!["00000000000000000000.json", "00000000000000000001.json"]
+        );
+
+        let first = std::fs::read_to_string(dir.join("_delta_log").join(&commits[0])).unwrap();
+        for action in ["commitInfo", "protocol", "metaData", "add"] {
+            assert!(first.contains(&format!("{{\"{action}\"")), "{action}");
+        }
+        let second = std::fs::read_to_string(dir.join("_delta_log").join(&commits[1])).unwrap();
+        for action in ["commitInfo", "metaData", "remove", "add"] {
+            assert!(second.contains(&format!("{{\"{action}\"")), "{action}");
+        }
+
+        let snapshot = load_snapshot(&engine, &table_url).await.unwrap().unwrap();
+        assert_eq!(snapshot.version(), 1);
+        assert_eq!(
+            snapshot
+                .schema()
+                .fields()
+                .map(|field| field.name().clone())
+                .collect::<Vec<_>>(),
+            vec!["b"]
+        );
+
+        let scan = snapshot.scan_builder().build().unwrap();
+        let files: usize = scan
+            .scan_metadata(engine.as_ref())
+            .unwrap()
+            .map(|scan_metadata| {
+                scan_metadata
+                    .unwrap()
+                    .scan_files
+                    .selection_vector()
+                    .iter()
+                    .filter(|selected| **selected)
+                    .count()
+            })
+            .sum();
+        assert_eq!(files, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn escape_partition_directory() {
+        assert_eq!(escape_partition("2024-01-01"), "2024-01-01");
+        assert_eq!(escape_partition("a b/c=d"), "a%20b%2Fc%3Dd");
+        assert_eq!(escape_partition("日"), "%E6%97%A5");
+    }
+
+    #[test]
+    fn timestamp_partition_value() {
+        let ntz: ArrayRef = Arc::new(TimestampMicrosecondArray::from(vec![1_500_000]));
+        assert_eq!(
+            partition_value(&ntz).unwrap().as_deref(),
+            Some("1970-01-01 00:00:01.500000")
+        );
+
+        let utc: ArrayRef =
+            Arc::new(TimestampMicrosecondArray::from(vec![1_500_000]).with_timezone("UTC"));
+        assert_eq!(
+            partition_value(&utc).unwrap().as_deref(),
+            Some("1970-01-01T00:00:01.500000Z")
+        );
+
+        let null: ArrayRef = Arc::new(StringArray::from(vec![None::<&str>]));
+        assert_eq!(partition_value(&null).unwrap(), None);
+    }
+
+    #[test]
+    fn min_max_and_null_count() {
+        let long = "x".repeat(40);
+        let columns: Vec<(&str, ArrayRef)> = vec![
+            (
+                "n",
+                Arc::new(Float64Array::from(vec![Some(2.5), None, Some(-1.0)])),
+            ),
+            ("s", Arc::new(StringArray::from(vec!["b", "a", "c"]))),
+            (
+                "l",
+                Arc::new(StringArray::from(vec!["a", long.as_str(), "c"])),
+            ),
+            (
+                "nan",
+                Arc::new(Float64Array::from(vec![1.0, f64::NAN, 2.0])),
+            ),
+        ];
+
+        let mut stats = columns
+            .iter()
+            .map(|(name, array)| {
+                let mut stats =
+                    ColumnStats::try_new(&Field::new(*name, array.data_type().clone(), true))
+                        .unwrap();
+                stats.update(array).unwrap();
+                stats
+            })
+            .collect::<Vec<_>>();
+
+        let batch = RecordBatch::from(file_stats(3, &mut stats).unwrap());
+        let mut writer = Writer::<_, JsonArray>::new(Vec::new());
+        writer.write(&batch).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(writer.into_inner()).unwrap(),
+            r#"[{"numRecords":3,"minValues":{"n":-1.0,"s":"a"},"maxValues":{"n":2.5,"s":"c"},"nullCount":{"n":1,"s":0,"l":0,"nan":0}}]"#
+        );
+    }
+}
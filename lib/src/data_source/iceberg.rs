@@ -0,0 +1,607 @@
+// data_source/iceberg.rs: Apache Iceberg tables from metadata file, file-based or SQL catalog
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::DateTime;
+use datafusion::datasource::TableProvider;
+use futures::{StreamExt, TryStreamExt};
+use iceberg::{
+    io::{
+        FileIO, FileIOBuilder, FileMetadata, FileRead, FileWrite, InputFile, OutputFile, Storage,
+        StorageConfig, StorageFactory,
+    },
+    table::{StaticTable, Table},
+    ErrorKind, TableIdent,
+};
+use iceberg_datafusion::IcebergStaticTableProvider;
+use object_store::{path::Path, DynObjectStore};
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+use crate::data_source::database::{
+    any_pool::QueryParam,
+    driver::{DatabaseDriver, Placeholder},
+};
+use crate::data_source::location::{
+    file,
+    uri::{self, SupportedScheme},
+};
+use crate::request::body::DataSourceOption;
+use crate::response::http_error::ResponseError;
+use crate::settings::Settings;
+
+const METADATA_FILE_SUFFIX: &str = ".metadata.json";
+
+/// Creates lazy table provider of the snapshot, pushes down projection and filters for partition pruning.
+pub async fn to_table_provider(
+    location: &str,
+    options: &DataSourceOption,
+) -> Result<Arc<dyn TableProvider>, ResponseError> {
+    log::debug!("iceberg::to_table_provider(): location={location:?}");
+
+    if options.snapshot_id.is_some() && options.timestamp.is_some() {
+        return Err(ResponseError::request_validation(
+            "Iceberg table can not specify both 'snapshotId' and 'timestamp'",
+        ));
+    }
+
+    let table = load_table(location, options).await?;
+
+    let snapshot_id = match &options.timestamp {
+        Some(timestamp) => Some(snapshot_at(&table, timestamp)?),
+        None => options.snapshot_id,
+    };
+
+    log::debug!(
+        "Opened iceberg table {} at snapshot {snapshot_id:?}",
+        table.identifier()
+    );
+
+    Ok(match snapshot_id {
+        Some(snapshot_id) => Arc::new(
+            IcebergStaticTableProvider::try_new_from_table_snapshot(table, snapshot_id).await?,
+        ),
+        None => Arc::new(IcebergStaticTableProvider::try_new_from_table(table).await?),
+    })
+}
+
+async fn load_table(location: &str, options: &DataSourceOption) -> Result<Table, ResponseError> {
+    #[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+    if let Some(catalog) = &options.catalog {
+        let metadata_location = sql_catalog_metadata(catalog, location, options).await?;
+        let table_ident = TableIdent::from_strs(location.split('.'))?;
+        return static_table(&metadata_location, table_ident).await;
+    }
+
+    #[cfg(not(any(feature = "postgres", feature = "mysql", feature = "sqlite")))]
+    if options.catalog.is_some() {
+        return Err(ResponseError::unsupported_type(
+            "SQL catalog of iceberg requires database feature",
+        ));
+    }
+
+    let metadata_location = if location.ends_with(METADATA_FILE_SUFFIX) {
+        to_url(location)?
+    } else {
+        latest_metadata(&to_url(location.trim_end_matches('/'))?).await?
+    };
+
+    // `{namespace}/{table}/metadata/{version}.metadata.json` in file-based catalog
+    let names: Vec<&str> = metadata_location
+        .trim_end_matches(|c| c != '/')
+        .trim_end_matches('/')
+        .trim_end_matches("/metadata")
+        .rsplit('/')
+        .take(2)
+        .collect();
+    let table_ident = TableIdent::from_strs(names.into_iter().rev())?;
+
+    static_table(&metadata_location, table_ident).await
+}
+
+async fn static_table(
+    metadata_location: &str,
+    table_ident: TableIdent,
+) -> Result<Table, ResponseError> {
+    Ok(
+        StaticTable::from_metadata_file(metadata_location, table_ident, file_io())
+            .await?
+            .into_table(),
+    )
+}
+
+/// Location URL of iceberg files, local table is placed under the data directory.
+fn to_url(location: &str) -> Result<String, ResponseError> {
+    let parts = uri::to_parts(location)?;
+
+    Ok(
+        if uri::scheme(&parts).unwrap_or(SupportedScheme::File) == SupportedScheme::File {
+            let path = std::path::absolute(file::create_data_file_path(location)?)?;
+            format!("file://{}", path.display())
+        } else {
+            location.to_string()
+        },
+    )
+}
+
+fn file_io() -> FileIO {
+    FileIOBuilder::new(Arc::new(ObjectStoreStorageFactory)).build()
+}
+
+/// Iceberg storage over the object stores of the credential manager, so that the tables are read
+/// with the same endpoints and credential chains as the other data sources.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ObjectStoreStorageFactory;
+
+#[typetag::serde]
+impl StorageFactory for ObjectStoreStorageFactory {
+    fn build(&self, _config: &StorageConfig) -> iceberg::Result<Arc<dyn Storage>> {
+        Ok(Arc::new(ObjectStoreStorage))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ObjectStoreStorage;
+
+impl ObjectStoreStorage {
+    fn locate(location: &str) -> iceberg::Result<(Arc<DynObjectStore>, Path)> {
+        let url = url::Url::parse(location).map_err(|e| {
+            iceberg::Error::new(
+                ErrorKind::DataInvalid,
+                format!("Invalid iceberg file location '{location}'"),
+            )
+            .with_source(e)
+        })?;
+        let store = object_store(&url)
+            .map_err(|e| iceberg::Error::new(ErrorKind::Unexpected, e.message))?;
+        let path = Path::from_url_path(url.path()).map_err(to_iceberg_error)?;
+
+        Ok((store, path))
+    }
+}
+
+#[async_trait]
+#[typetag::serde]
+impl Storage for ObjectStoreStorage {
+    async fn exists(&self, path: &str) -> iceberg::Result<bool> {
+        let (store, path) = Self::locate(path)?;
+
+        match store.head(&path).await {
+            Ok(_) => Ok(true),
+            Err(object_store::Error::NotFound { .. }) => Ok(false),
+            Err(e) => Err(to_iceberg_error(e)),
+        }
+    }
+
+    async fn metadata(&self, path: &str) -> iceberg::Result<FileMetadata> {
+        let (store, path) = Self::locate(path)?;
+        let meta = store.head(&path).await.map_err(to_iceberg_error)?;
+
+        Ok(FileMetadata { size: meta.size })
+    }
+
+    async fn read(&self, path: &str) -> iceberg::Result<Bytes> {
+        let (store, path) = Self::locate(path)?;
+
+        store
+            .get(&path)
+            .await
+            .map_err(to_iceberg_error)?
+            .bytes()
+            .await
+            .map_err(to_iceberg_error)
+    }
+
+    async fn reader(&self, path: &str) -> iceberg::Result<Box<dyn FileRead>> {
+        let (store, path) = Self::locate(path)?;
+
+        Ok(Box::new(ObjectStoreRead { store, path }))
+    }
+
+    async fn write(&self, path: &str, bs: Bytes) -> iceberg::Result<()> {
+        let (store, path) = Self::locate(path)?;
+
+        store
+            .put(&path, bs.into())
+            .await
+            .map_err(to_iceberg_error)?;
+
+        Ok(())
+    }
+
+    async fn writer(&self, path: &str) -> iceberg::Result<Box<dyn FileWrite>> {
+        let (store, path) = Self::locate(path)?;
+
+        Ok(Box::new(ObjectStoreWrite {
+            store,
+            path,
+            buffer: Some(bytes::BytesMut::new()),
+        }))
+    }
+
+    async fn delete(&self, path: &str) -> iceberg::Result<()> {
+        let (store, path) = Self::locate(path)?;
+
+        match store.delete(&path).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(e) => Err(to_iceberg_error(e)),
+        }
+    }
+
+    async fn delete_prefix(&self, path: &str) -> iceberg::Result<()> {
+        let (store, path) = Self::locate(path)?;
+        let locations = store.list(Some(&path)).map_ok(|meta| meta.location).boxed();
+
+        store
+            .delete_stream(locations)
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(to_iceberg_error)?;
+
+        Ok(())
+    }
+
+    fn new_input(&self, path: &str) -> iceberg::Result<InputFile> {
+        Ok(InputFile::new(Arc::new(self.clone()), path.to_string()))
+    }
+
+    fn new_output(&self, path: &str) -> iceberg::Result<OutputFile> {
+        Ok(OutputFile::new(Arc::new(self.clone()), path.to_string()))
+    }
+}
+
+/// Ranged reads of the data files, only the row groups and the columns of the scan are fetched.
+struct ObjectStoreRead {
+    store: Arc<DynObjectStore>,
+    path: Path,
+}
+
+#[async_trait]
+impl FileRead for ObjectStoreRead {
+    async fn read(&self, range: Range<u64>) -> iceberg::Result<Bytes> {
+        self.store
+            .get_range(&self.path, range)
+            .await
+            .map_err(to_iceberg_error)
+    }
+}
+
+/// Buffers the written bytes, and puts the object on close.
+struct ObjectStoreWrite {
+    store: Arc<DynObjectStore>,
+    path: Path,
+    buffer: Option<bytes::BytesMut>,
+}
+
+#[async_trait]
+impl FileWrite for ObjectStoreWrite {
+    async fn write(&mut self, bs: Bytes) -> iceberg::Result<()> {
+        match &mut self.buffer {
+            Some(buffer) => {
+                buffer.extend_from_slice(&bs);
+                Ok(())
+            }
+            None => Err(closed_error(&self.path)),
+        }
+    }
+
+    async fn close(&mut self) -> iceberg::Result<()> {
+        let Some(buffer) = self.buffer.take() else {
+            return Err(closed_error(&self.path));
+        };
+
+        self.store
+            .put(&self.path, buffer.freeze().into())
+            .await
+            .map_err(to_iceberg_error)?;
+
+        Ok(())
+    }
+}
+
+fn closed_error(path: &Path) -> iceberg::Error {
+    iceberg::Error::new(
+        ErrorKind::Unexpected,
+        format!("Iceberg file '{path}' is already closed"),
+    )
+}
+
+fn to_iceberg_error(e: impl Into<object_store::Error>) -> iceberg::Error {
+    let e = e.into();
+    let kind = match e {
+        object_store::Error::NotFound { .. } => ErrorKind::DataInvalid,
+        _ => ErrorKind::Unexpected,
+    };
+
+    iceberg::Error::new(kind, "Failed to access iceberg file in object store").with_source(e)
+}
+
+/// Current metadata file of file-based catalog, by version hint or the latest version.
+async fn latest_metadata(table_location: &str) -> Result<String, ResponseError> {
+    let url = url::Url::parse(&format!("{table_location}/"))?;
+    let store = object_store(&url)?;
+    let metadata_dir = Path::from_url_path(url.path())
+        .map_err(object_store::Error::from)?
+        .child("metadata");
+
+    if let Ok(hint) = store.get(&metadata_dir.child("version-hint.text")).await {
+        let version = String::from_utf8_lossy(&hint.bytes().await?)
+            .trim()
+            .to_string();
+        return Ok(format!(
+            "{table_location}/metadata/v{version}{METADATA_FILE_SUFFIX}"
+        ));
+    }
+
+    let mut latest: Option<(u64, String)> = None;
+    let mut listing = store.list(Some(&metadata_dir));
+
+    while let Some(meta) = listing.next().await {
+        let meta = meta?;
+        let Some(name) = meta.location.filename() else {
+            continue;
+        };
+
+        // `v{version}.metadata.json` or `{version}-{uuid}.metadata.json`
+        let Some(version) = name.strip_suffix(METADATA_FILE_SUFFIX).and_then(|stem| {
+            stem.trim_start_matches('v')
+                .split('-')
+                .next()
+                .and_then(|version| version.parse::<u64>().ok())
+        }) else {
+            continue;
+        };
+
+        if latest.as_ref().is_none_or(|(latest, _)| *latest < version) {
+            latest = Some((version, name.to_string()));
+        }
+    }
+
+    latest
+        .map(|(_, name)| format!("{table_location}/metadata/{name}"))
+        .ok_or_else(|| {
+            ResponseError::request_validation(format!(
+                "Iceberg metadata not found in '{table_location}'"
+            ))
+        })
+}
+
+fn object_store(url: &url::Url) -> Result<Arc<DynObjectStore>, ResponseError> {
+    if url.scheme() == "file" {
+        return Ok(Arc::new(object_store::local::LocalFileSystem::new()));
+    }
+
+    Settings::global()
+        .object_store_manager
+        .store(url)
+        .ok_or_else(|| {
+            ResponseError::request_validation(format!(
+                "Object store '{url}' credential not configured"
+            ))
+        })
+}
+
+/// Metadata location registered in `iceberg_tables` of SQL (JDBC) catalog in the database.
+#[cfg(any(feature = "postgres", feature = "mysql", feature = "sqlite"))]
+async fn sql_catalog_metadata(
+    namespace: &str,
+    table: &str,
+    options: &DataSourceOption,
+) -> Result<String, ResponseError> {
+    let Some(resolver) = Settings::global()
+        .database_pool_manager
+        .resolvers
+        .get(namespace)
+    else {
+        return Err(ResponseError::request_validation(format!(
+            "Database namespace '{namespace}' not configured"
+        )));
+    };
+
+    let Some((table_namespace, table_name)) = table.rsplit_once('.') else {
+        return Err(ResponseError::request_validation(format!(
+            "Iceberg table '{table}' requires namespace, e.g. 'namespace.table'"
+        )));
+    };
+
+    let Some(pool) = resolver.pool() else {
+        return Err(ResponseError::unsupported_type(format!(
+            "Iceberg catalog of the database driver of '{namespace}' is not supported"
+        )));
+    };
+
+    let mut params = vec![table_namespace, table_name];
+    params.extend(options.catalog_name.as_deref());
+
+    let mut placeholders = (1..=params.len()).map(|n| match pool.placeholder() {
+        Placeholder::Numbered => format!("${n}"),
+        Placeholder::Positional => "?".to_string(),
+    });
+
+    let mut sql = format!(
+        "SELECT metadata_location FROM iceberg_tables WHERE table_namespace = {} AND table_name = {}",
+        placeholders.next().unwrap_or_default(),
+        placeholders.next().unwrap_or_default(),
+    );
+
+    if let Some(placeholder) = placeholders.next() {
+        sql = format!("{sql} AND catalog_name = {placeholder}");
+    }
+
+    let params: Vec<QueryParam> = params
+        .into_iter()
+        .map(|param| QueryParam::Text(Some(param.to_string())))
+        .collect();
+
+    let row = pool
+        .fetch_with_params(&sql, &params)
+        .next()
+        .await
+        .unwrap_or(Err(sqlx::Error::RowNotFound))
+        .map_err(|e| {
+            ResponseError::request_validation(format!(
+                "Iceberg table '{table}' not found in catalog '{namespace}': {e}"
+            ))
+        })?;
+
+    row.get::<String>("metadata_location")?.ok_or_else(|| {
+        ResponseError::unsupported_format(format!(
+            "Invalid metadata location of iceberg table '{table}'"
+        ))
+    })
+}
+
+/// Latest snapshot committed at or before the RFC 3339 timestamp.
+fn snapshot_at(table: &Table, timestamp: &str) -> Result<i64, ResponseError> {
+    let timestamp_ms = DateTime::parse_from_rfc3339(timestamp)
+        .map_err(|e| {
+            ResponseError::request_validation(format!(
+                "Invalid timestamp '{timestamp}' of iceberg table: {e}"
+            ))
+        })?
+        .timestamp_millis();
+
+    table
+        .metadata()
+        .snapshots()
+        .filter(|snapshot| snapshot.timestamp_ms() <= timestamp_ms)
+        .max_by_key(|snapshot| snapshot.timestamp_ms())
+        .map(|snapshot| snapshot.snapshot_id())
+        .ok_or_else(|| {
+            ResponseError::request_validation(format!(
+                "No snapshot of iceberg table {} at {timestamp}",
+                table.identifier()
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use datafusion::{arrow::array::AsArray, prelude::SessionContext};
+    use iceberg::{
+        memory::{MemoryCatalogBuilder, MEMORY_CATALOG_WAREHOUSE},
+        spec::{NestedField, PrimitiveType, Schema, Transform, Type, UnboundPartitionSpec},
+        Catalog, CatalogBuilder, NamespaceIdent, TableCreation, TableIdent,
+    };
+    use iceberg_datafusion::{IcebergCatalogProvider, IcebergStaticTableProvider};
+
+    use crate::data_source::iceberg::{
+        latest_metadata, snapshot_at, static_table, ObjectStoreStorageFactory,
+    };
+
+    async fn count(provider: IcebergStaticTableProvider, sql: &str) -> i64 {
+        let ctx = SessionContext::new();
+        ctx.register_table("t", Arc::new(provider)).unwrap();
+        let batches = ctx.sql(sql).await.unwrap().collect().await.unwrap();
+        batches[0]
+            .column(0)
+            .as_primitive::<datafusion::arrow::datatypes::Int64Type>()
+            .value(0)
+    }
+
+    #[tokio::test]
+    async fn snapshots_of_table_in_object_store() {
+        let dir = std::env::temp_dir().join(format!(
+            "datafusion-server-iceberg-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let location = format!("file://{}/db/events", dir.display());
+
+        let catalog = MemoryCatalogBuilder::default()
+            .with_storage_factory(Arc::new(ObjectStoreStorageFactory))
+            .load(
+                "memory",
+                HashMap::from([(
+                    MEMORY_CATALOG_WAREHOUSE.to_string(),
+                    format!("file://{}", dir.display()),
+                )]),
+            )
+            .await
+            .unwrap();
+        let namespace = NamespaceIdent::new("db".to_string());
+        catalog
+            .create_namespace(&namespace, HashMap::new())
+            .await
+            .unwrap();
+
+        let schema = Schema::builder()
+            .with_fields(vec![
+                NestedField::required(1, "id", Type::Primitive(PrimitiveType::Int)).into(),
+                NestedField::required(2, "category", Type::Primitive(PrimitiveType::String)).into(),
+            ])
+            .build()
+            .unwrap();
+        let partition_spec = UnboundPartitionSpec::builder()
+            .add_partition_field(2, "category", Transform::Identity)
+            .unwrap()
+            .build();
+        catalog
+            .create_table(
+                &namespace,
+                TableCreation::builder()
+                    .name("events".to_string())
+                    .location(location.clone())
+                    .schema(schema)
+                    .partition_spec(partition_spec)
+                    .build(),
+            )
+            .await
+            .unwrap();
+
+        let ctx = SessionContext::new();
+        ctx.register_catalog(
+            "lake",
+            Arc::new(
+                IcebergCatalogProvider::try_new(Arc::new(catalog))
+                    .await
+                    .unwrap(),
+            ),
+        );
+        for values in ["(1, 'a'), (2, 'b')", "(3, 'a')"] {
+            ctx.sql(&format!("INSERT INTO lake.db.events VALUES {values}"))
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+        }
+
+        let metadata_location = latest_metadata(&location).await.unwrap();
+        let table = static_table(
+            &metadata_location,
+            TableIdent::from_strs(["db", "events"]).unwrap(),
+        )
+        .await
+        .unwrap();
+        let first = table
+            .metadata()
+            .snapshots()
+            .min_by_key(|snapshot| snapshot.timestamp_ms())
+            .unwrap()
+            .snapshot_id();
+
+        let current = IcebergStaticTableProvider::try_new_from_table(table.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            count(current, "SELECT count(*) FROM t WHERE category = 'a'").await,
+            2
+        );
+
+        let snapshot =
+            IcebergStaticTableProvider::try_new_from_table_snapshot(table.clone(), first)
+                .await
+                .unwrap();
+        assert_eq!(count(snapshot, "SELECT count(*) FROM t").await, 2);
+
+        assert!(snapshot_at(&table, "2000-01-01T00:00:00Z").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -34,10 +34,7 @@ pub async fn from_response_to_record_batch(
     schema: Option<&DataSourceSchema>,
     options: &DataSourceOption,
 ) -> Result<Vec<RecordBatch>, ResponseError> {
-    let response = match http::get(uri, options, http::ResponseDataType::Text).await? {
-        http::ResponseData::Text(data) => data,
-        http::ResponseData::Binary(_) => String::new(),
-    };
+    let response = http::get_text(uri, options).await?;
 
     Ok(if options.json_path.is_none() {
         build_record_batch::from_json(&response, schema, options)?
@@ -1,2 +0,0 @@
-pub mod fs;
-pub mod json_file;
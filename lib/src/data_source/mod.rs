@@ -2,20 +2,25 @@
 pub mod connector_plugin;
 pub mod csv;
 pub mod data_type;
-#[cfg(any(feature = "postgres", feature = "mysql"))]
+#[cfg(feature = "database")]
 pub mod database;
 mod decoder;
 #[cfg(feature = "deltalake")]
 pub mod deltalake;
+#[cfg(feature = "deltalake")]
+pub mod deltalake_writer;
 #[cfg(feature = "flight")]
 pub mod flight_stream;
+#[cfg(feature = "iceberg")]
+pub mod iceberg;
 pub mod infer_schema;
 pub mod json;
-pub mod local_fs;
 pub mod location;
 pub mod nd_json;
 pub mod object_store;
+#[cfg(feature = "orc")]
+pub mod orc;
 pub mod parquet;
 pub mod schema;
-mod transport;
+pub mod transport;
 mod with_jsonpath;
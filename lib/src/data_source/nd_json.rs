@@ -21,10 +21,7 @@ pub async fn from_response_to_record_batch(
     schema: Option<&DataSourceSchema>,
     options: &DataSourceOption,
 ) -> Result<Vec<RecordBatch>, ResponseError> {
-    let response = match http::get(uri, options, http::ResponseDataType::Binary).await? {
-        http::ResponseData::Text(_) => bytes::Bytes::new(),
-        http::ResponseData::Binary(data) => data,
-    };
+    let response = http::get_bytes(uri, options).await?;
     to_record_batch(Cursor::new(response), schema, options)
 }
 
@@ -2,16 +2,254 @@
 // Sasaki, Naoki <nsasaki@sal.co.jp> June 15, 2024
 //
 
-#[cfg(feature = "webdav")]
+use std::env;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use object_store::aws::{AmazonS3Builder, AmazonS3ConfigKey};
+use object_store::azure::{AzureConfigKey, MicrosoftAzureBuilder};
+use object_store::gcp::{GoogleCloudStorageBuilder, GoogleConfigKey};
+use object_store::DynObjectStore;
 #[cfg(feature = "webdav")]
-use object_store::{http::HttpBuilder, ClientOptions, DynObjectStore};
+use object_store::{http::HttpBuilder, ClientOptions};
 
 #[cfg(feature = "webdav")]
 use crate::data_source::location;
 #[cfg(feature = "webdav")]
 use crate::response::http_error::ResponseError;
+use crate::settings::{StorageAws, StorageAzure, StorageGcp};
+
+pub fn aws(
+    mut builder: AmazonS3Builder,
+    aws: &StorageAws,
+) -> Result<Arc<DynObjectStore>, object_store::Error> {
+    if let Some(profile) = &aws.profile {
+        builder = aws_profile(builder, profile)?;
+    }
+
+    for (key, value) in aws.options.iter().flatten() {
+        builder = builder.with_config(key.parse::<AmazonS3ConfigKey>()?, value);
+    }
+
+    builder = builder.with_bucket_name(&aws.bucket);
+
+    if let Some(access_key_id) = &aws.access_key_id {
+        builder = builder.with_access_key_id(access_key_id);
+    }
+    if let Some(secret_access_key) = &aws.secret_access_key {
+        builder = builder.with_secret_access_key(secret_access_key);
+    }
+    if let Some(session_token) = &aws.session_token {
+        builder = builder.with_token(session_token);
+    }
+    if let Some(region) = &aws.region {
+        builder = builder.with_region(region);
+    }
+    if let Some(endpoint) = &aws.endpoint {
+        builder = builder.with_endpoint(endpoint);
+    }
+    if let Some(path_style) = aws.path_style {
+        builder = builder.with_virtual_hosted_style_request(!path_style);
+    }
+    if let Some(allow_http) = aws.allow_http {
+        builder = builder.with_allow_http(allow_http);
+    }
+    if let Some(role_session_name) = &aws.role_session_name {
+        builder = builder.with_config(AmazonS3ConfigKey::RoleSessionName, role_session_name);
+    }
+    if let Some(sts_endpoint) = &aws.sts_endpoint {
+        builder = builder.with_config(AmazonS3ConfigKey::StsEndpoint, sts_endpoint);
+    }
+    if aws.imdsv1_fallback == Some(true) {
+        builder = builder.with_imdsv1_fallback();
+    }
+    if let Some(skip_signature) = aws.skip_signature {
+        builder = builder.with_skip_signature(skip_signature);
+    }
+
+    Ok(Arc::new(builder.build()?))
+}
+
+/// Applies the keys and region of the named profile in the shared credentials and config files.
+/// Profiles resolving the credentials by assume role, credential process or SSO are rejected.
+pub fn aws_profile(
+    builder: AmazonS3Builder,
+    profile: &str,
+) -> Result<AmazonS3Builder, object_store::Error> {
+    let home = env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_default();
+
+    let credentials = ini_section(
+        &env::var("AWS_SHARED_CREDENTIALS_FILE")
+            .map_or_else(|_| home.join(".aws").join("credentials"), PathBuf::from),
+        profile,
+    );
+
+    let config = ini_section(
+        &env::var("AWS_CONFIG_FILE")
+            .map_or_else(|_| home.join(".aws").join("config"), PathBuf::from),
+        &if profile == "default" {
+            profile.to_string()
+        } else {
+            format!("profile {profile}")
+        },
+    );
+
+    if credentials.is_empty() && config.is_empty() {
+        return Err(object_store::Error::Generic {
+            store: "S3",
+            source: format!("AWS profile '{profile}' not found").into(),
+        });
+    }
+
+    apply_profile_entries(builder, profile, config.into_iter().chain(credentials))
+}
+
+fn apply_profile_entries(
+    mut builder: AmazonS3Builder,
+    profile: &str,
+    entries: impl IntoIterator<Item = (String, String)>,
+) -> Result<AmazonS3Builder, object_store::Error> {
+    // config file may also have the keys, credentials file takes precedence
+    for (key, value) in entries {
+        if is_unsupported_profile_key(&key) {
+            return Err(object_store::Error::Generic {
+                store: "S3",
+                source: format!(
+                    "AWS profile '{profile}' uses '{key}' which is not supported, specify the keys or use the environment instead"
+                )
+                .into(),
+            });
+        }
+
+        let key = match key.as_str() {
+            "aws_access_key_id" => AmazonS3ConfigKey::AccessKeyId,
+            "aws_secret_access_key" => AmazonS3ConfigKey::SecretAccessKey,
+            "aws_session_token" => AmazonS3ConfigKey::Token,
+            "region" => AmazonS3ConfigKey::Region,
+            "endpoint_url" => AmazonS3ConfigKey::Endpoint,
+            _ => continue,
+        };
+        builder = builder.with_config(key, value);
+    }
+
+    Ok(builder)
+}
+
+fn is_unsupported_profile_key(key: &str) -> bool {
+    matches!(
+        key,
+        "role_arn"
+            | "source_profile"
+            | "credential_source"
+            | "credential_process"
+            | "web_identity_token_file"
+    ) || key.starts_with("sso_")
+}
+
+fn ini_section(path: &Path, section: &str) -> Vec<(String, String)> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return vec![];
+    };
+
+    let mut current = String::new();
+    let mut entries = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = name.trim().to_string();
+        } else if current == section {
+            if let Some((key, value)) = line.split_once('=') {
+                entries.push((key.trim().to_lowercase(), value.trim().to_string()));
+            }
+        }
+    }
+
+    entries
+}
+
+pub fn gcp(
+    mut builder: GoogleCloudStorageBuilder,
+    gcp: &StorageGcp,
+) -> Result<Arc<DynObjectStore>, object_store::Error> {
+    for (key, value) in gcp.options.iter().flatten() {
+        builder = builder.with_config(key.parse::<GoogleConfigKey>()?, value);
+    }
+
+    builder = builder.with_bucket_name(&gcp.bucket);
+
+    if let Some(service_account_key) = &gcp.service_account_key {
+        builder = builder.with_service_account_key(service_account_key);
+    }
+    if let Some(service_account_path) = &gcp.service_account_path {
+        builder = builder.with_service_account_path(service_account_path);
+    }
+    if let Some(application_credentials) = &gcp.application_credentials {
+        builder = builder.with_application_credentials(application_credentials);
+    }
+    if let Some(skip_signature) = gcp.skip_signature {
+        builder = builder.with_skip_signature(skip_signature);
+    }
+
+    Ok(Arc::new(builder.build()?))
+}
+
+pub fn azure(
+    mut builder: MicrosoftAzureBuilder,
+    azure: &StorageAzure,
+) -> Result<Arc<DynObjectStore>, object_store::Error> {
+    for (key, value) in azure.options.iter().flatten() {
+        builder = builder.with_config(key.parse::<AzureConfigKey>()?, value);
+    }
+
+    builder = builder
+        .with_account(&azure.account_name)
+        .with_container_name(&azure.container);
+
+    if let Some(access_key) = &azure.access_key {
+        builder = builder.with_access_key(access_key);
+    }
+    if let Some(sas_token) = &azure.sas_token {
+        builder = builder.with_config(AzureConfigKey::SasKey, sas_token);
+    }
+    if let Some(bearer_token) = &azure.bearer_token {
+        builder = builder.with_bearer_token_authorization(bearer_token);
+    }
+    if let Some(client_id) = &azure.client_id {
+        builder = builder.with_client_id(client_id);
+    }
+    if let Some(client_secret) = &azure.client_secret {
+        builder = builder.with_client_secret(client_secret);
+    }
+    if let Some(tenant_id) = &azure.tenant_id {
+        builder = builder.with_tenant_id(tenant_id);
+    }
+    if let Some(federated_token_file) = &azure.federated_token_file {
+        builder = builder.with_federated_token_file(federated_token_file);
+    }
+    if let Some(msi_endpoint) = &azure.msi_endpoint {
+        builder = builder.with_msi_endpoint(msi_endpoint);
+    }
+    if let Some(use_azure_cli) = azure.use_azure_cli {
+        builder = builder.with_use_azure_cli(use_azure_cli);
+    }
+    if let Some(use_emulator) = azure.use_emulator {
+        builder = builder.with_use_emulator(use_emulator);
+    }
+    if let Some(endpoint) = &azure.endpoint {
+        builder = builder.with_endpoint(endpoint.clone());
+    }
+    if let Some(allow_http) = azure.allow_http {
+        builder = builder.with_allow_http(allow_http);
+    }
+
+    Ok(Arc::new(builder.build()?))
+}
 
 #[cfg(feature = "webdav")]
 pub fn webdav(
@@ -35,3 +273,59 @@ pub fn webdav(
 
     Ok((Arc::new(http_store), scheme, authority))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(keys: &[(&str, &str)]) -> Vec<(String, String)> {
+        keys.iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn static_keys_applied() {
+        let builder = apply_profile_entries(
+            AmazonS3Builder::new(),
+            "dev",
+            entries(&[
+                ("aws_access_key_id", "AKIA"),
+                ("aws_secret_access_key", "SECRET"),
+                ("region", "ap-northeast-1"),
+                ("output", "json"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            builder.get_config_value(&AmazonS3ConfigKey::AccessKeyId),
+            Some("AKIA".to_string())
+        );
+        assert_eq!(
+            builder.get_config_value(&AmazonS3ConfigKey::Region),
+            Some("ap-northeast-1".to_string())
+        );
+    }
+
+    #[test]
+    fn unsupported_keys_rejected() {
+        for key in [
+            "role_arn",
+            "source_profile",
+            "credential_process",
+            "sso_start_url",
+            "sso_session",
+        ] {
+            let result = apply_profile_entries(
+                AmazonS3Builder::new(),
+                "dev",
+                entries(&[("region", "us-east-1"), (key, "value")]),
+            );
+            let Err(e) = result else {
+                panic!("'{key}' must be rejected");
+            };
+            assert!(e.to_string().contains(key));
+        }
+    }
+}
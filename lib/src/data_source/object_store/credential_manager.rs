@@ -10,9 +10,9 @@ use object_store::aws::AmazonS3Builder;
 use object_store::azure::MicrosoftAzureBuilder;
 use object_store::gcp::GoogleCloudStorageBuilder;
 use object_store::DynObjectStore;
+use url::Url;
 
 use crate::data_source::location::uri::SupportedScheme;
-#[cfg(feature = "webdav")]
 use crate::data_source::object_store::build_store;
 use crate::settings::Storage;
 
@@ -47,14 +47,7 @@ impl ObjectStoreManager {
                     log::debug!("Create '{key}' object store registry");
 
                     if let Entry::Vacant(entry) = stores.entry(key.clone()) {
-                        entry.insert(Arc::new(
-                            AmazonS3Builder::new()
-                                .with_bucket_name(&aws.bucket)
-                                .with_region(&aws.region)
-                                .with_access_key_id(&aws.access_key_id)
-                                .with_secret_access_key(&aws.secret_access_key)
-                                .build()?,
-                        ));
+                        entry.insert(build_store::aws(AmazonS3Builder::new(), aws)?);
                     } else {
                         log::error!("Duplicated credential '{key}'");
                     }
@@ -64,28 +57,17 @@ impl ObjectStoreManager {
                     log::debug!("Create '{key}' object store registry");
 
                     if let Entry::Vacant(entry) = stores.entry(key.clone()) {
-                        entry.insert(Arc::new(
-                            GoogleCloudStorageBuilder::new()
-                                .with_bucket_name(&gcp.bucket)
-                                .with_service_account_key(&gcp.service_account_key)
-                                .build()?,
-                        ));
+                        entry.insert(build_store::gcp(GoogleCloudStorageBuilder::new(), gcp)?);
                     } else {
                         log::error!("Duplicated credential '{key}'");
                     }
                 }
                 Storage::Azure(azure) => {
-                    let key = Self::store_key(&SupportedScheme::GS, &azure.container);
+                    let key = Self::azure_store_key(&azure.account_name, &azure.container);
                     log::debug!("Create '{key}' object store registry");
 
                     if let Entry::Vacant(entry) = stores.entry(key.clone()) {
-                        entry.insert(Arc::new(
-                            MicrosoftAzureBuilder::new()
-                                .with_account(&azure.account_name)
-                                .with_access_key(&azure.access_key)
-                                .with_container_name(&azure.container)
-                                .build()?,
-                        ));
+                        entry.insert(build_store::azure(MicrosoftAzureBuilder::new(), azure)?);
                     } else {
                         log::error!("Duplicated credential '{key}'");
                     }
@@ -121,26 +103,22 @@ impl ObjectStoreManager {
     fn from_env(
         stores: &mut HashMap<String, Arc<DynObjectStore>>,
     ) -> Result<(), object_store::Error> {
-        if env::var("AWS_ACCESS_KEY_ID").is_ok()
-            && env::var("AWS_SECRET_ACCESS_KEY").is_ok()
-            && env::var("AWS_BUCKET").is_ok()
-        {
-            let bucket = env::var("AWS_BUCKET").unwrap_or_default();
+        // other credentials are resolved by the builder, e.g. `AWS_SESSION_TOKEN`, `AWS_ENDPOINT`,
+        // `AWS_WEB_IDENTITY_TOKEN_FILE` and the instance profile
+        if let Ok(bucket) = env::var("AWS_BUCKET") {
             let key = Self::store_key(&SupportedScheme::S3, &bucket);
 
             log::debug!("Create '{key}' object store registry");
 
-            stores.insert(
-                key,
-                Arc::new(
-                    AmazonS3Builder::from_env()
-                        .with_bucket_name(&bucket)
-                        .build()?,
-                ),
-            );
+            let mut builder = AmazonS3Builder::from_env();
+            if let Ok(profile) = env::var("AWS_PROFILE") {
+                builder = build_store::aws_profile(builder, &profile)?;
+            }
+
+            stores.insert(key, Arc::new(builder.with_bucket_name(&bucket).build()?));
         }
 
-        if env::var("GOOGLE_SERVICE_ACCOUNT_KEY").is_ok() && env::var("GOOGLE_BUCKET").is_ok() {
+        if env::var("GOOGLE_BUCKET").is_ok() {
             let bucket = env::var("GOOGLE_BUCKET").unwrap_or_default();
             let key = Self::store_key(&SupportedScheme::GS, &bucket);
 
@@ -156,12 +134,11 @@ impl ObjectStoreManager {
             );
         }
 
-        if env::var("AZURE_STORAGE_ACCOUNT_NAME").is_ok()
-            && env::var("AZURE_STORAGE_ACCESS_KEY").is_ok()
-            && env::var("AZURE_CONTAINER").is_ok()
-        {
-            let container = env::var("AZURE_CONTAINER").unwrap_or_default();
-            let key = Self::store_key(&SupportedScheme::AZ, &container);
+        if let (Ok(account), Ok(container)) = (
+            env::var("AZURE_STORAGE_ACCOUNT_NAME"),
+            env::var("AZURE_CONTAINER"),
+        ) {
+            let key = Self::azure_store_key(&account, &container);
 
             log::debug!("Create '{key}' object store registry");
 
@@ -203,4 +180,61 @@ impl ObjectStoreManager {
     pub fn store_key(scheme: &SupportedScheme, bucket: &str) -> String {
         format!("{}://{}", scheme.to_str(), bucket)
     }
+
+    /// Azure stores are keyed by the account and the container, as the container names are
+    /// unique only in the account.
+    pub fn azure_store_key(account: &str, container: &str) -> String {
+        format!("abfss://{container}@{account}.dfs.core.windows.net")
+    }
+
+    /// Store of the location URL, Azure container without the account (e.g. `az://container`)
+    /// is resolved only when the container name is unique in the all accounts.
+    pub fn store(&self, url: &Url) -> Option<Arc<DynObjectStore>> {
+        match azure_location(url) {
+            Some((Some(account), container)) => self
+                .stores
+                .get(&Self::azure_store_key(&account, &container))
+                .cloned(),
+            Some((None, container)) => {
+                let prefix = format!("abfss://{container}@");
+                let mut stores = self
+                    .stores
+                    .iter()
+                    .filter(|(key, _)| key.starts_with(&prefix));
+
+                match (stores.next(), stores.next()) {
+                    (Some((_, store)), None) => Some(store.clone()),
+                    (Some((key, _)), Some(_)) => {
+                        log::warn!("Ambiguous Azure container '{container}', e.g. '{key}'");
+                        None
+                    }
+                    _ => None,
+                }
+            }
+            None => self
+                .stores
+                .get(&Self::store_key(
+                    &SupportedScheme::from_str(url.scheme()),
+                    url.host_str().unwrap_or_default(),
+                ))
+                .cloned(),
+        }
+    }
+}
+
+/// Account and container of the Azure location, `abfss://container@account.dfs.core.windows.net/path`
+/// or `az://container/path` (without account).
+pub fn azure_location(url: &Url) -> Option<(Option<String>, String)> {
+    if SupportedScheme::from_str(url.scheme()) != SupportedScheme::AZ {
+        return None;
+    }
+
+    let host = url.host_str()?;
+
+    if url.username().is_empty() {
+        Some((None, host.to_string()))
+    } else {
+        let account = host.split_once('.').map_or(host, |(account, _)| account);
+        Some((Some(account.to_string()), url.username().to_string()))
+    }
 }
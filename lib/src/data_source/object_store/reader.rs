@@ -3,11 +3,23 @@
 #[cfg(feature = "avro")]
 use datafusion::datasource::file_format::options::AvroReadOptions;
 use datafusion::{
-    datasource::file_format::options::{CsvReadOptions, NdJsonReadOptions, ParquetReadOptions},
-    execution::context::SessionContext,
+    arrow::datatypes::DataType,
+    datasource::{
+        file_format::options::{
+            CsvReadOptions, NdJsonReadOptions, ParquetReadOptions, ReadOptions,
+        },
+        listing::ListingTableUrl,
+    },
+    execution::{context::SessionContext, object_store::ObjectStoreUrl},
 };
 
+#[cfg(feature = "orc")]
+use datafusion_orc::OrcReadOptions;
+use futures::TryStreamExt;
+use object_store::GetOptions;
+
 use crate::data_source::location;
+use crate::data_source::transport::cache::{self, CacheKey, Validators};
 use crate::request::body::{DataSource, DataSourceFormat, DataSourceOption};
 use crate::response::http_error::ResponseError;
 
@@ -20,8 +32,10 @@ pub async fn register(ctx: &SessionContext, data_source: &DataSource) -> Result<
         == location::uri::SupportedScheme::File
     {
         location::file::create_data_file_path(&data_source.location)?
-    } else {
+    } else if is_listing(&data_source.location) || !is_read_whole(&data_source.format) {
         data_source.location.clone()
+    } else {
+        fetch_through_cache(ctx, &data_source.location).await?
     };
 
     match data_source.format {
@@ -30,6 +44,8 @@ pub async fn register(ctx: &SessionContext, data_source: &DataSource) -> Result<
         DataSourceFormat::Parquet => register_parquet(ctx, data_source, &location).await?,
         #[cfg(feature = "avro")]
         DataSourceFormat::Avro => register_avro(ctx, data_source, &location).await?,
+        #[cfg(feature = "orc")]
+        DataSourceFormat::Orc => register_orc(ctx, data_source, &location).await?,
         _ => {
             return Err(ResponseError::unsupported_type(format!(
                 "read from object store is not supported {:?}",
@@ -41,6 +57,158 @@ pub async fn register(ctx: &SessionContext, data_source: &DataSource) -> Result<
     Ok(())
 }
 
+// directory or glob pattern, e.g. `s3://bucket/events/*/part-*.parquet`
+fn is_listing(location: &str) -> bool {
+    location.ends_with('/')
+        || location.contains(['*', '?', '['])
+        || std::path::Path::new(location).is_dir()
+}
+
+// Parquet, Avro and ORC are read by the byte ranges of the footer and the projected columns,
+// downloading whole objects into the data cache costs more than the ranges
+fn is_read_whole(format: &DataSourceFormat) -> bool {
+    matches!(
+        format,
+        DataSourceFormat::Csv | DataSourceFormat::Json | DataSourceFormat::NdJson
+    )
+}
+
+/// Partition columns of hive-style directories, explicitly typed or discovered and typed by the values.
+async fn partition_columns<'a>(
+    ctx: &SessionContext,
+    options: &DataSourceOption,
+    location: &str,
+    read_options: &impl ReadOptions<'a>,
+) -> Result<Vec<(String, DataType)>, ResponseError> {
+    if let Some(columns) = &options.partition_columns {
+        return columns
+            .iter()
+            .map(|column| Ok((column.name.clone(), column.data_type.to_arrow_data_type()?)))
+            .collect();
+    }
+
+    if !options.hive_partitioning.unwrap_or(true) || !is_listing(location) {
+        return Ok(vec![]);
+    }
+
+    let table_url = ListingTableUrl::parse(location)?;
+    let listing_options =
+        read_options.to_listing_options(&ctx.copied_config(), ctx.copied_table_options());
+    let names = listing_options
+        .infer_partitions(&ctx.state(), &table_url)
+        .await?;
+
+    log::debug!("Discovered partition columns of {location}: {names:?}");
+
+    if names.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // every file is listed, a value of the other type would fail the scan
+    let state = ctx.state();
+    let store = ctx.runtime_env().object_store(&table_url)?;
+    let mut values: Vec<Vec<String>> = vec![vec![]; names.len()];
+    let mut files = table_url
+        .list_all_files(&state, store.as_ref(), &listing_options.file_extension)
+        .await?;
+    while let Some(file) = files.try_next().await? {
+        let Some(parts) = table_url.strip_prefix(&file.location) else {
+            continue;
+        };
+        for (part, (name, values)) in parts.zip(names.iter().zip(values.iter_mut())) {
+            match part.split_once('=') {
+                Some((key, value)) if key == name => values.push(value.to_string()),
+                _ => break,
+            }
+        }
+    }
+
+    Ok(names
+        .into_iter()
+        .zip(values)
+        .map(|(name, values)| (name, partition_value_type(&values)))
+        .collect())
+}
+
+/// Int64, Boolean or Date32 if all the values are parsable as the type, otherwise Utf8.
+fn partition_value_type(values: &[String]) -> DataType {
+    if values.is_empty() {
+        DataType::Utf8
+    } else if values.iter().all(|v| v.parse::<i64>().is_ok()) {
+        DataType::Int64
+    } else if values.iter().all(|v| v == "true" || v == "false") {
+        DataType::Boolean
+    } else if values
+        .iter()
+        .all(|v| chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d").is_ok())
+    {
+        DataType::Date32
+    } else {
+        DataType::Utf8
+    }
+}
+
+/// Downloads remote object into the data cache if modified, returns the location to register.
+/// The cached file is linked to the session not to be removed by eviction while the table is used.
+async fn fetch_through_cache(
+    ctx: &SessionContext,
+    location: &str,
+) -> Result<String, ResponseError> {
+    let Some(cache) = cache::global().await? else {
+        return Ok(location.to_string());
+    };
+
+    let url = url::Url::parse(location)?;
+    let store = ctx
+        .runtime_env()
+        .object_store(ObjectStoreUrl::parse(&url[..url::Position::BeforePath])?)?;
+    let path =
+        object_store::path::Path::from_url_path(url.path()).map_err(object_store::Error::from)?;
+
+    // credentials of the object store are configured per bucket, the location identifies it
+    let key = CacheKey::new(location, None, None);
+
+    let validators = cache.validators(&key).await.unwrap_or_default();
+    let options = GetOptions {
+        if_none_match: validators.etag,
+        if_modified_since: validators
+            .last_modified
+            .and_then(|v| chrono::DateTime::parse_from_rfc2822(&v).ok())
+            .map(|v| v.with_timezone(&chrono::Utc)),
+        ..GetOptions::default()
+    };
+
+    let result = match store.get_opts(&path, options).await {
+        Err(object_store::Error::NotModified { .. }) => {
+            if let Some(linked) = cache.checkout(&key, &ctx.session_id()).await? {
+                log::debug!("Not modified '{location}', using cached data");
+                return Ok(linked.to_string_lossy().to_string());
+            }
+            store.get(&path).await?
+        }
+        result => result?,
+    };
+
+    if !cache.can_store(result.meta.size) {
+        log::debug!("Not cacheable '{location}': size={}", result.meta.size);
+        return Ok(location.to_string());
+    }
+
+    let validators = Validators {
+        etag: result.meta.e_tag.clone(),
+        last_modified: Some(result.meta.last_modified.to_rfc2822()),
+    };
+
+    cache
+        .store_stream(&key, validators, result.into_stream())
+        .await?;
+
+    Ok(match cache.checkout(&key, &ctx.session_id()).await? {
+        Some(linked) => linked.to_string_lossy().to_string(),
+        None => location.to_string(),
+    })
+}
+
 async fn register_csv(
     ctx: &SessionContext,
     data_source: &DataSource,
@@ -65,6 +233,9 @@ async fn register_csv(
         csv_options.schema_infer_max_records(options.infer_schema_rows.unwrap_or(100))
     };
 
+    let partitions = partition_columns(ctx, &options, location, &csv_options).await?;
+    let csv_options = csv_options.table_partition_cols(partitions);
+
     ctx.register_csv(&data_source.name, location, csv_options)
         .await?;
 
@@ -94,6 +265,9 @@ async fn register_ndjson(
         ndjson_options
     };
 
+    let partitions = partition_columns(ctx, &options, location, &ndjson_options).await?;
+    let ndjson_options = ndjson_options.table_partition_cols(partitions);
+
     ctx.register_json(&data_source.name, location, ndjson_options)
         .await?;
 
@@ -106,8 +280,19 @@ async fn register_parquet(
     location: &str,
 ) -> Result<(), ResponseError> {
     log::debug!("object_store::register_parquet(): {location}");
-    ctx.register_parquet(&data_source.name, location, ParquetReadOptions::default())
+
+    let options = match &data_source.options {
+        Some(options) => options.clone(),
+        None => DataSourceOption::new(),
+    };
+
+    let parquet_options = ParquetReadOptions::default();
+    let partitions = partition_columns(ctx, &options, location, &parquet_options).await?;
+    let parquet_options = parquet_options.table_partition_cols(partitions);
+
+    ctx.register_parquet(&data_source.name, location, parquet_options)
         .await?;
+
     Ok(())
 }
 
@@ -119,6 +304,11 @@ async fn register_avro(
 ) -> Result<(), ResponseError> {
     log::debug!("object_store::register_avro(): {location}");
 
+    let options = match &data_source.options {
+        Some(options) => options.clone(),
+        None => DataSourceOption::new(),
+    };
+
     let mut avro_options = AvroReadOptions::default();
 
     let arrow_schema;
@@ -127,8 +317,120 @@ async fn register_avro(
         avro_options = avro_options.schema(&arrow_schema);
     }
 
+    let partitions = partition_columns(ctx, &options, location, &avro_options).await?;
+    let avro_options = avro_options.table_partition_cols(partitions);
+
     ctx.register_avro(&data_source.name, location, avro_options)
         .await?;
 
     Ok(())
 }
+
+/// Registers as listing table, scans only the projected columns (stripes) of ORC files.
+#[cfg(feature = "orc")]
+pub async fn register_orc(
+    ctx: &SessionContext,
+    data_source: &DataSource,
+    location: &str,
+) -> Result<(), ResponseError> {
+    log::debug!("object_store::register_orc(): {location}");
+
+    let options = match &data_source.options {
+        Some(options) => options.clone(),
+        None => DataSourceOption::new(),
+    };
+
+    let orc_options = OrcReadOptions::default();
+    let partitions = partition_columns(ctx, &options, location, &orc_options).await?;
+    let listing_options = orc_options
+        .to_listing_options(&ctx.copied_config(), ctx.copied_table_options())
+        .with_table_partition_cols(partitions);
+
+    let arrow_schema = match &data_source.schema {
+        Some(schema) => Some(std::sync::Arc::new(schema.to_arrow_schema()?)),
+        None => None,
+    };
+
+    ctx.register_listing_table(
+        &data_source.name,
+        location,
+        listing_options,
+        arrow_schema,
+        None,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_value_types() {
+        let values = |values: &[&str]| values.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            partition_value_type(&values(&["1", "-20"])),
+            DataType::Int64
+        );
+        assert_eq!(
+            partition_value_type(&values(&["true", "false"])),
+            DataType::Boolean
+        );
+        assert_eq!(
+            partition_value_type(&values(&["2024-01-01", "2024-12-31"])),
+            DataType::Date32
+        );
+        assert_eq!(partition_value_type(&values(&["1", "a"])), DataType::Utf8);
+        assert_eq!(partition_value_type(&values(&[])), DataType::Utf8);
+    }
+
+    #[test]
+    fn only_formats_read_whole_cached() {
+        assert!(is_read_whole(&DataSourceFormat::Csv));
+        assert!(is_read_whole(&DataSourceFormat::Json));
+        assert!(is_read_whole(&DataSourceFormat::NdJson));
+        assert!(!is_read_whole(&DataSourceFormat::Parquet));
+    }
+
+    #[tokio::test]
+    async fn discovered_partition_columns_typed() {
+        let dir = std::env::temp_dir().join(format!(
+            "datafusion-server-hive-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        for (date, region) in [
+            ("2024-01-01", "1"),
+            ("2024-01-02", "2"),
+            ("2024-01-02", "x"),
+        ] {
+            let partition = dir
+                .join(format!("date={date}"))
+                .join(format!("region={region}"));
+            std::fs::create_dir_all(&partition).unwrap();
+            std::fs::write(partition.join("part-0.csv"), "id\n1\n").unwrap();
+        }
+
+        let ctx = SessionContext::new();
+        let columns = partition_columns(
+            &ctx,
+            &DataSourceOption::new(),
+            &format!("{}/", dir.display()),
+            &CsvReadOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            columns,
+            vec![
+                ("date".to_string(), DataType::Date32),
+                ("region".to_string(), DataType::Utf8)
+            ]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,10 +1,19 @@
 // object_store/registry.rs
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
-use datafusion::execution::context::SessionContext;
-use object_store::DynObjectStore;
+use datafusion::{
+    error::DataFusionError,
+    execution::{
+        context::SessionContext,
+        object_store::{DefaultObjectStoreRegistry, ObjectStoreRegistry},
+    },
+};
+use object_store::{DynObjectStore, ObjectStore};
+use url::Url;
 
+use crate::data_source::object_store::credential_manager::azure_location;
 use crate::response::http_error::ResponseError;
 use crate::settings::Settings;
 
@@ -28,3 +37,49 @@ fn register_to_runtime_env(
 
     Ok(())
 }
+
+/// Object store registry of the session, the default registry is keyed by the scheme and host,
+/// which can not distinguish the containers of `abfss://container@account.dfs.core.windows.net`.
+#[derive(Debug, Default)]
+pub struct StoreRegistry {
+    inner: DefaultObjectStoreRegistry,
+    azure: RwLock<HashMap<(String, String), Arc<dyn ObjectStore>>>, // by account and container
+}
+
+impl ObjectStoreRegistry for StoreRegistry {
+    fn register_store(
+        &self,
+        url: &Url,
+        store: Arc<dyn ObjectStore>,
+    ) -> Option<Arc<dyn ObjectStore>> {
+        match azure_location(url) {
+            Some((Some(account), container)) => self
+                .azure
+                .write()
+                .unwrap()
+                .insert((account, container), store),
+            _ => self.inner.register_store(url, store),
+        }
+    }
+
+    fn get_store(&self, url: &Url) -> Result<Arc<dyn ObjectStore>, DataFusionError> {
+        let Some((account, container)) = azure_location(url) else {
+            return self.inner.get_store(url);
+        };
+
+        let stores = self.azure.read().unwrap();
+        let mut matched = stores.iter().filter(|((a, c), _)| {
+            *c == container && account.as_ref().is_none_or(|account| a == account)
+        });
+
+        match (matched.next(), matched.next()) {
+            (Some((_, store)), None) => Ok(store.clone()),
+            (Some(_), Some(_)) => Err(DataFusionError::Configuration(format!(
+                "Azure container '{container}' exists in multiple accounts, use `abfss://{container}@account.dfs.core.windows.net`"
+            ))),
+            _ => Err(DataFusionError::Configuration(format!(
+                "Object store '{url}' credential not configured"
+            ))),
+        }
+    }
+}
@@ -1,14 +1,190 @@
 // object_store/writer.rs
 
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[cfg(feature = "avro")]
+use arrow_avro::writer::AvroWriter;
 use datafusion::{
-    common::config::{CsvOptions, JsonOptions, ParquetOptions, TableParquetOptions},
-    dataframe::DataFrameWriteOptions,
-    execution::context::SessionContext,
+    arrow::json::{writer::JsonArray, Writer as JsonWriter},
+    common::{
+        config::{CsvOptions, JsonOptions, ParquetOptions, TableParquetOptions},
+        parsers::CompressionTypeVariant,
+    },
+    dataframe::{DataFrame, DataFrameWriteOptions},
+    execution::{context::SessionContext, object_store::ObjectStoreUrl},
 };
+use futures::{StreamExt, TryStreamExt};
+use object_store::{path::Path, DynObjectStore, PutPayload};
+use url::Url;
 
-use crate::request::body::{DataSource, DataSourceFormat, DataSourceOption};
+#[cfg(feature = "deltalake")]
+use crate::data_source::deltalake_writer;
+use crate::data_source::location::{
+    file,
+    uri::{self, SupportedScheme},
+};
+use crate::request::body::{DataSource, DataSourceFormat, DataSourceOption, SaveMode};
 use crate::response::http_error::ResponseError;
 
+/// Output file, or directory of multiple files by `partitionBy` or the location ending with `/`.
+struct Target {
+    url: Url,
+    store: Arc<DynObjectStore>,
+    path: Path,
+    directory: bool,
+}
+
+impl Target {
+    fn new(ctx: &SessionContext, location: &str, directory: bool) -> Result<Self, ResponseError> {
+        validate_location(location)?;
+
+        // local files are placed under the data directory
+        let url = if is_local(location)? {
+            let path = std::path::absolute(file::create_data_file_path(location)?)?;
+            if directory {
+                Url::from_directory_path(&path)
+            } else {
+                Url::from_file_path(&path)
+            }
+            .map_err(|()| {
+                ResponseError::request_validation(format!("Invalid file path: {}", path.display()))
+            })?
+        } else if directory {
+            Url::parse(&format!("{}/", location.trim_end_matches('/')))?
+        } else {
+            Url::parse(location)?
+        };
+
+        Self::with_url(ctx, url, directory)
+    }
+
+    fn with_url(ctx: &SessionContext, url: Url, directory: bool) -> Result<Self, ResponseError> {
+        let store = ctx
+            .runtime_env()
+            .object_store(ObjectStoreUrl::parse(&url[..url::Position::BeforePath])?)?;
+        let path = Path::from_url_path(url.path()).map_err(object_store::Error::from)?;
+
+        Ok(Self {
+            url,
+            store,
+            path,
+            directory,
+        })
+    }
+
+    /// Sibling directory of the target, the output is moved into the target after written.
+    fn staging(&self, ctx: &SessionContext) -> Result<Self, ResponseError> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        Self::with_url(
+            ctx,
+            Url::parse(&staging_url(self.url.as_str(), nanos))?,
+            true,
+        )
+    }
+
+    async fn exists(&self) -> Result<bool, ResponseError> {
+        if self.directory {
+            return Ok(self
+                .store
+                .list(Some(&self.path))
+                .next()
+                .await
+                .transpose()?
+                .is_some());
+        }
+
+        match self.store.head(&self.path).await {
+            Ok(_) => Ok(true),
+            Err(object_store::Error::NotFound { .. }) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn remove(&self) -> Result<(), ResponseError> {
+        let locations = self
+            .store
+            .list(Some(&self.path))
+            .map_ok(|meta| meta.location)
+            .boxed();
+
+        self.store
+            .delete_stream(locations)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        // local file system leaves the empty directories
+        if self.url.scheme() == "file" {
+            if let Ok(path) = self.url.to_file_path() {
+                match tokio::fs::remove_dir_all(&path).await {
+                    Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                    _ => {}
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the existing files of the directory with the written files of the staging.
+    async fn replace_with(&self, staging: &Self) -> Result<(), ResponseError> {
+        self.remove().await?;
+
+        let written = self
+            .store
+            .list(Some(&staging.path))
+            .map_ok(|meta| meta.location)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        for from in written {
+            let Some(parts) = from.prefix_match(&staging.path) else {
+                continue;
+            };
+            let to = self.path.parts().chain(parts).collect::<Path>();
+            self.store.rename(&from, &to).await?;
+        }
+
+        staging.remove().await
+    }
+
+    /// Checks the existing output by the save mode, returns the staging directory to write when
+    /// overwriting the existing directory. A single file is replaced by the writer itself.
+    async fn prepare(
+        &self,
+        ctx: &SessionContext,
+        mode: &SaveMode,
+        location: &str,
+    ) -> Result<Option<Self>, ResponseError> {
+        if !self.exists().await? {
+            return Ok(None);
+        }
+
+        match mode {
+            SaveMode::ErrorIfExists => Err(ResponseError::already_existing(format!(
+                "Already exists '{location}'"
+            ))),
+            SaveMode::Overwrite if self.directory => {
+                log::debug!("Write into staging to overwrite '{}'", self.url);
+                Ok(Some(self.staging(ctx)?))
+            }
+            SaveMode::Overwrite => Ok(None),
+            // new files are added into the directory
+            SaveMode::Append if self.directory => Ok(None),
+            SaveMode::Append => Err(ResponseError::request_validation(format!(
+                "Can not append to the file '{location}', use the directory location ending with '/'"
+            ))),
+            SaveMode::Upsert => Err(ResponseError::unsupported_type(
+                "Save mode 'upsert' is not supported for files",
+            )),
+        }
+    }
+}
+
 pub async fn write(ctx: &SessionContext, data_source: &DataSource) -> Result<(), ResponseError> {
     log::debug!("object_store::writer(): {data_source:?}");
 
@@ -17,38 +193,93 @@ pub async fn write(ctx: &SessionContext, data_source: &DataSource) -> Result<(),
         None => DataSourceOption::new(),
     };
 
-    let write_options = DataFrameWriteOptions::new()
-        // TODO: raise error when options.overwrite is true and already exists target file
-        // .with_overwrite(options.overwrite.unwrap_or(false))
-        .with_single_file_output(true);
-
     let df = ctx
         .sql(&format!("SELECT * FROM {}", data_source.name))
         .await?;
 
+    #[cfg(feature = "deltalake")]
+    if data_source.format == DataSourceFormat::Deltalake {
+        validate_location(&data_source.location)?;
+        return deltalake_writer::write(ctx, df, data_source, &options).await;
+    }
+
+    let mode = options.save_mode(SaveMode::ErrorIfExists);
+    let partition_by = options.partition_by.clone().unwrap_or_default();
+
+    let target = Target::new(
+        ctx,
+        &data_source.location,
+        is_directory(&data_source.location, &data_source.format, &partition_by),
+    )?;
+    let staging = target.prepare(ctx, &mode, &data_source.location).await?;
+
+    let result = write_format(
+        ctx,
+        df,
+        data_source,
+        &options,
+        staging.as_ref().unwrap_or(&target),
+        partition_by,
+    )
+    .await;
+
+    match staging {
+        Some(staging) if result.is_ok() => target.replace_with(&staging).await,
+        Some(staging) => {
+            if let Err(e) = staging.remove().await {
+                log::error!("Can not remove the staging '{}': {e:?}", staging.url);
+            }
+            result
+        }
+        None => result,
+    }
+}
+
+async fn write_format(
+    ctx: &SessionContext,
+    df: DataFrame,
+    data_source: &DataSource,
+    options: &DataSourceOption,
+    target: &Target,
+    partition_by: Vec<String>,
+) -> Result<(), ResponseError> {
+    let write_options = DataFrameWriteOptions::new()
+        .with_single_file_output(!target.directory)
+        .with_partition_by(partition_by.clone());
+
     match data_source.format {
         DataSourceFormat::Csv => {
-            let csv_options = CsvOptions::default()
+            let mut csv_options = CsvOptions::default()
                 .with_has_header(options.has_header.unwrap_or(true))
                 .with_delimiter(options.delimiter.unwrap_or(',') as u8);
+            csv_options.compression = compression(options)?;
 
-            df.write_csv(&data_source.location, write_options, Some(csv_options))
+            df.write_csv(target.url.as_str(), write_options, Some(csv_options))
                 .await?;
         }
         DataSourceFormat::NdJson => {
-            let ndjson_options = JsonOptions::default();
-            df.write_json(&data_source.location, write_options, Some(ndjson_options))
+            let ndjson_options = JsonOptions {
+                compression: compression(options)?,
+                ..Default::default()
+            };
+
+            df.write_json(target.url.as_str(), write_options, Some(ndjson_options))
                 .await?;
         }
         DataSourceFormat::Parquet => {
             let parquet_options = ParquetOptions {
-                compression: Some("snappy".to_string()),
+                compression: Some(
+                    options
+                        .compression
+                        .clone()
+                        .unwrap_or_else(|| "snappy".to_string()),
+                ),
                 created_by: format!("datafusion-server v{}", env!("CARGO_PKG_VERSION")),
                 ..Default::default()
             };
 
             df.write_parquet(
-                &data_source.location,
+                target.url.as_str(),
                 write_options,
                 Some(TableParquetOptions {
                     global: parquet_options,
@@ -57,6 +288,56 @@ pub async fn write(ctx: &SessionContext, data_source: &DataSource) -> Result<(),
             )
             .await?;
         }
+        DataSourceFormat::Arrow => {
+            uncompressed(options, &data_source.format)?;
+
+            // `DataFrame` has no writer of Arrow IPC file
+            let partitioned = if partition_by.is_empty() {
+                String::new()
+            } else {
+                format!(
+                    " PARTITIONED BY ({})",
+                    partition_by
+                        .iter()
+                        .map(|column| format!("\"{}\"", column.replace('"', "\"\"")))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            };
+
+            ctx.sql(&format!(
+                "COPY (SELECT * FROM {}) TO '{}' STORED AS ARROW{partitioned}",
+                data_source.name,
+                target.url.as_str().replace('\'', "''"),
+            ))
+            .await?
+            .collect()
+            .await?;
+        }
+        DataSourceFormat::Json => {
+            uncompressed(options, &data_source.format)?;
+            single_file(target, &data_source.format)?;
+
+            let mut writer = JsonWriter::<_, JsonArray>::new(Vec::<u8>::new());
+            writer.write_batches(&df.collect().await?.iter().collect::<Vec<_>>())?;
+            writer.finish()?;
+
+            put(target, writer.into_inner()).await?;
+        }
+        #[cfg(feature = "avro")]
+        DataSourceFormat::Avro => {
+            uncompressed(options, &data_source.format)?;
+            single_file(target, &data_source.format)?;
+
+            let mut writer = AvroWriter::new(Vec::<u8>::new(), df.schema().as_arrow().clone())?;
+            for batch in df.collect().await? {
+                writer.write(&batch)?;
+            }
+            writer.finish()?;
+
+            put(target, writer.into_inner()).await?;
+        }
+        #[allow(unreachable_patterns)]
         _ => {
             return Err(ResponseError::unsupported_type(format!(
                 "write to object store is not supported {:?}",
@@ -67,3 +348,186 @@ pub async fn write(ctx: &SessionContext, data_source: &DataSource) -> Result<(),
 
     Ok(())
 }
+
+// COPY of Arrow IPC writes a directory to the location without the file extension
+fn is_directory(location: &str, format: &DataSourceFormat, partition_by: &[String]) -> bool {
+    !partition_by.is_empty()
+        || location.ends_with('/')
+        || (*format == DataSourceFormat::Arrow
+            && std::path::Path::new(location.rsplit('/').next().unwrap_or_default())
+                .extension()
+                .is_none())
+}
+
+fn is_local(location: &str) -> Result<bool, ResponseError> {
+    Ok(
+        uri::scheme(&uri::to_parts(location)?).unwrap_or(SupportedScheme::File)
+            == SupportedScheme::File,
+    )
+}
+
+// the path relative to the data directory of local files, the raw path of the others
+// since `Url` resolves the dot segments
+fn validate_location(location: &str) -> Result<(), ResponseError> {
+    if is_local(location)? {
+        validate_path(&uri::to_file_path_and_name(location)?, location)
+    } else {
+        let parts = uri::to_parts(location)?;
+        validate_path(
+            parts.path_and_query.as_ref().map_or("", |pq| pq.path()),
+            location,
+        )
+    }
+}
+
+// rejects `..` escaping from the data directory or the prefix, and the root of the store
+pub(crate) fn validate_path(path: &str, location: &str) -> Result<(), ResponseError> {
+    let path = path.split('?').next().unwrap_or_default();
+
+    if path.split('/').any(|segment| {
+        matches!(
+            segment.to_lowercase().as_str(),
+            ".." | ".%2e" | "%2e." | "%2e%2e"
+        )
+    }) {
+        return Err(ResponseError::request_validation(format!(
+            "Can not access the location including '..': '{location}'"
+        )));
+    }
+
+    if path.trim_matches('/').is_empty() {
+        return Err(ResponseError::request_validation(format!(
+            "Can not access the root of the store: '{location}'"
+        )));
+    }
+
+    Ok(())
+}
+
+// e.g. `s3://bucket/dir/.out.1700000000.staging/` of `s3://bucket/dir/out/`
+fn staging_url(url: &str, nanos: u128) -> String {
+    let url = url.trim_end_matches('/');
+    let (parent, name) = url.rsplit_once('/').unwrap_or(("", url));
+    format!("{parent}/.{name}.{nanos}.staging/")
+}
+
+// `gzip`, `bzip2`, `xz` or `zstd`, uncompressed by default
+fn compression(options: &DataSourceOption) -> Result<CompressionTypeVariant, ResponseError> {
+    match &options.compression {
+        Some(compression) => CompressionTypeVariant::from_str(compression).map_err(|_| {
+            ResponseError::unsupported_format(format!("Unsupported compression '{compression}'"))
+        }),
+        None => Ok(CompressionTypeVariant::UNCOMPRESSED),
+    }
+}
+
+fn uncompressed(
+    options: &DataSourceOption,
+    format: &DataSourceFormat,
+) -> Result<(), ResponseError> {
+    if options.compression.is_some() {
+        return Err(ResponseError::unsupported_format(format!(
+            "Compression is not supported for '{}' format",
+            format.to_str()
+        )));
+    }
+
+    Ok(())
+}
+
+fn single_file(target: &Target, format: &DataSourceFormat) -> Result<(), ResponseError> {
+    if target.directory {
+        return Err(ResponseError::unsupported_format(format!(
+            "Partitioned output is not supported for '{}' format",
+            format.to_str()
+        )));
+    }
+
+    Ok(())
+}
+
+async fn put(target: &Target, data: Vec<u8>) -> Result<(), ResponseError> {
+    target
+        .store
+        .put(&target.path, PutPayload::from(data))
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data_source::object_store::writer::{
+        is_directory, staging_url, validate_location, validate_path,
+    };
+    use crate::request::body::DataSourceFormat;
+
+    #[test]
+    fn reject_parent_and_root() {
+        assert!(validate_path("dir/out.csv", "file:///dir/out.csv").is_ok());
+        assert!(validate_path("../..", "file:///../..").is_err());
+        assert!(validate_path("/dir/../../out/", "s3://bucket/dir/../../out/").is_err());
+        assert!(validate_path("/dir/%2E%2e/out/", "s3://bucket/dir/%2E%2e/out/").is_err());
+        assert!(validate_path("", "file:///").is_err());
+        assert!(validate_path("/", "s3://bucket/").is_err());
+        assert!(validate_path("/?versionId=1", "s3://bucket/?versionId=1").is_err());
+    }
+
+    // checked before dispatching to the writers without `Target`, e.g. Delta Lake
+    #[test]
+    fn reject_location_of_parent_and_root() {
+        assert!(validate_location("file:///delta/sales").is_ok());
+        assert!(validate_location("s3://bucket/delta/sales").is_ok());
+        assert!(validate_location("file:///../../x").is_err());
+        assert!(validate_location("s3://bucket/delta/../../x").is_err());
+        assert!(validate_location("file:///").is_err());
+        assert!(validate_location("s3://bucket").is_err());
+    }
+
+    #[cfg(feature = "deltalake")]
+    #[tokio::test]
+    async fn reject_deltalake_outside_data_dir() {
+        use crate::data_source::object_store::writer::write;
+        use crate::request::body::DataSource;
+
+        let ctx = datafusion::prelude::SessionContext::new();
+        ctx.sql("CREATE TABLE sales AS VALUES (1, 'a')")
+            .await
+            .unwrap();
+
+        for location in ["file:///../../sales", "file:///"] {
+            let data_source = DataSource::new(DataSourceFormat::Deltalake, "sales", Some(location));
+            let e = write(&ctx, &data_source).await.unwrap_err();
+            assert!(e.to_string().contains("Can not access"), "{e}");
+        }
+    }
+
+    #[test]
+    fn arrow_without_extension_is_directory() {
+        let none = Vec::<String>::new();
+        assert!(is_directory("file:///out", &DataSourceFormat::Arrow, &none));
+        assert!(!is_directory(
+            "file:///out.arrow",
+            &DataSourceFormat::Arrow,
+            &none
+        ));
+        assert!(!is_directory("file:///out", &DataSourceFormat::Csv, &none));
+        assert!(is_directory("file:///out/", &DataSourceFormat::Csv, &none));
+        assert!(is_directory(
+            "file:///out.csv",
+            &DataSourceFormat::Csv,
+            &["year".to_string()]
+        ));
+    }
+
+    #[test]
+    fn staging_is_sibling() {
+        assert_eq!(
+            staging_url("s3://bucket/dir/out/", 1),
+            "s3://bucket/dir/.out.1.staging/"
+        );
+        assert_eq!(
+            staging_url("file:///data/out/", 2),
+            "file:///data/.out.2.staging/"
+        );
+    }
+}
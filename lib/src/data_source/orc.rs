@@ -0,0 +1,50 @@
+// orc.rs - ORC file from HTTP response or uploaded bytes
+
+use std::sync::Arc;
+
+#[cfg(feature = "plugin")]
+use datafusion::arrow::{error::ArrowError, record_batch::RecordBatch};
+use datafusion::{execution::context::SessionContext, execution::object_store::ObjectStoreUrl};
+use object_store::{memory::InMemory, path::Path, PutPayload};
+#[cfg(feature = "plugin")]
+use orc_rust::ArrowReaderBuilder;
+
+use crate::data_source::object_store::reader;
+use crate::request::body::DataSource;
+use crate::response::http_error::ResponseError;
+
+// in-memory store of the session holds the downloaded and uploaded files
+const MEMORY_STORE: &str = "memory://orc";
+
+/// Places the ORC file into the in-memory store of the session and registers it as the listing
+/// table, the stripes are decoded lazily with the projection and limit of each query.
+pub async fn register_bytes(
+    ctx: &SessionContext,
+    data_source: &DataSource,
+    data: bytes::Bytes,
+) -> Result<(), ResponseError> {
+    let store_url = ObjectStoreUrl::parse(MEMORY_STORE)?;
+    let store = if let Ok(store) = ctx.runtime_env().object_store(&store_url) {
+        store
+    } else {
+        let store = Arc::new(InMemory::new());
+        ctx.runtime_env()
+            .register_object_store(store_url.as_ref(), store.clone());
+        store
+    };
+
+    let path = Path::from(format!("{}.orc", data_source.name));
+    store.put(&path, PutPayload::from(data)).await?;
+
+    reader::register_orc(ctx, data_source, &format!("{MEMORY_STORE}/{path}")).await
+}
+
+/// Decodes the whole ORC file eagerly, used for the bytes returned by connector plugins.
+#[cfg(feature = "plugin")]
+pub fn from_bytes_to_record_batch(data: bytes::Bytes) -> Result<Vec<RecordBatch>, ResponseError> {
+    let reader = ArrowReaderBuilder::try_new(data)
+        .map_err(ResponseError::orc_deserialization)?
+        .build();
+    let batches: Result<Vec<RecordBatch>, ArrowError> = reader.collect();
+    Ok(batches?)
+}
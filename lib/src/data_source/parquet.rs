@@ -15,12 +15,7 @@ pub async fn from_response_to_record_batch(
     uri: &str,
     options: &DataSourceOption,
 ) -> Result<Vec<RecordBatch>, ResponseError> {
-    from_bytes_to_record_batch(
-        match http::get(uri, options, http::ResponseDataType::Binary).await? {
-            http::ResponseData::Binary(data) => data,
-            http::ResponseData::Text(_) => bytes::Bytes::new(),
-        },
-    )
+    from_bytes_to_record_batch(http::get_bytes(uri, options).await?)
 }
 
 pub fn from_bytes_to_record_batch(data: bytes::Bytes) -> Result<Vec<RecordBatch>, ResponseError> {
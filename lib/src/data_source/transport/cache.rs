@@ -0,0 +1,560 @@
+// transport/cache.rs: On-disk cache of remote data sources validated by ETag / Last-Modified
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+use chrono::Utc;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex, sync::OnceCell};
+
+use crate::response::http_error::ResponseError;
+use crate::settings::Settings;
+
+const META_FILE_NAME: &str = "meta.json";
+// the directory is managed by the cache only if marked, other files are never removed
+const MARKER_FILE_NAME: &str = ".datafusion-server-cache";
+const PARTIAL_EXTENSION: &str = "partial";
+// hard links of cached files referenced by the tables of sessions
+const SESSIONS_DIR_NAME: &str = "sessions";
+
+static DATA_CACHE: OnceCell<Option<DataCache>> = OnceCell::const_new();
+
+/// Returns the global data cache, `None` if disabled by configuration.
+pub async fn global() -> Result<Option<&'static DataCache>, ResponseError> {
+    Ok(DATA_CACHE
+        .get_or_try_init(|| async {
+            let config = &Settings::global().cache;
+            if config.enabled {
+                DataCache::open(Path::new(&config.dir), config.max_size as u64 * 1024 * 1024)
+                    .await
+                    .map(Some)
+            } else {
+                Ok(None)
+            }
+        })
+        .await?
+        .as_ref())
+}
+
+/// Removes the cached files linked to the tables of the session.
+pub async fn release(session_id: &str) {
+    if let Some(Some(cache)) = DATA_CACHE.get() {
+        cache.release(session_id).await;
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>, // HTTP-date (RFC 2822)
+}
+
+/// Identifies the cached response by the URL and the variant of the request, responses fetched
+/// with different headers or credentials are cached separately.
+#[derive(Clone, Debug)]
+pub struct CacheKey {
+    url: String,
+    variant: String,
+}
+
+impl CacheKey {
+    pub fn new(
+        url: &str,
+        headers: Option<&HashMap<String, String>>,
+        credential: Option<&str>,
+    ) -> Self {
+        let mut parts = headers
+            .iter()
+            .flat_map(|headers| headers.iter())
+            .map(|(name, value)| format!("{}:{value}", name.to_lowercase()))
+            .collect::<Vec<_>>();
+        parts.sort();
+
+        if let Some(credential) = credential {
+            parts.push(format!("credential={credential}"));
+        }
+
+        // header values may contain secrets, keeps only the hash of them
+        Self {
+            url: url.to_string(),
+            variant: if parts.is_empty() {
+                String::new()
+            } else {
+                fnv1a(&parts.join("\n"))
+            },
+        }
+    }
+
+    fn id(&self) -> String {
+        fnv1a(&format!("{}\n{}", self.url, self.variant))
+    }
+
+    fn matches(&self, entry: &CacheEntry) -> bool {
+        entry.url == self.url && entry.variant == self.variant
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct CacheEntry {
+    url: String,
+    #[serde(default)]
+    variant: String,
+    file_name: String,
+    etag: Option<String>,
+    #[serde(rename = "lastModified")]
+    last_modified: Option<String>,
+    size: u64,
+    #[serde(rename = "accessedAt")]
+    accessed_at: i64,
+}
+
+pub struct DataCache {
+    dir: PathBuf,
+    max_size: u64,
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl DataCache {
+    async fn open(dir: &Path, max_size: u64) -> Result<Self, ResponseError> {
+        fs::create_dir_all(dir).await?;
+        Self::mark(dir).await?;
+
+        // tables of sessions are not restored after restart
+        let sessions_dir = dir.join(SESSIONS_DIR_NAME);
+        if fs::try_exists(&sessions_dir).await? {
+            fs::remove_dir_all(&sessions_dir).await?;
+        }
+
+        let mut entries = HashMap::new();
+        let mut read_dir = fs::read_dir(dir).await?;
+
+        while let Some(dir_entry) = read_dir.next_entry().await? {
+            let path = dir_entry.path();
+            let Some(id) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+
+            if !dir_entry.file_type().await?.is_dir() {
+                // orphaned partial download
+                if path.extension().and_then(|ext| ext.to_str()) == Some(PARTIAL_EXTENSION) {
+                    fs::remove_file(&path).await?;
+                }
+                continue;
+            }
+
+            let meta_path = path.join(META_FILE_NAME);
+            if !fs::try_exists(&meta_path).await? {
+                log::warn!(
+                    "Skipped unknown directory in data cache: {}",
+                    path.display()
+                );
+                continue;
+            }
+
+            match fs::read(&meta_path)
+                .await
+                .ok()
+                .and_then(|meta| serde_json::from_slice::<CacheEntry>(&meta).ok())
+            {
+                Some(entry) if fs::try_exists(path.join(&entry.file_name)).await? => {
+                    entries.insert(id.to_string(), entry);
+                }
+                _ => fs::remove_dir_all(&path).await?,
+            }
+        }
+
+        log::debug!(
+            "Opened data cache {}: {} entries",
+            dir.display(),
+            entries.len()
+        );
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            max_size,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Marks the empty directory as the cache, refuses the directory not marked with the other files.
+    async fn mark(dir: &Path) -> Result<(), ResponseError> {
+        if fs::try_exists(dir.join(MARKER_FILE_NAME)).await? {
+            return Ok(());
+        }
+
+        if fs::read_dir(dir).await?.next_entry().await?.is_some() {
+            return Err(ResponseError::internal_server_error(format!(
+                "Data cache directory {} is not empty and not created by the cache, \
+                 specify an empty directory",
+                dir.display()
+            )));
+        }
+
+        fs::write(dir.join(MARKER_FILE_NAME), b"").await?;
+        Ok(())
+    }
+
+    /// Validators of the cached entry to send as a conditional request.
+    pub async fn validators(&self, key: &CacheKey) -> Option<Validators> {
+        let entries = self.entries.lock().await;
+        entries
+            .get(&key.id())
+            .filter(|entry| key.matches(entry))
+            .map(|entry| Validators {
+                etag: entry.etag.clone(),
+                last_modified: entry.last_modified.clone(),
+            })
+    }
+
+    /// Reads the cached entry, also marks it as recently used.
+    pub async fn read(&self, key: &CacheKey) -> Result<Option<Bytes>, ResponseError> {
+        let mut entries = self.entries.lock().await;
+
+        let Some(path) = self.touch(&mut entries, key).await else {
+            return Ok(None);
+        };
+
+        Ok(Some(Bytes::from(fs::read(path).await?)))
+    }
+
+    /// Links the cached entry into the directory of the session, the linked file is kept while
+    /// the session is alive even if the entry is evicted or replaced by a newer response.
+    pub async fn checkout(
+        &self,
+        key: &CacheKey,
+        session_id: &str,
+    ) -> Result<Option<PathBuf>, ResponseError> {
+        let mut entries = self.entries.lock().await;
+
+        let Some(path) = self.touch(&mut entries, key).await else {
+            return Ok(None);
+        };
+
+        let link_dir = self
+            .dir
+            .join(SESSIONS_DIR_NAME)
+            .join(session_id)
+            .join(format!(
+                "{}.{}",
+                key.id(),
+                Utc::now().timestamp_nanos_opt().unwrap_or_default()
+            ));
+        fs::create_dir_all(&link_dir).await?;
+
+        let link_path = link_dir.join(path.file_name().unwrap_or_default());
+        if fs::hard_link(&path, &link_path).await.is_err() {
+            fs::copy(&path, &link_path).await?;
+        }
+
+        Ok(Some(link_path))
+    }
+
+    pub fn can_store(&self, size: u64) -> bool {
+        size <= self.max_size
+    }
+
+    pub async fn store(
+        &self,
+        key: &CacheKey,
+        validators: Validators,
+        data: &[u8],
+    ) -> Result<(), ResponseError> {
+        if !self.can_store(data.len() as u64) {
+            return Ok(());
+        }
+
+        let partial_path = self.partial_path(key);
+        if let Err(e) = fs::write(&partial_path, data).await {
+            let _ = fs::remove_file(&partial_path).await;
+            return Err(e.into());
+        }
+
+        self.commit(key, validators, &partial_path).await
+    }
+
+    /// Downloads the stream into the temporary file and moves it into the cache, the partial
+    /// file is removed if the download fails.
+    pub async fn store_stream<S, E>(
+        &self,
+        key: &CacheKey,
+        validators: Validators,
+        mut stream: S,
+    ) -> Result<(), ResponseError>
+    where
+        S: Stream<Item = Result<Bytes, E>> + Unpin,
+        ResponseError: From<E>,
+    {
+        let partial_path = self.partial_path(key);
+
+        let download = async {
+            let mut file = fs::File::create(&partial_path).await?;
+            while let Some(chunk) = stream.next().await {
+                file.write_all(&chunk?).await?;
+            }
+            file.flush().await?;
+            Ok::<_, ResponseError>(())
+        };
+
+        if let Err(e) = download.await {
+            let _ = fs::remove_file(&partial_path).await;
+            return Err(e);
+        }
+
+        self.commit(key, validators, &partial_path).await
+    }
+
+    /// Removes the files linked to the session by `checkout()`.
+    pub async fn release(&self, session_id: &str) {
+        let session_dir = self.dir.join(SESSIONS_DIR_NAME).join(session_id);
+
+        if let Ok(true) = fs::try_exists(&session_dir).await {
+            if let Err(e) = fs::remove_dir_all(&session_dir).await {
+                log::warn!("Can not remove cached files of session {session_id}: {e}");
+            }
+        }
+    }
+
+    // temporary file path to download into before `commit()`
+    fn partial_path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!(
+            "{}.{}.{PARTIAL_EXTENSION}",
+            key.id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ))
+    }
+
+    // path of the matched entry, updates the last accessed time
+    async fn touch(
+        &self,
+        entries: &mut HashMap<String, CacheEntry>,
+        key: &CacheKey,
+    ) -> Option<PathBuf> {
+        let id = key.id();
+        let entry = entries.get_mut(&id).filter(|entry| key.matches(entry))?;
+        entry.accessed_at = Utc::now().timestamp_millis();
+
+        let entry_dir = self.dir.join(&id);
+        if let Err(e) = Self::write_meta(&entry_dir, entry).await {
+            log::warn!("Can not update cache entry of '{}': {e}", key.url);
+        }
+
+        Some(entry_dir.join(&entry.file_name))
+    }
+
+    /// Moves downloaded file into the cache, evicts least recently used entries to keep limit size.
+    async fn commit(
+        &self,
+        key: &CacheKey,
+        validators: Validators,
+        partial_path: &Path,
+    ) -> Result<(), ResponseError> {
+        let size = fs::metadata(partial_path).await?.len();
+
+        if (validators.etag.is_none() && validators.last_modified.is_none())
+            || !self.can_store(size)
+        {
+            log::debug!("Not cacheable '{}': size={size}, {validators:?}", key.url);
+            fs::remove_file(partial_path).await?;
+            return Ok(());
+        }
+
+        let id = key.id();
+        let mut entries = self.entries.lock().await;
+
+        if entries.contains_key(&id) {
+            self.evict(&mut entries, &id).await?;
+        }
+
+        let mut total_size: u64 = entries.values().map(|entry| entry.size).sum();
+
+        while total_size + size > self.max_size {
+            let Some(lru_id) = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.accessed_at)
+                .map(|(id, _)| id.clone())
+            else {
+                break;
+            };
+
+            total_size -= entries.get(&lru_id).map_or(0, |entry| entry.size);
+            self.evict(&mut entries, &lru_id).await?;
+        }
+
+        let entry = CacheEntry {
+            url: key.url.clone(),
+            variant: key.variant.clone(),
+            file_name: Self::file_name(&key.url),
+            etag: validators.etag,
+            last_modified: validators.last_modified,
+            size,
+            accessed_at: Utc::now().timestamp_millis(),
+        };
+
+        let entry_dir = self.dir.join(&id);
+        fs::create_dir_all(&entry_dir).await?;
+
+        // the directory with the metadata is recognized as the entry after restart
+        Self::write_meta(&entry_dir, &entry).await?;
+        let path = entry_dir.join(&entry.file_name);
+        fs::rename(partial_path, &path).await?;
+
+        log::debug!("Cached '{}' into {}", key.url, path.display());
+
+        entries.insert(id, entry);
+
+        Ok(())
+    }
+
+    async fn evict(
+        &self,
+        entries: &mut HashMap<String, CacheEntry>,
+        id: &str,
+    ) -> Result<(), ResponseError> {
+        if let Some(entry) = entries.remove(id) {
+            log::debug!("Evicting cached '{}'", entry.url);
+
+            let entry_dir = self.dir.join(id);
+            if fs::try_exists(&entry_dir).await? {
+                fs::remove_dir_all(entry_dir).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn write_meta(entry_dir: &Path, entry: &CacheEntry) -> Result<(), ResponseError> {
+        fs::write(entry_dir.join(META_FILE_NAME), serde_json::to_vec(entry)?).await?;
+        Ok(())
+    }
+
+    // keeps original file name, DataFusion filters listing files by its extension
+    fn file_name(url: &str) -> String {
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        match path.rsplit('/').next() {
+            Some(name) if !name.is_empty() && name != META_FILE_NAME => name.to_string(),
+            _ => "data".to_string(),
+        }
+    }
+}
+
+// FNV-1a, stable across process restarts
+fn fnv1a(value: &str) -> String {
+    let hash = value.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::data_source::transport::cache::{CacheKey, DataCache, Validators};
+
+    #[test]
+    fn same_request_same_key() {
+        let headers = HashMap::from([("Accept".to_string(), "text/csv".to_string())]);
+        let a = CacheKey::new("https://example.com/a.csv", Some(&headers), Some("api"));
+        let b = CacheKey::new("https://example.com/a.csv", Some(&headers), Some("api"));
+        assert_eq!(a.id(), b.id());
+    }
+
+    #[test]
+    fn different_headers_different_key() {
+        let url = "https://example.com/a.csv";
+        let alice = HashMap::from([("Authorization".to_string(), "Bearer alice".to_string())]);
+        let bob = HashMap::from([("Authorization".to_string(), "Bearer bob".to_string())]);
+
+        let plain = CacheKey::new(url, None, None);
+        let alice = CacheKey::new(url, Some(&alice), None);
+        let bob = CacheKey::new(url, Some(&bob), None);
+
+        assert_ne!(plain.id(), alice.id());
+        assert_ne!(alice.id(), bob.id());
+        assert!(!alice.variant.contains("alice"));
+    }
+
+    #[test]
+    fn different_credential_different_key() {
+        let url = "https://example.com/a.csv";
+        assert_ne!(
+            CacheKey::new(url, None, Some("a")).id(),
+            CacheKey::new(url, None, Some("b")).id()
+        );
+    }
+
+    #[tokio::test]
+    async fn linked_file_survives_replacement() {
+        let dir = std::env::temp_dir().join(format!(
+            "datafusion-server-cache-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let cache = DataCache::open(&dir, 1024).await.unwrap();
+        let key = CacheKey::new("s3://bucket/a.csv", None, None);
+        let validators = || Validators {
+            etag: Some("v".to_string()),
+            last_modified: None,
+        };
+
+        cache
+            .store(&key, validators(), b"a,b\n1,2\n")
+            .await
+            .unwrap();
+        let linked = cache.checkout(&key, "session").await.unwrap().unwrap();
+
+        cache
+            .store(&key, validators(), b"a,b\n3,4\n")
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&linked).unwrap(), b"a,b\n1,2\n");
+        assert_eq!(
+            cache.read(&key).await.unwrap().unwrap().as_ref(),
+            b"a,b\n3,4\n"
+        );
+
+        cache.release("session").await;
+        assert!(!linked.exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_own_files_removed() {
+        let dir = std::env::temp_dir().join(format!(
+            "datafusion-server-cache-{}",
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        std::fs::create_dir_all(dir.join("photos")).unwrap();
+        std::fs::write(dir.join("notes.txt"), b"keep").unwrap();
+        assert!(DataCache::open(&dir, 1024).await.is_err());
+        assert!(dir.join("notes.txt").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        DataCache::open(&dir, 1024).await.unwrap();
+        std::fs::create_dir_all(dir.join("photos")).unwrap();
+        std::fs::write(dir.join("notes.txt"), b"keep").unwrap();
+        std::fs::write(dir.join("0123.456.partial"), b"a,b\n").unwrap();
+
+        DataCache::open(&dir, 1024).await.unwrap();
+        assert!(dir.join("photos").exists());
+        assert!(dir.join("notes.txt").exists());
+        assert!(!dir.join("0123.456.partial").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn file_name_of_url() {
+        assert_eq!(
+            DataCache::file_name("https://example.com/path/a.parquet?v=1"),
+            "a.parquet"
+        );
+        assert_eq!(DataCache::file_name("https://example.com/"), "data");
+        assert_eq!(
+            DataCache::file_name("https://example.com/meta.json"),
+            "data"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+// transport/credential_manager.rs: Named credential profiles for REST data sources
+
+use std::collections::{hash_map::Entry, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+use crate::data_source::transport::http;
+use crate::response::http_error::ResponseError;
+use crate::settings::{Credential, CredentialOAuth2};
+
+// renews access token a little before actual expiration
+const TOKEN_EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+#[derive(Error, Debug)]
+pub enum InvalidCredential {
+    #[error("Duplicated credential profile '{0}'")]
+    DuplicatedProfile(String),
+    #[error("Empty token endpoint in credential profile '{0}'")]
+    EmptyTokenUrl(String),
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+struct AccessToken {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl AccessToken {
+    fn is_valid(&self) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| Instant::now() + TOKEN_EXPIRY_MARGIN < expires_at)
+    }
+}
+
+pub struct OAuth2Client {
+    config: CredentialOAuth2,
+    token: Mutex<Option<AccessToken>>,
+}
+
+impl OAuth2Client {
+    fn new(config: &CredentialOAuth2) -> Self {
+        Self {
+            config: config.clone(),
+            token: Mutex::new(None),
+        }
+    }
+
+    async fn access_token(&self) -> Result<String, ResponseError> {
+        let mut token = self.token.lock().await;
+
+        if let Some(token) = token.as_ref().filter(|token| token.is_valid()) {
+            return Ok(token.value.clone());
+        }
+
+        log::debug!(
+            "Requesting OAuth2 access token for credential profile '{}'",
+            self.config.name
+        );
+
+        let renewed = self.request_token().await?;
+        let value = renewed.value.clone();
+        *token = Some(renewed);
+
+        Ok(value)
+    }
+
+    async fn invalidate(&self) {
+        *self.token.lock().await = None;
+    }
+
+    async fn request_token(&self) -> Result<AccessToken, ResponseError> {
+        let mut form = vec![
+            ("grant_type", "client_credentials".to_string()),
+            ("client_id", self.config.client_id.clone()),
+            ("client_secret", self.config.client_secret.clone()),
+        ];
+
+        if let Some(scopes) = &self.config.scopes {
+            form.push(("scope", scopes.join(" ")));
+        }
+
+        if let Some(audience) = &self.config.audience {
+            form.push(("audience", audience.clone()));
+        }
+
+        let response = http::client()?
+            .post(&self.config.token_url)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| {
+                ResponseError::http_authorization(format!(
+                    "Can not reach token endpoint of credential profile '{}': {e}",
+                    self.config.name
+                ))
+            })?;
+
+        if !response.status().is_success() {
+            return Err(ResponseError::http_authorization(format!(
+                "Token endpoint of credential profile '{}' responded {}",
+                self.config.name,
+                response.status()
+            )));
+        }
+
+        let token: TokenResponse = response.json().await.map_err(|e| {
+            ResponseError::http_authorization(format!(
+                "Unrecognized token response of credential profile '{}': {e}",
+                self.config.name
+            ))
+        })?;
+
+        Ok(AccessToken {
+            value: token.access_token,
+            expires_at: token
+                .expires_in
+                .map(|seconds| Instant::now() + Duration::from_secs(seconds)),
+        })
+    }
+}
+
+pub enum CredentialProfile {
+    Basic {
+        user: String,
+        password: Option<String>,
+    },
+    Bearer {
+        token: String,
+    },
+    OAuth2(OAuth2Client),
+}
+
+impl CredentialProfile {
+    /// Attaches the authorization of this profile to the outgoing request.
+    pub async fn authorize(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<RequestBuilder, ResponseError> {
+        Ok(match self {
+            CredentialProfile::Basic { user, password } => {
+                request.basic_auth(user, password.as_ref())
+            }
+            CredentialProfile::Bearer { token } => request.bearer_auth(token),
+            CredentialProfile::OAuth2(client) => request.bearer_auth(client.access_token().await?),
+        })
+    }
+
+    /// Discards a cached access token, e.g. the server rejected it before its expiration.
+    pub async fn invalidate(&self) {
+        if let CredentialProfile::OAuth2(client) = self {
+            client.invalidate().await;
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct HttpCredentialManager {
+    pub profiles: HashMap<String, Arc<CredentialProfile>>,
+}
+
+impl HttpCredentialManager {
+    pub fn new_with_config(
+        credential_settings: Option<&Vec<Credential>>,
+    ) -> Result<Self, InvalidCredential> {
+        let mut profiles: HashMap<String, Arc<CredentialProfile>> = HashMap::new();
+
+        if let Some(credentials) = credential_settings {
+            for credential in credentials {
+                let profile = match credential {
+                    Credential::Basic(basic) => CredentialProfile::Basic {
+                        user: basic.user.clone(),
+                        password: basic.password.clone(),
+                    },
+                    Credential::Bearer(bearer) => CredentialProfile::Bearer {
+                        token: bearer.token.clone(),
+                    },
+                    Credential::OAuth2(oauth2) => {
+                        if oauth2.token_url.is_empty() {
+                            return Err(InvalidCredential::EmptyTokenUrl(oauth2.name.clone()));
+                        }
+                        CredentialProfile::OAuth2(OAuth2Client::new(oauth2))
+                    }
+                };
+
+                log::debug!("Create '{}' http credential profile", credential.name());
+
+                if let Entry::Vacant(entry) = profiles.entry(credential.name().to_string()) {
+                    entry.insert(Arc::new(profile));
+                } else {
+                    return Err(InvalidCredential::DuplicatedProfile(
+                        credential.name().to_string(),
+                    ));
+                }
+            }
+        }
+
+        Ok(Self { profiles })
+    }
+
+    pub fn profile(&self, name: &str) -> Result<Arc<CredentialProfile>, ResponseError> {
+        self.profiles.get(name).cloned().ok_or_else(|| {
+            ResponseError::request_validation(format!("Credential profile '{name}' not configured"))
+        })
+    }
+}
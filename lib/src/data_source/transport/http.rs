@@ -2,43 +2,240 @@
 
 use std::collections::HashMap;
 use std::str::FromStr;
+use std::time::Duration;
 
+use once_cell::sync::OnceCell;
 use reqwest::{
-    header::{HeaderMap, HeaderName, HeaderValue},
-    Client,
+    header::{
+        HeaderMap, HeaderName, HeaderValue, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+        RETRY_AFTER,
+    },
+    Certificate, Client, Proxy, Response, StatusCode,
 };
 
+use crate::data_source::transport::cache::{self, CacheKey, Validators};
 use crate::request::body::DataSourceOption;
 use crate::response::http_error::ResponseError;
+use crate::settings::{self, Settings};
 
-pub enum ResponseDataType {
-    Text,
-    Binary,
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+static HTTP_CLIENT: OnceCell<Client> = OnceCell::new();
+
+/// Shared http client for remote data sources, keeps connections alive between requests.
+pub fn client() -> Result<&'static Client, ResponseError> {
+    HTTP_CLIENT.get_or_try_init(|| build_client(&Settings::global().http_client))
 }
 
-pub enum ResponseData {
-    Text(String),
-    Binary(bytes::Bytes),
+fn build_client(config: &settings::HttpClient) -> Result<Client, ResponseError> {
+    let mut builder = Client::builder()
+        .user_agent(format!("datafusion-server/{}", env!("CARGO_PKG_VERSION")))
+        .connect_timeout(Duration::from_secs(config.connect_timeout))
+        .timeout(Duration::from_secs(config.timeout));
+
+    if let Some(proxy) = &config.proxy {
+        builder = builder.proxy(Proxy::all(proxy).map_err(|e| {
+            ResponseError::internal_server_error(format!("Invalid http proxy '{proxy}': {e}"))
+        })?);
+    }
+
+    if let Some(ca_bundle) = &config.ca_bundle {
+        let pem = std::fs::read(ca_bundle)?;
+        for certificate in Certificate::from_pem_bundle(&pem).map_err(|e| {
+            ResponseError::internal_server_error(format!("Invalid CA bundle '{ca_bundle}': {e}"))
+        })? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    builder.build().map_err(|e| {
+        ResponseError::internal_server_error(format!("Can not initialize http client: {e}"))
+    })
 }
 
-pub async fn get(
+pub async fn get_bytes(
     uri: &str,
     options: &DataSourceOption,
-    data_type: ResponseDataType,
-) -> Result<ResponseData, ResponseError> {
-    let response = Client::new()
-        .get(uri)
-        .headers(to_header_map(options.headers.as_ref())?)
-        .send()
-        .await
-        .map_err(ResponseError::http_request)?;
+) -> Result<bytes::Bytes, ResponseError> {
+    let Some(cache) = cache::global().await? else {
+        let response = send(uri, options, None).await?;
+        return read_body(uri, response).await;
+    };
+
+    let key = CacheKey::new(uri, options.headers.as_ref(), options.credential.as_deref());
+    let validators = cache.validators(&key).await;
+    let response = send(uri, options, validators.as_ref()).await?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        if let Some(data) = cache.read(&key).await? {
+            log::debug!("Not modified '{uri}', using cached data");
+            return Ok(data);
+        }
+        // evicted while requesting, fetches again without validators
+        let response = send(uri, options, None).await?;
+        return read_body(uri, response).await;
+    }
+
+    let validators = Validators {
+        etag: header_value(&response, ETAG),
+        last_modified: header_value(&response, LAST_MODIFIED),
+    };
+    let data = read_body(uri, response).await?;
 
-    Ok(match data_type {
-        ResponseDataType::Text => ResponseData::Text(response.text().await?),
-        ResponseDataType::Binary => ResponseData::Binary(response.bytes().await?),
+    if let Err(e) = cache.store(&key, validators, &data).await {
+        log::warn!("Can not cache response of '{uri}': {e}");
+    }
+
+    Ok(data)
+}
+
+pub async fn get_text(uri: &str, options: &DataSourceOption) -> Result<String, ResponseError> {
+    String::from_utf8(get_bytes(uri, options).await?.to_vec()).map_err(|e| {
+        ResponseError::unsupported_format(format!("Response of '{uri}' is not UTF-8 text: {e}"))
     })
 }
 
+async fn send(
+    uri: &str,
+    options: &DataSourceOption,
+    validators: Option<&Validators>,
+) -> Result<Response, ResponseError> {
+    let config = &Settings::global().http_client;
+    let mut headers = to_header_map(options.headers.as_ref())?;
+
+    if let Some(validators) = validators {
+        if let Some(etag) = validators.etag.as_ref().and_then(|v| v.parse().ok()) {
+            headers.insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = validators
+            .last_modified
+            .as_ref()
+            .and_then(|v| v.parse().ok())
+        {
+            headers.insert(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let profile = if let Some(name) = &options.credential {
+        Some(Settings::global().http_credential_manager.profile(name)?)
+    } else {
+        None
+    };
+
+    let mut attempt = 0;
+    let mut reauthorized = false;
+
+    loop {
+        let mut request = client()?.get(uri).headers(headers.clone());
+        if let Some(profile) = &profile {
+            request = profile.authorize(request).await?;
+        }
+
+        let delay = match request.send().await {
+            Ok(response) => {
+                let status = response.status();
+
+                if status.is_success()
+                    || (status == StatusCode::NOT_MODIFIED && validators.is_some())
+                {
+                    return Ok(response);
+                }
+
+                // retries once with renewed access token when rejected before expiration
+                if status == StatusCode::UNAUTHORIZED && !reauthorized {
+                    if let Some(profile) = &profile {
+                        profile.invalidate().await;
+                        reauthorized = true;
+                        continue;
+                    }
+                }
+
+                if !is_retryable(status) || attempt >= config.max_retries {
+                    return Err(ResponseError::http_response_status(uri, status));
+                }
+
+                retry_after(&response)
+            }
+            Err(e) => {
+                if !(e.is_timeout() || e.is_connect()) || attempt >= config.max_retries {
+                    return Err(ResponseError::http_request(&e));
+                }
+
+                None
+            }
+        }
+        .unwrap_or_else(|| backoff(config.retry_backoff, attempt));
+
+        attempt += 1;
+        log::warn!(
+            "Retrying http request to '{uri}' after {delay:?} ({attempt}/{})",
+            config.max_retries
+        );
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn read_body(uri: &str, mut response: Response) -> Result<bytes::Bytes, ResponseError> {
+    let limit = Settings::global().http_client.max_response_size * 1024 * 1024;
+
+    let exceeded = || {
+        ResponseError::payload_too_large(format!(
+            "Response of '{uri}' exceeds limit size {} MB",
+            Settings::global().http_client.max_response_size
+        ))
+    };
+
+    if limit > 0
+        && response
+            .content_length()
+            .is_some_and(|len| len > limit as u64)
+    {
+        return Err(exceeded());
+    }
+
+    let mut buffer = bytes::BytesMut::new();
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ResponseError::http_request(&e))?
+    {
+        if limit > 0 && buffer.len() + chunk.len() > limit {
+            return Err(exceeded());
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+
+    Ok(buffer.freeze())
+}
+
+fn header_value(response: &Response, name: HeaderName) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string)
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(|seconds| Duration::from_secs(seconds).min(MAX_RETRY_DELAY))
+}
+
+fn backoff(initial: u64, attempt: u32) -> Duration {
+    Duration::from_millis(initial.saturating_mul(2_u64.saturating_pow(attempt)))
+        .min(MAX_RETRY_DELAY)
+}
+
 fn to_header_map(headers: Option<&HashMap<String, String>>) -> Result<HeaderMap, ResponseError> {
     let mut result = HeaderMap::new();
 
@@ -1 +1,3 @@
+pub mod cache;
+pub mod credential_manager;
 pub mod http;
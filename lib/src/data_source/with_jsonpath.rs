@@ -46,7 +46,7 @@ pub fn to_record_batch(
                 df_schema.fields().iter().enumerate().try_for_each(
                     |(index, field)| match json_row.get(index) {
                         Some(value) => {
-                            map.insert(field.name().to_string(), value.clone());
+                            map.insert(field.name().clone(), value.clone());
                             Ok(())
                         }
                         // TODO: fills-up default value when normalize is required
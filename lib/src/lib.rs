@@ -11,7 +11,7 @@ use context::session_manager::SessionContextManager;
 use log::Level;
 use plugin::plugin_manager::{PluginManager, PLUGIN_MANAGER};
 
-use crate::server::{interval_worker, signal_handler};
+use crate::server::{health_check, interval_worker, signal_handler};
 use crate::settings::{Settings, LAZY_SETTINGS};
 use crate::statistics::{Statistics, LAZY_STATISTICS};
 
@@ -24,6 +24,10 @@ mod server;
 pub mod settings;
 mod statistics;
 
+/// Drivers of other database engines, registered before `execute`.
+#[cfg(feature = "database")]
+pub use data_source::database::driver as database_driver;
+
 #[cfg(any(not(feature = "flight"), not(feature = "telemetry")))]
 type BoxedFuture =
     std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), anyhow::Error>> + Send>>;
@@ -33,7 +37,7 @@ type BoxedFuture =
 /// * Configuration
 /// * Statistics Manager
 /// * Logging System
-/// * DataFusion Session Manager
+/// * `DataFusion` Session Manager
 /// * Python Plugin Manager (feature = "plugin" only)
 /// * HTTP socket binding
 /// * gRPC socket binding (feature = "flight" only)
@@ -121,6 +125,7 @@ pub async fn execute(settings: Settings) -> anyhow::Result<()> {
             return Err(anyhow::anyhow!("metrics server initialization error: {e:?}"));
         },
         () = interval_worker::cleanup_and_update_metrics(session_mgr) => {},
+        () = health_check::check_components() => {},
     }
 
     log::info!("Server terminated");
@@ -12,6 +12,8 @@ use crate::data_source::{
     schema,
 };
 use crate::response::http_error::ResponseError;
+#[cfg(feature = "database")]
+use crate::settings::Settings;
 
 #[derive(Deserialize, Clone, Debug)]
 pub struct DataSourceOption {
@@ -25,10 +27,58 @@ pub struct DataSourceOption {
     #[serde(rename = "requireNormalize")]
     pub require_normalize: Option<bool>,
     pub overwrite: Option<bool>,
+    // for saving data sources
+    #[serde(rename = "saveMode")]
+    pub save_mode: Option<SaveMode>,
+    #[serde(rename = "partitionBy")]
+    pub partition_by: Option<Vec<String>>,
+    // `gzip`, `bzip2`, `xz`, `zstd` for csv and ndJson, and parquet codecs e.g. `zstd(3)`
+    pub compression: Option<String>,
+    #[cfg_attr(not(feature = "deltalake"), allow(dead_code))]
+    #[serde(rename = "mergeSchema")]
+    pub merge_schema: Option<bool>,
+    #[cfg_attr(not(feature = "deltalake"), allow(dead_code))]
+    #[serde(rename = "overwriteSchema")]
+    pub overwrite_schema: Option<bool>,
+    // typed partition columns of hive-style directories, e.g. `date=2024-01-01/`
+    #[serde(rename = "partitionColumns")]
+    pub partition_columns: Option<Vec<schema::Field>>,
+    // discovers partition columns typed by the values (Int64, Boolean, Date32, otherwise Utf8) if not specified
+    #[serde(rename = "hivePartitioning")]
+    pub hive_partitioning: Option<bool>,
     // for http headers
     pub headers: Option<HashMap<String, String>>,
-    #[allow(dead_code)]
+    // name of http credential profile in configuration
+    pub credential: Option<String>,
+    // time travel of delta table by version, iceberg table by snapshot id, or RFC 3339 timestamp
+    #[cfg_attr(not(feature = "deltalake"), allow(dead_code))]
     pub version: Option<u64>,
+    #[cfg_attr(not(feature = "iceberg"), allow(dead_code))]
+    #[serde(rename = "snapshotId")]
+    pub snapshot_id: Option<i64>,
+    #[cfg_attr(not(any(feature = "deltalake", feature = "iceberg")), allow(dead_code))]
+    pub timestamp: Option<String>,
+    // database namespace of iceberg SQL catalog, and its catalog name
+    #[cfg_attr(not(feature = "iceberg"), allow(dead_code))]
+    pub catalog: Option<String>,
+    #[cfg_attr(
+        not(all(
+            feature = "iceberg",
+            any(feature = "postgres", feature = "mysql", feature = "sqlite")
+        )),
+        allow(dead_code)
+    )]
+    #[serde(rename = "catalogName")]
+    pub catalog_name: Option<String>,
+    // target table of external database, and its primary keys for upsert
+    #[cfg_attr(not(feature = "database"), allow(dead_code))]
+    pub table: Option<String>,
+    #[cfg_attr(not(feature = "database"), allow(dead_code))]
+    #[serde(rename = "primaryKeys")]
+    pub primary_keys: Option<Vec<String>>,
+    // native query of external database, `:name` is bound by the session variable
+    #[cfg_attr(not(feature = "database"), allow(dead_code))]
+    pub sql: Option<String>,
 }
 
 impl DataSourceOption {
@@ -41,8 +91,23 @@ impl DataSourceOption {
             json_path: None,
             require_normalize: None,
             overwrite: None,
+            save_mode: None,
+            partition_by: None,
+            compression: None,
+            merge_schema: None,
+            overwrite_schema: None,
+            partition_columns: None,
+            hive_partitioning: None,
             headers: None,
+            credential: None,
             version: None,
+            snapshot_id: None,
+            timestamp: None,
+            catalog: None,
+            catalog_name: None,
+            table: None,
+            primary_keys: None,
+            sql: None,
         }
     }
 
@@ -55,8 +120,32 @@ impl DataSourceOption {
             json_path: None,
             require_normalize: Some(false),
             overwrite: Some(false),
+            save_mode: None,
+            partition_by: None,
+            compression: None,
+            merge_schema: Some(false),
+            overwrite_schema: Some(false),
+            partition_columns: None,
+            hive_partitioning: Some(true),
             headers: None,
+            credential: None,
             version: None,
+            snapshot_id: None,
+            timestamp: None,
+            catalog: None,
+            catalog_name: None,
+            table: None,
+            primary_keys: None,
+            sql: None,
+        }
+    }
+
+    /// Save mode by `saveMode`, or `overwrite` flag if not specified.
+    pub fn save_mode(&self, default: SaveMode) -> SaveMode {
+        match (&self.save_mode, self.overwrite) {
+            (Some(mode), _) => mode.clone(),
+            (None, Some(true)) => SaveMode::Overwrite,
+            (None, _) => default,
         }
     }
 
@@ -66,6 +155,18 @@ impl DataSourceOption {
     }
 }
 
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub enum SaveMode {
+    #[serde(rename = "append")]
+    Append,
+    #[serde(rename = "overwrite")]
+    Overwrite,
+    #[serde(rename = "errorIfExists")]
+    ErrorIfExists,
+    #[serde(rename = "upsert")]
+    Upsert,
+}
+
 #[cfg(feature = "plugin")]
 #[derive(Deserialize, Clone, Debug)]
 #[serde(transparent)]
@@ -96,6 +197,9 @@ pub enum DataSourceFormat {
     #[cfg(feature = "avro")]
     #[serde(rename = "avro")]
     Avro,
+    #[cfg(feature = "orc")]
+    #[serde(rename = "orc")]
+    Orc,
     #[serde(rename = "arrow")]
     Arrow,
     #[cfg(feature = "flight")]
@@ -104,6 +208,12 @@ pub enum DataSourceFormat {
     #[cfg(feature = "deltalake")]
     #[serde(rename = "deltalake")]
     Deltalake,
+    #[cfg(feature = "iceberg")]
+    #[serde(rename = "iceberg")]
+    Iceberg,
+    #[cfg(feature = "database")]
+    #[serde(rename = "database")]
+    Database,
 }
 
 impl DataSourceFormat {
@@ -117,10 +227,16 @@ impl DataSourceFormat {
             DataSourceFormat::Arrow => "arrow",
             #[cfg(feature = "avro")]
             DataSourceFormat::Avro => "avro",
+            #[cfg(feature = "orc")]
+            DataSourceFormat::Orc => "orc",
             #[cfg(feature = "flight")]
             DataSourceFormat::Flight => "flight",
             #[cfg(feature = "deltalake")]
             DataSourceFormat::Deltalake => "deltaLake",
+            #[cfg(feature = "iceberg")]
+            DataSourceFormat::Iceberg => "iceberg",
+            #[cfg(feature = "database")]
+            DataSourceFormat::Database => "database",
         }
     }
 }
@@ -170,6 +286,20 @@ impl DataSource {
     }
 
     pub fn validator(&self) -> Result<(), ResponseError> {
+        // location of the external database is the namespace
+        #[cfg(feature = "database")]
+        if self.format == DataSourceFormat::Database {
+            if !Settings::global()
+                .database_pool_manager
+                .resolvers
+                .contains_key(&self.location)
+            {
+                return Err(ResponseError::database_not_found(&self.location));
+            }
+
+            return Ok(());
+        }
+
         let uri = location::uri::to_parts(&self.location)
             .map_err(|e| ResponseError::unsupported_type(e.to_string()))?;
         let scheme = location::uri::scheme(&uri)?;
@@ -201,6 +331,8 @@ impl DataSource {
                     )));
                 }
             }
+            #[cfg(feature = "orc")]
+            DataSourceFormat::Orc => {}
             #[cfg(feature = "flight")]
             DataSourceFormat::Flight => {
                 if !matches!(
@@ -224,6 +356,17 @@ impl DataSource {
                     ));
                 }
             }
+            #[cfg(feature = "iceberg")]
+            DataSourceFormat::Iceberg => {
+                if !scheme.handle_object_store() {
+                    return Err(ResponseError::unsupported_type(format!(
+                        "Not supported data source, Iceberg with remote location '{}'",
+                        self.location
+                    )));
+                }
+            }
+            #[cfg(feature = "database")]
+            DataSourceFormat::Database => {}
         }
 
         Ok(())
@@ -7,8 +7,8 @@ use axum::{
     response::Response,
 };
 
+#[allow(dead_code)]
 pub struct BodyWithLimit {
-    #[allow(dead_code)]
     pub payload: bytes::Bytes,
 }
 
@@ -17,6 +17,8 @@ pub fn resolve_from(
                 (mime::APPLICATION, "vnd.apache.parquet") => Some(DataSourceFormat::Parquet),
                 #[cfg(feature = "avro")]
                 (mime::APPLICATION, "vnd.apache.avro") => Some(DataSourceFormat::Avro),
+                #[cfg(feature = "orc")]
+                (mime::APPLICATION, "vnd.apache.orc") => Some(DataSourceFormat::Orc),
                 _ => None,
             }
         } else {
@@ -38,6 +40,8 @@ pub fn resolve_from(
                     "parquet" => Some(DataSourceFormat::Parquet),
                     #[cfg(feature = "avro")]
                     "avro" => Some(DataSourceFormat::Avro),
+                    #[cfg(feature = "orc")]
+                    "orc" => Some(DataSourceFormat::Orc),
                     _ => None,
                 }
             } else {
@@ -2,14 +2,24 @@
 // Sasaki, Naoki <nsasaki@sal.co.jp> February 21, 2024
 //
 
-use axum::http::header;
+#[cfg(feature = "database")]
+use std::sync::Arc;
+
+use axum::http::{header, HeaderMap};
+#[cfg(feature = "database")]
+use axum_extra::headers::{authorization::Basic, Authorization, HeaderMapExt};
 use axum_extra::{
     headers::{self, Header, HeaderName, HeaderValue},
     TypedHeader,
 };
+use datafusion::execution::context::SessionConfig;
 
+#[cfg(feature = "database")]
+use crate::data_source::database::impersonation::DatabaseIdentity;
 use crate::request::body::ResponseFormat;
 use crate::response::http_error::ResponseError;
+#[cfg(feature = "database")]
+use crate::settings::Settings;
 
 #[derive(Debug)]
 pub struct Accept(String);
@@ -98,3 +108,38 @@ pub fn request_format(content_type: &TypedHeader<ContentType>) -> Result<String,
         })?
         .to_string())
 }
+
+/// Authenticated user of the request, the identity header set by the authenticating proxy
+/// and the basic authorization are kept apart to be chosen by the impersonation mode.
+#[cfg(feature = "database")]
+pub fn database_identity(headers: &HeaderMap) -> Option<DatabaseIdentity> {
+    let user = Settings::global()
+        .server
+        .identity_header
+        .as_ref()
+        .and_then(|name| headers.get(name.as_str()))
+        .and_then(|value| value.to_str().ok())
+        .map(ToString::to_string);
+
+    let credentials = headers
+        .typed_get::<Authorization<Basic>>()
+        .map(|Authorization(basic)| (basic.username().to_string(), basic.password().to_string()));
+
+    if user.is_none() && credentials.is_none() {
+        return None;
+    }
+
+    Some(DatabaseIdentity { user, credentials })
+}
+
+/// Session config with the authenticated user of the request.
+#[cfg(feature = "database")]
+pub fn session_config(headers: &HeaderMap) -> Option<SessionConfig> {
+    database_identity(headers)
+        .map(|identity| SessionConfig::new().with_extension(Arc::new(identity)))
+}
+
+#[cfg(not(feature = "database"))]
+pub fn session_config(_headers: &HeaderMap) -> Option<SessionConfig> {
+    None
+}
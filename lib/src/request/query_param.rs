@@ -23,7 +23,7 @@ pub fn usize_or_default(value: Option<&String>, default: usize) -> usize {
 #[allow(dead_code)]
 pub fn str_or_default(value: Option<&String>, default: &str) -> String {
     match value {
-        Some(v) => v.to_string(),
+        Some(v) => v.clone(),
         None => default.to_string(),
     }
 }
@@ -121,6 +121,12 @@ pub async fn upload<S: SessionManager>(
                     .append_csv_bytes(&session_id, &name, bytes_buffer.freeze())
                     .await?;
             }
+            #[cfg(feature = "orc")]
+            Some(DataSourceFormat::Orc) => {
+                locked_session_mgr
+                    .append_orc_bytes(&session_id, &name, bytes_buffer.freeze())
+                    .await?;
+            }
             _ => {
                 return Err(ResponseError::unsupported_format(
                     "content-type of the multipart field must be either \
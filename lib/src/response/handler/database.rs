@@ -0,0 +1,107 @@
+// response/handler/database.rs - External database information handler
+
+use axum::{
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde::Serialize;
+
+use crate::request::header;
+use crate::response::http_error::ResponseError;
+use crate::settings::Settings;
+
+#[derive(Serialize)]
+struct Column {
+    name: String,
+    #[serde(rename = "dataType")]
+    data_type: String,
+    nullable: bool,
+}
+
+#[derive(Serialize)]
+struct Table {
+    schema: String,
+    name: String,
+    columns: Vec<Column>,
+}
+
+/// Tables visible to the user of the request in impersonation mode.
+pub async fn tables(
+    headers: HeaderMap,
+    Path(namespace): Path<String>,
+) -> Result<impl IntoResponse, ResponseError> {
+    log::info!("Accessing database table list handler: {namespace}");
+
+    let Some(resolver) = Settings::global()
+        .database_pool_manager
+        .resolvers
+        .get(&namespace)
+    else {
+        return Err(ResponseError::database_not_found(namespace));
+    };
+
+    let driver = resolver.driver_as(header::database_identity(&headers).as_ref())?;
+
+    let mut tables: Vec<Table> = vec![];
+
+    for column in driver.table_columns(None).await? {
+        let column_info = Column {
+            name: column.column_name,
+            data_type: column.data_type,
+            nullable: column.nullable,
+        };
+
+        match tables.last_mut() {
+            Some(table)
+                if table.schema == column.table_schema && table.name == column.table_name =>
+            {
+                table.columns.push(column_info);
+            }
+            _ => tables.push(Table {
+                schema: column.table_schema,
+                name: column.table_name,
+                columns: vec![column_info],
+            }),
+        }
+    }
+
+    Ok(Json(tables))
+}
+
+#[allow(clippy::unused_async)] // requires `async` in axum
+pub async fn invalidate_cache(
+    Path(namespace): Path<String>,
+) -> Result<impl IntoResponse, ResponseError> {
+    log::info!("Accessing database schema cache invalidation handler: {namespace}");
+
+    let Some(resolver) = Settings::global()
+        .database_pool_manager
+        .resolvers
+        .get(&namespace)
+    else {
+        return Err(ResponseError::database_not_found(namespace));
+    };
+
+    resolver.invalidate(None);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[allow(clippy::unused_async)] // requires `async` in axum
+pub async fn invalidate_table_cache(
+    Path((namespace, table)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ResponseError> {
+    log::info!("Accessing database schema cache invalidation handler: {namespace}, {table}");
+
+    let Some(resolver) = Settings::global()
+        .database_pool_manager
+        .resolvers
+        .get(&namespace)
+    else {
+        return Err(ResponseError::database_not_found(namespace));
+    };
+
+    resolver.invalidate(Some(&table));
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -5,7 +5,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use axum::{extract, response::IntoResponse};
+use axum::{extract, http::HeaderMap, response::IntoResponse};
 use axum_extra::{either::Either, TypedHeader};
 use datafusion::arrow::record_batch::RecordBatch;
 
@@ -16,6 +16,7 @@ use crate::request::{body::DataFrameQuery, body::ResponseFormat, header};
 use crate::response::{http_error::ResponseError, http_response, record_batch_stream};
 
 pub async fn query_responder<S: SessionManager>(
+    headers: HeaderMap,
     accept_header: Option<TypedHeader<header::Accept>>,
     extract::State(session_mgr): extract::State<Arc<tokio::sync::Mutex<S>>>,
     extract::Query(params): extract::Query<HashMap<String, String>>,
@@ -33,17 +34,18 @@ pub async fn query_responder<S: SessionManager>(
 
     let session_mgr = session_mgr.lock().await;
     let session_id = session_mgr
-        .create_new_session(None, Some(keep_alive), None)
-        .await?;
-
-    session_mgr
-        .append_data_sources(&session_id, &payload.data_sources)
+        .create_new_session(None, Some(keep_alive), header::session_config(&headers))
         .await?;
 
+    // binds the native queries of external databases
     if let Some(variables) = &payload.variables {
         session_mgr.append_variables(&session_id, variables).await?;
     }
 
+    session_mgr
+        .append_data_sources(&session_id, &payload.data_sources)
+        .await?;
+
     if let Some(processor) = &payload.processor {
         if let Some(merge_processors) = &processor.merge_processors {
             session_mgr
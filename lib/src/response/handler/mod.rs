@@ -1,6 +1,9 @@
 pub mod data_source;
+#[cfg(feature = "database")]
+pub mod database;
 pub mod dataframe;
 pub mod processor;
 pub mod session;
+pub mod storage;
 pub mod sys_info;
 pub mod variable;
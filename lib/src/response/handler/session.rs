@@ -7,8 +7,11 @@ use std::sync::Arc;
 
 use axum::{
     extract::{self, Path, Query},
+    http::HeaderMap,
     response::IntoResponse,
 };
+#[cfg(feature = "database")]
+use axum::{middleware, response::Response};
 use axum_extra::{either::Either, TypedHeader};
 use datafusion::arrow::record_batch::RecordBatch;
 use serde::Serialize;
@@ -29,6 +32,29 @@ pub struct Session {
     pub ttl: i64,
 }
 
+/// Rejects the requests to the session by the other users than its creator, as the session accesses
+/// the external databases as the creator.
+#[cfg(feature = "database")]
+pub async fn verify_owner<E: SessionManager>(
+    Path(params): Path<HashMap<String, String>>,
+    extract::State(session_mgr): extract::State<Arc<tokio::sync::Mutex<E>>>,
+    request: extract::Request,
+    next: middleware::Next,
+) -> Result<Response, ResponseError> {
+    if let Some(session_id) = params.get("session_id") {
+        session_mgr
+            .lock()
+            .await
+            .verify_identity(
+                session_id,
+                header::database_identity(request.headers()).as_ref(),
+            )
+            .await?;
+    }
+
+    Ok(next.run(request).await)
+}
+
 pub async fn index<E: SessionManager>(
     extract::State(session_mgr): extract::State<Arc<tokio::sync::Mutex<E>>>,
 ) -> Result<impl IntoResponse, ResponseError> {
@@ -48,6 +74,7 @@ pub async fn index<E: SessionManager>(
 }
 
 pub async fn create<E: SessionManager>(
+    headers: HeaderMap,
     extract::State(session_mgr): extract::State<Arc<tokio::sync::Mutex<E>>>,
     Query(params): Query<HashMap<String, String>>,
 ) -> Result<impl IntoResponse, ResponseError> {
@@ -58,7 +85,7 @@ pub async fn create<E: SessionManager>(
 
     let session_mgr = session_mgr.lock().await;
     let new_session_id = session_mgr
-        .create_new_session(session_id, keep_alive, None)
+        .create_new_session(session_id, keep_alive, header::session_config(&headers))
         .await?;
 
     Ok(axum::Json(session_mgr.session(&new_session_id).await?))
@@ -0,0 +1,302 @@
+// response/handler/storage.rs - Object store browsing handler
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query},
+    response::IntoResponse,
+    Json,
+};
+use futures::StreamExt;
+use object_store::{local::LocalFileSystem, path, DynObjectStore, ObjectMeta};
+use serde::Serialize;
+
+use crate::context::session::{ConcurrentSessionContext, Session, SessionContext};
+use crate::data_source::location::uri::SupportedScheme;
+use crate::data_source::object_store::{credential_manager::ObjectStoreManager, writer};
+use crate::data_source::schema::DataSourceSchema;
+use crate::request::body::{DataSource, DataSourceFormat};
+use crate::response::http_error::ResponseError;
+use crate::settings::{Settings, Storage as StorageSetting};
+
+// `data_dir` of the server, appears as `file:///` in the data source location
+const LOCAL_STORE: &str = "local";
+
+#[derive(Serialize)]
+struct Store {
+    name: String,
+    url: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Object {
+    name: String,
+    size: u64,
+    #[serde(rename = "lastModified")]
+    last_modified: String,
+    #[serde(rename = "eTag", skip_serializing_if = "Option::is_none")]
+    e_tag: Option<String>,
+}
+
+impl From<ObjectMeta> for Object {
+    fn from(meta: ObjectMeta) -> Self {
+        Self {
+            name: meta.location.to_string(),
+            size: meta.size,
+            last_modified: meta.last_modified.to_rfc3339(),
+            e_tag: meta.e_tag,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Listing {
+    prefixes: Vec<String>,
+    objects: Vec<Object>,
+    truncated: bool,
+}
+
+#[allow(clippy::unused_async)] // requires `async` in axum
+pub async fn index() -> Result<impl IntoResponse, ResponseError> {
+    log::info!("Accessing storage list handler");
+
+    let mut stores = vec![Store {
+        name: LOCAL_STORE.to_string(),
+        url: "file:///".to_string(),
+        kind: "local".to_string(),
+        description: None,
+    }];
+
+    let mut keys = Settings::global()
+        .object_store_manager
+        .stores
+        .keys()
+        .collect::<Vec<_>>();
+    keys.sort();
+
+    for key in keys {
+        let (scheme, _) = key.split_once("://").unwrap_or_default();
+
+        stores.push(Store {
+            name: store_name(key),
+            url: key.clone(),
+            kind: match SupportedScheme::from_str(scheme) {
+                SupportedScheme::S3 => "aws",
+                SupportedScheme::GS => "gcp",
+                SupportedScheme::AZ => "azure",
+                _ => "webdav",
+            }
+            .to_string(),
+            description: description(key),
+        });
+    }
+
+    Ok(Json(stores))
+}
+
+/// Objects and the sub-directories of the prefix, or all objects under the prefix by `recursive=true`.
+pub async fn list(
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    log::info!("Accessing storage object list handler: {name}");
+
+    let (_, store) = resolve(&name)?;
+
+    let prefix = params
+        .get("prefix")
+        .map(|prefix| path::Path::parse(prefix.trim_matches('/')))
+        .transpose()
+        .map_err(|e| ResponseError::request_validation(format!("Invalid prefix: {e}")))?;
+
+    let limit = params
+        .get("limit")
+        .and_then(|limit| limit.parse::<usize>().ok())
+        .unwrap_or(1000);
+
+    let recursive = params
+        .get("recursive")
+        .is_some_and(|recursive| recursive.to_lowercase() == "true");
+
+    let listing = if recursive {
+        let mut objects = vec![];
+        let mut truncated = false;
+        let mut stream = store.list(prefix.as_ref());
+
+        while let Some(meta) = stream.next().await {
+            if objects.len() == limit {
+                truncated = true;
+                break;
+            }
+            objects.push(Object::from(meta?));
+        }
+
+        Listing {
+            prefixes: vec![],
+            objects,
+            truncated,
+        }
+    } else {
+        let result = store.list_with_delimiter(prefix.as_ref()).await?;
+        let truncated = result.objects.len() > limit;
+
+        Listing {
+            prefixes: result
+                .common_prefixes
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            objects: result
+                .objects
+                .into_iter()
+                .take(limit)
+                .map(Object::from)
+                .collect(),
+            truncated,
+        }
+    };
+
+    Ok(Json(listing))
+}
+
+/// Infers the schema of the file by a temporary context not registered as the session,
+/// format is guessed by the extension unless `format` is specified.
+pub async fn schema(
+    Path(name): Path<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<impl IntoResponse, ResponseError> {
+    log::info!("Accessing storage schema handler: {name}");
+
+    let (url, _) = resolve(&name)?;
+
+    let Some(file_path) = params.get("path") else {
+        return Err(ResponseError::request_validation(
+            "Query parameter 'path' is required",
+        ));
+    };
+
+    let format = match params.get("format") {
+        Some(format) => {
+            serde_json::from_value::<DataSourceFormat>(serde_json::Value::String(format.clone()))
+                .map_err(|_| {
+                    ResponseError::unsupported_format(format!("Unsupported format '{format}'"))
+                })?
+        }
+        None => format_of(file_path).ok_or_else(|| {
+            ResponseError::unsupported_format(format!(
+                "Can not guess the format of '{file_path}', specify 'format'"
+            ))
+        })?,
+    };
+
+    let location = format!(
+        "{}/{}",
+        url.trim_end_matches('/'),
+        file_path.trim_start_matches('/')
+    );
+    writer::validate_path(file_path, &location)?;
+
+    let data_source = DataSource::new(format, "peek", Some(&location));
+    data_source.validator()?;
+
+    let context = ConcurrentSessionContext::new(SessionContext::new(None)?);
+
+    match data_source.format {
+        DataSourceFormat::Json => context.append_from_json_file(&data_source).await?,
+        DataSourceFormat::Csv | DataSourceFormat::NdJson | DataSourceFormat::Parquet => {
+            context.append_from_object_store(&data_source).await?;
+        }
+        #[cfg(feature = "avro")]
+        DataSourceFormat::Avro => context.append_from_object_store(&data_source).await?,
+        #[cfg(feature = "orc")]
+        DataSourceFormat::Orc => context.append_from_object_store(&data_source).await?,
+        _ => {
+            return Err(ResponseError::unsupported_format(
+                "Schema of the file is available for 'csv', 'json', 'ndJson', 'parquet', 'avro' and 'orc'",
+            ))
+        }
+    }
+
+    let (_, schema) = context.data_source("peek").await?;
+
+    Ok(Json(DataSourceSchema::from_arrow_schema(&schema)))
+}
+
+// e.g. `s3:my-bucket` of `s3://my-bucket`
+fn store_name(key: &str) -> String {
+    key.replacen("://", ":", 1)
+}
+
+fn resolve(name: &str) -> Result<(String, Arc<DynObjectStore>), ResponseError> {
+    if name == LOCAL_STORE {
+        return Ok((
+            "file:///".to_string(),
+            Arc::new(LocalFileSystem::new_with_prefix(
+                &Settings::global().server.data_dir,
+            )?),
+        ));
+    }
+
+    Settings::global()
+        .object_store_manager
+        .stores
+        .iter()
+        .find(|(key, _)| store_name(key) == name)
+        .map(|(key, store)| (key.clone(), store.clone()))
+        .ok_or_else(|| ResponseError::storage_not_found(name))
+}
+
+fn description(key: &str) -> Option<String> {
+    Settings::global()
+        .storages
+        .iter()
+        .flatten()
+        .find_map(|storage| match storage {
+            StorageSetting::Aws(aws)
+                if key == ObjectStoreManager::store_key(&SupportedScheme::S3, &aws.bucket) =>
+            {
+                aws.description.clone()
+            }
+            StorageSetting::Gcp(gcp)
+                if key == ObjectStoreManager::store_key(&SupportedScheme::GS, &gcp.bucket) =>
+            {
+                gcp.description.clone()
+            }
+            StorageSetting::Azure(azure)
+                if key
+                    == ObjectStoreManager::azure_store_key(
+                        &azure.account_name,
+                        &azure.container,
+                    ) =>
+            {
+                azure.description.clone()
+            }
+            #[cfg(feature = "webdav")]
+            StorageSetting::Webdav(http) if http.url.starts_with(key) => http.description.clone(),
+            _ => None,
+        })
+}
+
+fn format_of(file_path: &str) -> Option<DataSourceFormat> {
+    let extension = std::path::Path::new(file_path)
+        .extension()?
+        .to_str()?
+        .to_lowercase();
+
+    match extension.as_str() {
+        "csv" => Some(DataSourceFormat::Csv),
+        "json" => Some(DataSourceFormat::Json),
+        "ndjson" | "jsonl" => Some(DataSourceFormat::NdJson),
+        "parquet" => Some(DataSourceFormat::Parquet),
+        #[cfg(feature = "avro")]
+        "avro" => Some(DataSourceFormat::Avro),
+        #[cfg(feature = "orc")]
+        "orc" => Some(DataSourceFormat::Orc),
+        _ => None,
+    }
+}
@@ -2,6 +2,7 @@
 // Sasaki, Naoki <nsasaki@sal.co.jp> July 29, 2023
 //
 
+use crate::server::health_check::{self, ComponentStatus};
 use crate::statistics::Statistics;
 #[cfg(feature = "plugin")]
 use crate::PluginManager;
@@ -48,6 +49,7 @@ struct System {
     #[cfg(feature = "plugin")]
     plugin: Plugin,
     statistics: Stats,
+    components: Vec<ComponentStatus>,
 }
 
 #[allow(clippy::unused_async)] // requires `async` in axum
@@ -89,5 +91,6 @@ pub async fn handler() -> impl IntoResponse {
         #[cfg(feature = "plugin")]
         plugin,
         statistics,
+        components: health_check::statuses(),
     })
 }
@@ -51,6 +51,17 @@ impl From<arrow::error::ArrowError> for ResponseError {
 
 impl From<DataFusionError> for ResponseError {
     fn from(e: DataFusionError) -> Self {
+        // the response errors of the database drivers keep their status
+        if let DataFusionError::External(error) = e.find_root() {
+            if let Some(error) = error.downcast_ref::<ResponseError>() {
+                return ResponseError {
+                    code: error.code,
+                    error: error.error.clone(),
+                    message: error.message.clone(),
+                };
+            }
+        }
+
         ResponseError {
             error: "data_fusion_error".to_string(),
             message: e.to_string(),
@@ -139,6 +150,28 @@ impl From<delta_kernel::Error> for ResponseError {
     }
 }
 
+#[cfg(feature = "iceberg")]
+impl From<iceberg::Error> for ResponseError {
+    fn from(e: iceberg::Error) -> Self {
+        ResponseError {
+            error: "iceberg_error".to_string(),
+            message: e.to_string(),
+            code: http::StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[cfg(feature = "database")]
+impl From<sqlx::Error> for ResponseError {
+    fn from(e: sqlx::Error) -> Self {
+        ResponseError {
+            error: "database_error".to_string(),
+            message: e.to_string(),
+            code: http::StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
 impl ResponseError {
     pub fn session_not_found(id: impl Into<String>) -> Self {
         Self {
@@ -148,6 +181,41 @@ impl ResponseError {
         }
     }
 
+    pub fn session_forbidden(id: impl Into<String>) -> Self {
+        Self {
+            code: http::StatusCode::FORBIDDEN,
+            error: "session_forbidden".to_string(),
+            message: format!("Session {} was created by the other user", id.into()),
+        }
+    }
+
+    pub fn database_not_found(namespace: impl Into<String>) -> Self {
+        Self {
+            code: http::StatusCode::NOT_FOUND,
+            error: "database_not_found".to_string(),
+            message: namespace.into(),
+        }
+    }
+
+    pub fn native_query_forbidden(namespace: impl Into<String>) -> Self {
+        Self {
+            code: http::StatusCode::FORBIDDEN,
+            error: "native_query_forbidden".to_string(),
+            message: format!(
+                "Native query is not allowed on the database '{}'",
+                namespace.into()
+            ),
+        }
+    }
+
+    pub fn storage_not_found(name: impl Into<String>) -> Self {
+        Self {
+            code: http::StatusCode::NOT_FOUND,
+            error: "storage_not_found".to_string(),
+            message: name.into(),
+        }
+    }
+
     pub fn payload_too_large(message: impl Into<String>) -> Self {
         Self {
             code: http::StatusCode::PAYLOAD_TOO_LARGE,
@@ -212,6 +280,15 @@ impl ResponseError {
         }
     }
 
+    #[cfg(feature = "orc")]
+    pub fn orc_deserialization(_: orc_rust::error::OrcError) -> Self {
+        Self {
+            code: http::StatusCode::INTERNAL_SERVER_ERROR,
+            error: "orc_deserialization".to_string(),
+            message: "Failed to deserialize orc file into record batches".to_string(),
+        }
+    }
+
     pub fn arrow_stream_serialization(_: arrow::error::ArrowError) -> Self {
         Self {
             code: http::StatusCode::INTERNAL_SERVER_ERROR,
@@ -228,11 +305,27 @@ impl ResponseError {
         }
     }
 
-    pub fn http_request(_: reqwest::Error) -> Self {
+    pub fn http_request(e: &reqwest::Error) -> Self {
         Self {
             code: http::StatusCode::EXPECTATION_FAILED,
             error: "http_request".to_string(),
-            message: "Failed to request external HTTP server".to_string(),
+            message: format!("Failed to request external HTTP server: {e}"),
+        }
+    }
+
+    pub fn http_response_status(uri: &str, status: reqwest::StatusCode) -> Self {
+        Self {
+            code: http::StatusCode::EXPECTATION_FAILED,
+            error: "http_response_status".to_string(),
+            message: format!("External HTTP server responded {status} for '{uri}'"),
+        }
+    }
+
+    pub fn http_authorization(message: impl Into<String>) -> Self {
+        Self {
+            code: http::StatusCode::EXPECTATION_FAILED,
+            error: "http_authorization".to_string(),
+            message: message.into(),
         }
     }
 
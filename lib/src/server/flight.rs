@@ -68,6 +68,32 @@ impl DataFusionServerFlightService {
         )?)
     }
 
+    /// Rejects the requests to the session by the other users than its creator.
+    #[cfg(feature = "database")]
+    async fn verify_owner(
+        &self,
+        metadata: &tonic::metadata::MetadataMap,
+        session_id: &str,
+    ) -> Result<(), Status> {
+        let identity = crate::request::header::database_identity(&metadata.clone().into_headers());
+        self.session_mgr
+            .lock()
+            .await
+            .verify_identity(session_id, identity.as_ref())
+            .await
+            .map_err(from_http_response_err)
+    }
+
+    #[cfg(not(feature = "database"))]
+    #[allow(clippy::unused_async)]
+    async fn verify_owner(
+        &self,
+        _metadata: &tonic::metadata::MetadataMap,
+        _session_id: &str,
+    ) -> Result<(), Status> {
+        Ok(())
+    }
+
     async fn ipc_schema_result(&self, session_id: &str, sql: &str) -> Result<SchemaResult, Status> {
         let schema = Self::schema_from_logical_plan(self, session_id, sql).await?;
         let schema_result = SchemaAsIpc::new(&schema, &IpcWriteOptions::default())
@@ -180,8 +206,10 @@ impl FlightService for DataFusionServerFlightService {
         request: Request<FlightDescriptor>,
     ) -> Result<Response<FlightInfo>, Status> {
         metrics::track_flight("get_flight_info", request, |request| async move {
+            let metadata = request.metadata().clone();
             let descriptor = request.into_inner();
             let (session_id, sql) = Self::resolve_descriptor(&descriptor).map_err(|e| *e)?;
+            self.verify_owner(&metadata, &session_id).await?;
             let schema = Self::schema_from_logical_plan(self, &session_id, &sql).await?;
 
             Ok(Response::new(
@@ -210,8 +238,10 @@ impl FlightService for DataFusionServerFlightService {
         request: Request<FlightDescriptor>,
     ) -> Result<Response<SchemaResult>, Status> {
         metrics::track_flight("get_schema", request, |request| async move {
+            let metadata = request.metadata().clone();
             let descriptor = request.into_inner();
             let (session_id, sql) = Self::resolve_descriptor(&descriptor).map_err(|e| *e)?;
+            self.verify_owner(&metadata, &session_id).await?;
 
             Ok(Response::new(
                 Self::ipc_schema_result(self, &session_id, &sql).await?,
@@ -227,6 +257,7 @@ impl FlightService for DataFusionServerFlightService {
         request: Request<Ticket>,
     ) -> Result<Response<Self::DoGetStream>, Status> {
         metrics::track_flight("do_get", request, |request| async move {
+            let metadata = request.metadata().clone();
             let ticket = request.into_inner();
 
             if let Ok(ticket_str) = std::str::from_utf8(&ticket.ticket) {
@@ -234,6 +265,7 @@ impl FlightService for DataFusionServerFlightService {
 
                 let (session_id, ticket_value) =
                     split_descriptor_value(Some(ticket_str)).map_err(|e| *e)?;
+                self.verify_owner(&metadata, &session_id).await?;
                 let sql = if ticket_value.chars().any(char::is_whitespace) {
                     ticket_value // Maybe SQL statement
                 } else {
@@ -276,6 +308,7 @@ impl FlightService for DataFusionServerFlightService {
         request: Request<Streaming<FlightData>>,
     ) -> Result<Response<Self::DoPutStream>, Status> {
         metrics::track_flight("do_put", request, |request| async move {
+            let metadata = request.metadata().clone();
             let mut stream = request.into_inner();
 
             let (record_batches, descriptor) = flight_stream::to_record_batches(&mut stream)
@@ -297,6 +330,7 @@ impl FlightService for DataFusionServerFlightService {
                     "No descriptor found in FlightData",
                 ));
             };
+            self.verify_owner(&metadata, &session_id).await?;
 
             self.session_mgr
                 .lock()
@@ -379,6 +413,7 @@ pub fn split_descriptor_value(value: Option<&str>) -> Result<(String, String), B
 fn from_http_response_err(e: crate::response::http_error::ResponseError) -> Status {
     match e.code {
         axum::http::StatusCode::BAD_REQUEST => Status::invalid_argument(e.message),
+        axum::http::StatusCode::FORBIDDEN => Status::permission_denied(e.message),
         _ => Status::internal(e.message),
     }
 }
@@ -0,0 +1,128 @@
+// server/health_check.rs: Periodic connectivity checks of external databases and object stores
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use chrono::{SecondsFormat, Utc};
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+#[cfg(feature = "telemetry")]
+use crate::server::metrics;
+use crate::settings::Settings;
+
+const CHECK_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentStatus {
+    pub kind: &'static str,
+    pub name: String,
+    pub healthy: bool,
+    #[serde(rename = "checkedAt")]
+    pub checked_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+static COMPONENT_STATUSES: Lazy<RwLock<BTreeMap<String, ComponentStatus>>> =
+    Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+/// Checks every `server.health_check_interval` seconds, disabled by zero.
+pub async fn check_components() {
+    let interval = Settings::global().server.health_check_interval;
+
+    if interval == 0 {
+        return futures::future::pending().await;
+    }
+
+    loop {
+        check_once().await;
+        tokio::time::sleep(tokio::time::Duration::from_secs(interval)).await;
+    }
+}
+
+async fn check_once() {
+    #[cfg(feature = "database")]
+    for (namespace, resolver) in &Settings::global().database_pool_manager.resolvers {
+        let result = with_timeout(async { resolver.ping().await.map_err(|e| e.to_string()) }).await;
+
+        update_status("database", namespace, result);
+    }
+
+    for (key, store) in &Settings::global().object_store_manager.stores {
+        let result = with_timeout(async {
+            // just lists the first object, an empty store is also healthy
+            match store.list(None).next().await {
+                Some(Err(e)) => Err(e.to_string()),
+                _ => Ok(()),
+            }
+        })
+        .await;
+
+        update_status("storage", key, result);
+    }
+}
+
+async fn with_timeout(
+    check: impl std::future::Future<Output = Result<(), String>>,
+) -> Result<(), String> {
+    tokio::time::timeout(tokio::time::Duration::from_secs(CHECK_TIMEOUT_SECS), check)
+        .await
+        .unwrap_or_else(|_| Err(format!("Timed out after {CHECK_TIMEOUT_SECS} seconds")))
+}
+
+fn update_status(kind: &'static str, name: &str, result: Result<(), String>) {
+    let status = ComponentStatus {
+        kind,
+        name: name.to_string(),
+        healthy: result.is_ok(),
+        checked_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        error: result.err(),
+    };
+
+    #[cfg(feature = "telemetry")]
+    metrics::track_component_health(kind, name, status.healthy);
+
+    let mut statuses = COMPONENT_STATUSES.write().unwrap();
+
+    let was_healthy = statuses
+        .get(&format!("{kind}:{name}"))
+        .is_none_or(|previous| previous.healthy);
+
+    match (&status.error, was_healthy) {
+        (Some(error), true) => log::warn!("Health check of {kind} '{name}' failed: {error}"),
+        (None, false) => log::info!("Health check of {kind} '{name}' recovered"),
+        _ => {}
+    }
+
+    statuses.insert(format!("{kind}:{name}"), status);
+}
+
+pub fn statuses() -> Vec<ComponentStatus> {
+    COMPONENT_STATUSES
+        .read()
+        .unwrap()
+        .values()
+        .cloned()
+        .collect()
+}
+
+/// Ready when all of the configured components have been checked as healthy.
+pub fn ready() -> bool {
+    if Settings::global().server.health_check_interval == 0 {
+        return true;
+    }
+
+    #[allow(unused_mut)]
+    let mut components = Settings::global().object_store_manager.stores.len();
+
+    #[cfg(feature = "database")]
+    {
+        components += Settings::global().database_pool_manager.resolvers.len();
+    }
+
+    let statuses = COMPONENT_STATUSES.read().unwrap();
+
+    statuses.len() >= components && statuses.values().all(|status| status.healthy)
+}
@@ -20,6 +20,9 @@ pub async fn create_server<S: SessionManager>(
     ),
     anyhow::Error,
 > {
+    // the cache directory is checked before accepting requests
+    crate::data_source::transport::cache::global().await?;
+
     let app = routes::register::<S>(&session_mgr).layer(
         tower_http::cors::CorsLayer::new()
             .allow_headers(vec![
@@ -24,6 +24,9 @@ pub async fn cleanup_and_update_metrics(
             metrics::gauge!("virtual_memory_usage_bytes").set(process.virtual_memory() as f64);
         }
 
+        #[cfg(all(feature = "telemetry", feature = "database"))]
+        crate::server::metrics::track_database_pools();
+
         session_mgr.lock().await.cleanup().await;
         tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
 
@@ -158,3 +158,24 @@ pub fn track_data_sources_total(scheme: &str, format: &str) {
     ];
     metrics::counter!("data_source_registrations_total", &labels).increment(1);
 }
+
+#[cfg(feature = "telemetry")]
+pub fn track_component_health(kind: &str, name: &str, healthy: bool) {
+    let labels = [("kind", kind.to_string()), ("name", name.to_string())];
+    metrics::gauge!("component_health_status", &labels).set(if healthy { 1.0 } else { 0.0 });
+}
+
+#[cfg(all(feature = "telemetry", feature = "database"))]
+pub fn track_database_pools() {
+    for (namespace, resolver) in &Settings::global().database_pool_manager.resolvers {
+        let (size, idle) = resolver.connections();
+        let labels = [("namespace", namespace.clone())];
+
+        metrics::gauge!("database_pool_connections", &labels).set(f64::from(size));
+        #[allow(clippy::cast_precision_loss)]
+        metrics::gauge!("database_pool_idle_connections", &labels).set(idle as f64);
+        metrics::gauge!("database_pool_in_use_connections", &labels).set(f64::from(
+            size.saturating_sub(u32::try_from(idle).unwrap_or(u32::MAX)),
+        ));
+    }
+}
@@ -1,5 +1,6 @@
 #[cfg(feature = "flight")]
 pub mod flight;
+pub mod health_check;
 pub mod http;
 pub mod interval_worker;
 pub mod metrics;
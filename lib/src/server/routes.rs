@@ -9,12 +9,17 @@ use axum::{
     http::StatusCode,
     response::IntoResponse,
     routing::{delete, get, post},
-    Router,
+    Json, Router,
 };
 use tokio::sync::Mutex;
 
 use crate::context::session_manager::SessionManager;
-use crate::response::handler::{data_source, dataframe, processor, session, sys_info, variable};
+#[cfg(feature = "database")]
+use crate::response::handler::database;
+use crate::response::handler::{
+    data_source, dataframe, processor, session, storage, sys_info, variable,
+};
+use crate::server::health_check;
 #[cfg(feature = "telemetry")]
 use crate::server::metrics;
 use crate::settings::Settings;
@@ -25,8 +30,6 @@ pub fn register<S: SessionManager>(session_mgr: &Arc<Mutex<S>>) -> Router {
         .with_state(session_mgr.clone());
 
     let session_route = Router::new()
-        .route("/", get(session::index))
-        .route("/create", get(session::create))
         .route("/{session_id}", get(session::detail))
         .route("/{session_id}", delete(session::remove))
         .route("/{session_id}/query", post(session::query))
@@ -43,22 +46,53 @@ pub fn register<S: SessionManager>(session_mgr: &Arc<Mutex<S>>) -> Router {
             get(data_source::refresh),
         )
         .route("/{session_id}/variable", post(variable::register))
-        .route("/{session_id}/processor", post(processor::processing))
-        .with_state(session_mgr.clone());
+        .route("/{session_id}/processor", post(processor::processing));
 
     let session_upload_route = Router::new()
         .route("/{session_id}/datasource/upload", post(data_source::upload))
         .layer(DefaultBodyLimit::max(
             Settings::global().session.upload_limit_size * 1024 * 1024,
-        ))
+        ));
+
+    let session_route = Router::new()
+        .route("/", get(session::index))
+        .route("/create", get(session::create))
+        .merge(verify_owner(session_route, session_mgr))
         .with_state(session_mgr.clone());
 
+    let session_upload_route =
+        verify_owner(session_upload_route, session_mgr).with_state(session_mgr.clone());
+
+    let storage_route = Router::new()
+        .route("/", get(storage::index))
+        .route("/{store}/list", get(storage::list))
+        .route("/{store}/schema", get(storage::schema));
+
     let base_url = get_base_url();
 
     let mut router = Router::new()
         .route(&format!("{base_url}/healthz"), get(hc_handler))
+        .route(&format!("{base_url}/readyz"), get(ready_handler))
         .route(&format!("{base_url}/sysinfo"), get(sys_info::handler))
-        .nest(&format!("{base_url}/dataframe"), df_route);
+        .nest(&format!("{base_url}/dataframe"), df_route)
+        .nest(&format!("{base_url}/storage"), storage_route);
+
+    #[cfg(feature = "database")]
+    {
+        router = router
+            .route(
+                &format!("{base_url}/database/{{namespace}}/tables"),
+                get(database::tables),
+            )
+            .route(
+                &format!("{base_url}/database/{{namespace}}/cache"),
+                delete(database::invalidate_cache),
+            )
+            .route(
+                &format!("{base_url}/database/{{namespace}}/cache/{{table}}"),
+                delete(database::invalidate_table_cache),
+            );
+    }
 
     if !Settings::global().server.disable_stateful_features {
         router = router
@@ -74,6 +108,26 @@ pub fn register<S: SessionManager>(session_mgr: &Arc<Mutex<S>>) -> Router {
     router
 }
 
+/// Requests to the session are of the user created it, as the session accesses the external databases as the user.
+#[cfg(feature = "database")]
+fn verify_owner<S: SessionManager>(
+    router: Router<Arc<Mutex<S>>>,
+    session_mgr: &Arc<Mutex<S>>,
+) -> Router<Arc<Mutex<S>>> {
+    router.route_layer(axum::middleware::from_fn_with_state(
+        session_mgr.clone(),
+        session::verify_owner::<S>,
+    ))
+}
+
+#[cfg(not(feature = "database"))]
+fn verify_owner<S: SessionManager>(
+    router: Router<Arc<Mutex<S>>>,
+    _session_mgr: &Arc<Mutex<S>>,
+) -> Router<Arc<Mutex<S>>> {
+    router
+}
+
 fn get_base_url() -> String {
     let mut base_url = Settings::global().server.base_url.clone();
 
@@ -93,3 +147,17 @@ async fn hc_handler() -> impl IntoResponse {
     log::info!("Accessing health condition endpoint");
     StatusCode::NO_CONTENT
 }
+
+/// Ready when the external databases and object stores are reachable.
+#[allow(clippy::unused_async)] // requires `async` in axum
+async fn ready_handler() -> impl IntoResponse {
+    log::info!("Accessing readiness endpoint");
+
+    let status = if health_check::ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(health_check::statuses()))
+}
@@ -2,6 +2,7 @@
 // Sasaki, Naoki <nsasaki@sal.co.jp> December 31, 2022
 //
 
+use std::collections::HashMap;
 use std::path::Path;
 
 use config::{
@@ -12,9 +13,10 @@ use log::Level;
 use once_cell::sync::OnceCell;
 use serde::Deserialize;
 
-#[cfg(any(feature = "postgres", feature = "mysql"))]
+#[cfg(feature = "database")]
 use crate::data_source::database::database_manager;
 use crate::data_source::object_store::credential_manager;
+use crate::data_source::transport::credential_manager as http_credential_manager;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Server {
@@ -28,6 +30,8 @@ pub struct Server {
     pub data_dir: String,
     pub plugin_dir: String,
     pub disable_stateful_features: bool,
+    pub health_check_interval: u64,      // in seconds, 0 is disabled
+    pub identity_header: Option<String>, // database user set by the authenticating proxy
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -36,6 +40,24 @@ pub struct Session {
     pub upload_limit_size: usize, // in MB
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct HttpClient {
+    pub connect_timeout: u64,     // in seconds
+    pub timeout: u64,             // in seconds
+    pub max_retries: u32,         // retries on 429, 5xx and connection errors
+    pub retry_backoff: u64,       // initial backoff in milliseconds, doubles on each retry
+    pub max_response_size: usize, // in MB, 0 is unlimited
+    pub proxy: Option<String>,
+    pub ca_bundle: Option<String>, // PEM file path
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Cache {
+    pub enabled: bool,
+    pub dir: String,
+    pub max_size: usize, // in MB
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Log {
     pub level: String,
@@ -67,9 +89,24 @@ pub struct DatabaseConfigPostgres {
     pub ssl_mode: Option<String>,
     pub max_connections: Option<u32>,
     pub enable_schema_cache: Option<bool>,
+    pub schema_cache_ttl: Option<u64>,
+    pub partitioned_tables: Option<Vec<PartitionedTable>>,
+    pub fallback_to_string: Option<bool>,
+    pub impersonation: Option<String>,
+    pub impersonation_max_connections: Option<u32>,
+    pub allow_native_query: Option<bool>,
     pub description: Option<String>,
 }
 
+/// Postgres table scanned in parallel by the key ranges of integer column.
+#[cfg(feature = "postgres")]
+#[derive(Debug, Deserialize, Clone)]
+pub struct PartitionedTable {
+    pub table: String,
+    pub column: String,
+    pub partitions: usize,
+}
+
 #[cfg(feature = "mysql")]
 #[derive(Debug, Deserialize, Clone)]
 pub struct DatabaseConfigMySQL {
@@ -82,10 +119,40 @@ pub struct DatabaseConfigMySQL {
     pub ssl_mode: Option<String>,
     pub max_connections: Option<u32>,
     pub enable_schema_cache: Option<bool>,
+    pub schema_cache_ttl: Option<u64>,
+    pub fallback_to_string: Option<bool>,
+    pub impersonation: Option<String>,
+    pub impersonation_max_connections: Option<u32>,
+    pub allow_native_query: Option<bool>,
     pub description: Option<String>,
 }
 
-#[cfg(any(feature = "postgres", feature = "mysql"))]
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Deserialize, Clone)]
+pub struct DatabaseConfigSqlite {
+    pub namespace: Option<String>,
+    pub path: String,
+    pub max_connections: Option<u32>,
+    pub enable_schema_cache: Option<bool>,
+    pub schema_cache_ttl: Option<u64>,
+    pub allow_native_query: Option<bool>,
+    pub description: Option<String>,
+}
+
+/// Database engine of the driver registered by `database_driver::register`.
+#[cfg(feature = "database")]
+#[derive(Debug, Deserialize, Clone)]
+pub struct DatabaseConfigDriver {
+    pub namespace: Option<String>,
+    pub driver: String,
+    pub url: String,
+    pub max_connections: Option<u32>,
+    pub enable_schema_cache: Option<bool>,
+    pub schema_cache_ttl: Option<u64>,
+    pub description: Option<String>,
+}
+
+#[cfg(feature = "database")]
 #[derive(Debug, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Database {
@@ -93,9 +160,12 @@ pub enum Database {
     Postgres(DatabaseConfigPostgres),
     #[cfg(feature = "mysql")]
     MySQL(DatabaseConfigMySQL),
+    #[cfg(feature = "sqlite")]
+    Sqlite(DatabaseConfigSqlite),
+    Driver(DatabaseConfigDriver),
 }
 
-#[cfg(any(feature = "postgres", feature = "mysql"))]
+#[cfg(feature = "database")]
 impl Database {
     #[must_use]
     pub fn scheme(&self) -> &str {
@@ -104,31 +174,64 @@ impl Database {
             Database::Postgres(_) => "postgres",
             #[cfg(feature = "mysql")]
             Database::MySQL(_) => "mysql",
+            #[cfg(feature = "sqlite")]
+            Database::Sqlite(_) => "sqlite",
+            Database::Driver(driver) => &driver.driver,
         }
     }
 }
 
+// credentials are resolved by the environment (web identity, ECS task, EC2 instance profile)
+// when neither keys nor profile are specified
 #[derive(Debug, Deserialize, Clone)]
 pub struct StorageAws {
-    pub access_key_id: String,
-    pub secret_access_key: String,
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub session_token: Option<String>,
+    pub profile: Option<String>, // in `~/.aws/credentials` and `~/.aws/config`
     pub bucket: String,
-    pub region: String,
+    pub region: Option<String>,
+    pub endpoint: Option<String>, // S3 compatible storages, e.g. MinIO, Ceph, LocalStack
+    pub path_style: Option<bool>,
+    pub allow_http: Option<bool>,
+    pub role_session_name: Option<String>,
+    pub sts_endpoint: Option<String>,
+    pub imdsv1_fallback: Option<bool>,
+    pub skip_signature: Option<bool>,             // public buckets
+    pub options: Option<HashMap<String, String>>, // other `aws_*` builder options
     pub description: Option<String>,
 }
 
+// application default credentials or the instance metadata when no keys are specified
 #[derive(Debug, Deserialize, Clone)]
 pub struct StorageGcp {
-    pub service_account_key: String,
+    pub service_account_key: Option<String>, // JSON serialized
+    pub service_account_path: Option<String>,
+    pub application_credentials: Option<String>, // path of authorized user or service account
     pub bucket: String,
+    pub skip_signature: Option<bool>,
+    pub options: Option<HashMap<String, String>>, // other `google_*` builder options
     pub description: Option<String>,
 }
 
+// managed identity when no credentials are specified
 #[derive(Debug, Deserialize, Clone)]
 pub struct StorageAzure {
     pub account_name: String,
-    pub access_key: String,
+    pub access_key: Option<String>,
+    pub sas_token: Option<String>,
+    pub bearer_token: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub tenant_id: Option<String>,
+    pub federated_token_file: Option<String>, // workload identity
+    pub msi_endpoint: Option<String>,
+    pub use_azure_cli: Option<bool>,
+    pub use_emulator: Option<bool>, // Azurite
+    pub endpoint: Option<String>,
+    pub allow_http: Option<bool>,
     pub container: String,
+    pub options: Option<HashMap<String, String>>, // other `azure_*` builder options
     pub description: Option<String>,
 }
 
@@ -151,19 +254,69 @@ pub enum Storage {
     Webdav(StorageHttp),
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct CredentialBasic {
+    pub name: String,
+    pub user: String,
+    pub password: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CredentialBearer {
+    pub name: String,
+    pub token: String,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CredentialOAuth2 {
+    pub name: String,
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Option<Vec<String>>,
+    pub audience: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Credential {
+    Basic(CredentialBasic),
+    Bearer(CredentialBearer),
+    OAuth2(CredentialOAuth2),
+}
+
+impl Credential {
+    #[must_use]
+    pub fn name(&self) -> &str {
+        match self {
+            Credential::Basic(basic) => &basic.name,
+            Credential::Bearer(bearer) => &bearer.name,
+            Credential::OAuth2(oauth2) => &oauth2.name,
+        }
+    }
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Settings {
     pub server: Server,
     pub session: Session,
+    pub http_client: HttpClient,
+    pub cache: Cache,
     pub log: Log,
-    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    #[cfg(feature = "database")]
     pub databases: Option<Vec<Database>>,
     pub storages: Option<Vec<Storage>>,
-    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    pub credentials: Option<Vec<Credential>>,
+    #[cfg(feature = "database")]
     #[serde(skip)]
     pub database_pool_manager: database_manager::DatabaseManager,
     #[serde(skip)]
     pub object_store_manager: credential_manager::ObjectStoreManager,
+    #[serde(skip)]
+    pub http_credential_manager: http_credential_manager::HttpCredentialManager,
 }
 
 pub static LAZY_SETTINGS: OnceCell<Settings> = OnceCell::new();
@@ -208,10 +361,28 @@ impl Settings {
             .unwrap()
             .set_default("server.disable_stateful_features", false)
             .unwrap()
+            .set_default("server.health_check_interval", 30)
+            .unwrap()
             .set_default("session.default_keep_alive", 3600)
             .unwrap()
             .set_default("session.upload_limit_size", 20) // 20MB
             .unwrap()
+            .set_default("http_client.connect_timeout", 10)
+            .unwrap()
+            .set_default("http_client.timeout", 300)
+            .unwrap()
+            .set_default("http_client.max_retries", 3)
+            .unwrap()
+            .set_default("http_client.retry_backoff", 500)
+            .unwrap()
+            .set_default("http_client.max_response_size", 1024) // 1GB
+            .unwrap()
+            .set_default("cache.enabled", false)
+            .unwrap()
+            .set_default("cache.dir", "cache")
+            .unwrap()
+            .set_default("cache.max_size", 10240) // 10GB
+            .unwrap()
             .set_default("log.level", "info")
             .unwrap()
     }
@@ -219,15 +390,15 @@ impl Settings {
     /// ## Errors
     /// Can not initialize object store credentials and external database connection pools.
     pub fn init_global_managers(mut self) -> Result<Self, ConfigError> {
-        #[cfg(any(feature = "postgres", feature = "mysql"))]
+        #[cfg(feature = "database")]
         {
-            self.database_pool_manager =
-                database_manager::DatabaseManager::new_with_config(self.databases.as_ref())
-                    .map_err(|e| {
-                        ConfigError::Message(format!(
-                            "Can not initialize database connection pools: {e}"
-                        ))
-                    })?;
+            self.database_pool_manager = database_manager::DatabaseManager::new_with_config(
+                self.databases.as_ref(),
+                &self.server.data_dir,
+            )
+            .map_err(|e| {
+                ConfigError::Message(format!("Can not initialize database connection pools: {e}"))
+            })?;
         }
 
         self.object_store_manager =
@@ -238,6 +409,14 @@ impl Settings {
                     ))
                 })?;
 
+        self.http_credential_manager =
+            http_credential_manager::HttpCredentialManager::new_with_config(
+                self.credentials.as_ref(),
+            )
+            .map_err(|e| {
+                ConfigError::Message(format!("Can not initialize http credential profiles: {e}"))
+            })?;
+
         Ok(self)
     }
 
@@ -249,9 +428,12 @@ impl Settings {
 
     #[must_use]
     pub fn debug(&self) -> String {
-        let mut result = format!("{:?}, {:?}, {:?}", self.server, self.session, self.log);
+        let mut result = format!(
+            "{:?}, {:?}, {:?}, {:?}, {:?}",
+            self.server, self.session, self.http_client, self.cache, self.log
+        );
 
-        #[cfg(any(feature = "postgres", feature = "mysql"))]
+        #[cfg(feature = "database")]
         {
             let databases: Vec<_> = self
                 .database_pool_manager
@@ -265,6 +447,14 @@ impl Settings {
         let stores: Vec<_> = self.object_store_manager.stores.keys().cloned().collect();
         result = format!("{result}, Storage {{ stores: {stores:?} }}");
 
+        let profiles: Vec<_> = self
+            .http_credential_manager
+            .profiles
+            .keys()
+            .cloned()
+            .collect();
+        result = format!("{result}, Credential {{ profiles: {profiles:?} }}");
+
         result
     }
 }